@@ -570,6 +570,10 @@ impl<'v> ToViper<'v, viper::Expr<'v>> for expression::ContainerOp {
             expression::ContainerOpKind::SeqIndex => ast.seq_index(arg(0), arg(1)),
             expression::ContainerOpKind::SeqConcat => ast.seq_append(arg(0), arg(1)),
             expression::ContainerOpKind::SeqLen => ast.seq_length(arg(0)),
+            expression::ContainerOpKind::SeqTake => ast.seq_take(arg(0), arg(1)),
+            expression::ContainerOpKind::SeqDrop => ast.seq_drop(arg(0), arg(1)),
+            expression::ContainerOpKind::SeqUpdate => ast.seq_update(arg(0), arg(1), arg(2)),
+            expression::ContainerOpKind::SeqContains => ast.seq_contains(arg(0), arg(1)),
             expression::ContainerOpKind::MapEmpty => {
                 let (key_ty, val_ty) = key_value_types();
                 ast.empty_map(key_ty, val_ty)
@@ -578,6 +582,7 @@ impl<'v> ToViper<'v, viper::Expr<'v>> for expression::ContainerOp {
             expression::ContainerOpKind::MapContains => ast.map_contains(arg(0), arg(1)),
             expression::ContainerOpKind::MapLookup => ast.lookup_map(arg(0), arg(1)),
             expression::ContainerOpKind::MapLen => ast.map_len(arg(0)),
+            expression::ContainerOpKind::MapDomain => ast.map_domain(arg(0)),
             expression::ContainerOpKind::SetEmpty => ast.empty_set(element_type()),
             expression::ContainerOpKind::SetConstructor => {
                 let elements = args();
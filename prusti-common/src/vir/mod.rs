@@ -12,6 +12,7 @@ pub use low_to_viper::Context as LoweringContext;
 pub use vir::{high as vir_high, legacy::*, polymorphic as polymorphic_vir};
 
 pub mod fixes;
+pub mod obligation_splitting;
 pub mod optimizations;
 mod to_viper;
 mod low_to_viper;
@@ -0,0 +1,254 @@
+// © 2023, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Splitting of a program into one program per proof obligation, enabled by
+//! the `split_obligations` configuration flag.
+//!
+//! A proof obligation is the group of the assertions of a method that have
+//! the same position, e.g. a postcondition, the preservation of a loop
+//! invariant or the absence of a panic. Each program checks one obligation
+//! and assumes the assertions of the others. If all the programs verify, so
+//! does the original one: the first assertion that fails in an execution of
+//! the original program also fails in the program that checks it, since all
+//! the assertions before it hold.
+//!
+//! The checks that are not assertions (e.g. the permissions required by a
+//! `fold` or an `exhale`) stay in every program, so the same error can be
+//! reported by several programs of a method.
+
+use super::program::Program;
+use rustc_hash::FxHashSet;
+use vir::{
+    legacy::{self, ExprIterator, ExprWalker},
+    low::{
+        self,
+        ast::statement::{self, Statement},
+    },
+};
+
+/// Split a program that encodes a single method into one program per proof
+/// obligation of the method, named `<program>_obligation_<index>`. Other
+/// programs, and programs of methods with at most one obligation, are
+/// returned unchanged.
+pub fn split_obligations(program: Program) -> Vec<Program> {
+    match program {
+        Program::Legacy(program) if program.methods.len() == 1 => {
+            let obligations = legacy_obligations(&program.methods[0]);
+            if obligations.len() <= 1 {
+                return vec![Program::Legacy(program)];
+            }
+            obligations
+                .into_iter()
+                .enumerate()
+                .map(|(index, checked)| {
+                    let mut part = program.clone();
+                    part.name = format!("{}_obligation_{index}", program.name);
+                    for block in &mut part.methods[0].basic_blocks {
+                        block.stmts =
+                            assume_legacy_obligations(std::mem::take(&mut block.stmts), checked);
+                    }
+                    Program::Legacy(part)
+                })
+                .collect()
+        }
+        Program::Low(program) if program.procedures.len() == 1 => {
+            let obligations = low_obligations(&program.procedures[0]);
+            if obligations.len() <= 1 {
+                return vec![Program::Low(program)];
+            }
+            obligations
+                .into_iter()
+                .enumerate()
+                .map(|(index, checked)| {
+                    let mut part = program.clone();
+                    part.name = format!("{}_obligation_{index}", program.name);
+                    for block in &mut part.procedures[0].basic_blocks {
+                        block.statements =
+                            assume_low_obligations(std::mem::take(&mut block.statements), checked);
+                    }
+                    Program::Low(part)
+                })
+                .collect()
+        }
+        program => vec![program],
+    }
+}
+
+/// The position ids of the obligations of `method`, in the order in which
+/// they first appear.
+fn legacy_obligations(method: &legacy::CfgMethod) -> Vec<u64> {
+    fn collect(stmts: &[legacy::Stmt], seen: &mut FxHashSet<u64>, obligations: &mut Vec<u64>) {
+        for stmt in stmts {
+            match stmt {
+                legacy::Stmt::Assert(_, pos) | legacy::Stmt::Exhale(_, pos) => {
+                    if !pos.is_default() && seen.insert(pos.id()) {
+                        obligations.push(pos.id());
+                    }
+                }
+                legacy::Stmt::If(_, then_stmts, else_stmts) => {
+                    collect(then_stmts, seen, obligations);
+                    collect(else_stmts, seen, obligations);
+                }
+                _ => {}
+            }
+        }
+    }
+    let mut seen = FxHashSet::default();
+    let mut obligations = vec![];
+    for block in &method.basic_blocks {
+        collect(&block.stmts, &mut seen, &mut obligations);
+    }
+    obligations
+}
+
+/// Assume the assertions of the obligations other than `checked`. Since
+/// only pure assertions can be assumed, the permissions required by an
+/// `assert` or an `exhale` are still checked.
+fn assume_legacy_obligations(stmts: Vec<legacy::Stmt>, checked: u64) -> Vec<legacy::Stmt> {
+    let mut result = Vec::with_capacity(stmts.len());
+    for stmt in stmts {
+        match stmt {
+            legacy::Stmt::Assert(expr, pos) if !pos.is_default() && pos.id() != checked => {
+                if is_assumable(&expr) {
+                    result.push(legacy::Stmt::Inhale(expr));
+                } else {
+                    result.extend(assumable_conjuncts(&expr).map(legacy::Stmt::Inhale));
+                    result.push(legacy::Stmt::Assert(expr, pos));
+                }
+            }
+            legacy::Stmt::Exhale(expr, pos) if !pos.is_default() && pos.id() != checked => {
+                result.extend(assumable_conjuncts(&expr).map(legacy::Stmt::Inhale));
+                result.push(legacy::Stmt::Exhale(expr, pos));
+            }
+            legacy::Stmt::If(guard, then_stmts, else_stmts) => {
+                result.push(legacy::Stmt::If(
+                    guard,
+                    assume_legacy_obligations(then_stmts, checked),
+                    assume_legacy_obligations(else_stmts, checked),
+                ));
+            }
+            stmt => result.push(stmt),
+        }
+    }
+    result
+}
+
+/// Whether `expr` can be inhaled instead of asserted, i.e. it is pure and
+/// means the same when inhaled and when exhaled.
+fn is_assumable(expr: &legacy::Expr) -> bool {
+    struct ResourceFinder {
+        found: bool,
+    }
+    impl ExprWalker for ResourceFinder {
+        fn walk_predicate_access_predicate(
+            &mut self,
+            _name: &str,
+            _arg: &legacy::Expr,
+            _perm_amount: legacy::PermAmount,
+            _pos: &legacy::Position,
+        ) {
+            self.found = true;
+        }
+        fn walk_field_access_predicate(
+            &mut self,
+            _receiver: &legacy::Expr,
+            _perm_amount: legacy::PermAmount,
+            _pos: &legacy::Position,
+        ) {
+            self.found = true;
+        }
+        fn walk_magic_wand(
+            &mut self,
+            _lhs: &legacy::Expr,
+            _rhs: &legacy::Expr,
+            _borrow: &Option<legacy::Borrow>,
+            _pos: &legacy::Position,
+        ) {
+            self.found = true;
+        }
+        fn walk_inhale_exhale(
+            &mut self,
+            _inhale_expr: &legacy::Expr,
+            _exhale_expr: &legacy::Expr,
+            _pos: &legacy::Position,
+        ) {
+            self.found = true;
+        }
+    }
+    let mut finder = ResourceFinder { found: false };
+    finder.walk(expr);
+    !finder.found
+}
+
+/// The conjunction of the top-level conjuncts of `expr` that can be assumed,
+/// if any.
+fn assumable_conjuncts(expr: &legacy::Expr) -> Option<legacy::Expr> {
+    fn collect(expr: &legacy::Expr, conjuncts: &mut Vec<legacy::Expr>) {
+        match expr {
+            legacy::Expr::BinOp(legacy::BinaryOpKind::And, left, right, _) => {
+                collect(left, conjuncts);
+                collect(right, conjuncts);
+            }
+            _ if is_assumable(expr) => conjuncts.push(expr.clone()),
+            _ => {}
+        }
+    }
+    let mut conjuncts = vec![];
+    collect(expr, &mut conjuncts);
+    (!conjuncts.is_empty()).then(|| conjuncts.into_iter().conjoin())
+}
+
+/// The position ids of the obligations of `procedure`, in the order in which
+/// they first appear. Only assertions are split, because the `exhale`
+/// statements of the core proof encoding mostly transfer permissions.
+fn low_obligations(procedure: &low::ProcedureDecl) -> Vec<u64> {
+    fn collect(statements: &[Statement], seen: &mut FxHashSet<u64>, obligations: &mut Vec<u64>) {
+        for statement in statements {
+            match statement {
+                Statement::Assert(assert) => {
+                    if !assert.position.is_default() && seen.insert(assert.position.id()) {
+                        obligations.push(assert.position.id());
+                    }
+                }
+                Statement::Conditional(conditional) => {
+                    collect(&conditional.then_branch, seen, obligations);
+                    collect(&conditional.else_branch, seen, obligations);
+                }
+                _ => {}
+            }
+        }
+    }
+    let mut seen = FxHashSet::default();
+    let mut obligations = vec![];
+    for block in &procedure.basic_blocks {
+        collect(&block.statements, &mut seen, &mut obligations);
+    }
+    obligations
+}
+
+/// Assume the assertions of the obligations other than `checked`, which are
+/// all pure.
+fn assume_low_obligations(statements: Vec<Statement>, checked: u64) -> Vec<Statement> {
+    statements
+        .into_iter()
+        .map(|statement| match statement {
+            Statement::Assert(assert)
+                if !assert.position.is_default() && assert.position.id() != checked =>
+            {
+                Statement::Assume(statement::Assume {
+                    expression: assert.expression,
+                    position: assert.position,
+                })
+            }
+            Statement::Conditional(conditional) => Statement::Conditional(statement::Conditional {
+                then_branch: assume_low_obligations(conditional.then_branch, checked),
+                else_branch: assume_low_obligations(conditional.else_branch, checked),
+                ..conditional
+            }),
+            statement => statement,
+        })
+        .collect()
+}
@@ -29,6 +29,41 @@ impl Program {
     pub fn get_name_with_check_mode(&self) -> String {
         format!("{}-{}", self.get_name(), self.get_check_mode())
     }
+    /// The number of declarations of the program and of statements in the
+    /// bodies of its methods, a rough measure of its size.
+    pub fn get_size(&self) -> usize {
+        match self {
+            Program::Legacy(program) => {
+                let statements: usize = program
+                    .methods
+                    .iter()
+                    .flat_map(|method| &method.basic_blocks)
+                    .map(|block| block.stmts.len())
+                    .sum();
+                program.domains.len()
+                    + program.fields.len()
+                    + program.builtin_methods.len()
+                    + program.methods.len()
+                    + program.functions.len()
+                    + program.viper_predicates.len()
+                    + statements
+            }
+            Program::Low(program) => {
+                let statements: usize = program
+                    .procedures
+                    .iter()
+                    .flat_map(|procedure| &procedure.basic_blocks)
+                    .map(|block| block.statements.len())
+                    .sum();
+                program.domains.len()
+                    + program.predicates.len()
+                    + program.functions.len()
+                    + program.methods.len()
+                    + program.procedures.len()
+                    + statements
+            }
+        }
+    }
 }
 
 impl<'v> ToViper<'v, viper::Program<'v>> for Program {
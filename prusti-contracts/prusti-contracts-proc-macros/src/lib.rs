@@ -1,10 +1,10 @@
-#![cfg_attr(not(feature = "prusti"), no_std)]
+#![cfg_attr(not(any(feature = "prusti", feature = "runtime_checks")), no_std)]
 use proc_macro::TokenStream;
 
 // -----------------------
 // --- PRUSTI DISABLED ---
 
-#[cfg(not(feature = "prusti"))]
+#[cfg(not(any(feature = "prusti", feature = "runtime_checks")))]
 #[proc_macro_attribute]
 pub fn requires(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
     tokens
@@ -16,12 +16,24 @@ pub fn invariant(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
     tokens
 }
 
-#[cfg(not(feature = "prusti"))]
+#[cfg(not(any(feature = "prusti", feature = "runtime_checks")))]
 #[proc_macro_attribute]
 pub fn ensures(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
     tokens
 }
 
+#[cfg(not(any(feature = "prusti", feature = "runtime_checks")))]
+#[proc_macro_attribute]
+pub fn panics_if(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    tokens
+}
+
+#[cfg(not(feature = "prusti"))]
+#[proc_macro_attribute]
+pub fn ensures_on_panic(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    tokens
+}
+
 #[cfg(not(feature = "prusti"))]
 #[proc_macro_attribute]
 pub fn after_expiry(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
@@ -52,13 +64,13 @@ pub fn verified(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
     tokens
 }
 
-#[cfg(not(feature = "prusti"))]
+#[cfg(not(any(feature = "prusti", feature = "runtime_checks")))]
 #[proc_macro]
 pub fn body_invariant(_tokens: TokenStream) -> TokenStream {
     TokenStream::new()
 }
 
-#[cfg(not(feature = "prusti"))]
+#[cfg(not(any(feature = "prusti", feature = "runtime_checks")))]
 #[proc_macro]
 pub fn prusti_assert(_tokens: TokenStream) -> TokenStream {
     TokenStream::new()
@@ -130,11 +142,76 @@ pub fn body_variant(_tokens: TokenStream) -> TokenStream {
     TokenStream::new()
 }
 
+#[cfg(not(any(feature = "prusti", feature = "runtime_checks")))]
+#[proc_macro_attribute]
+pub fn contract_tests(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    tokens
+}
+
+// ------------------------------
+// --- RUNTIME CHECKS ENABLED ---
+
+#[cfg(all(feature = "runtime_checks", not(feature = "prusti")))]
+use prusti_specs::runtime_checks;
+
+#[cfg(all(feature = "runtime_checks", not(feature = "prusti")))]
+#[proc_macro_attribute]
+pub fn requires(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    runtime_checks::rewrite_prusti_attributes(
+        SpecAttributeKind::Requires,
+        attr.into(),
+        tokens.into(),
+    )
+    .into()
+}
+
+#[cfg(all(feature = "runtime_checks", not(feature = "prusti")))]
+#[proc_macro_attribute]
+pub fn ensures(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    runtime_checks::rewrite_prusti_attributes(
+        SpecAttributeKind::Ensures,
+        attr.into(),
+        tokens.into(),
+    )
+    .into()
+}
+
+#[cfg(all(feature = "runtime_checks", not(feature = "prusti")))]
+#[proc_macro_attribute]
+pub fn panics_if(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    runtime_checks::rewrite_prusti_attributes(
+        SpecAttributeKind::PanicsIf,
+        attr.into(),
+        tokens.into(),
+    )
+    .into()
+}
+
+#[cfg(all(feature = "runtime_checks", not(feature = "prusti")))]
+#[proc_macro]
+pub fn body_invariant(tokens: TokenStream) -> TokenStream {
+    runtime_checks::body_invariant(tokens.into()).into()
+}
+
+#[cfg(all(feature = "runtime_checks", not(feature = "prusti")))]
+#[proc_macro]
+pub fn prusti_assert(tokens: TokenStream) -> TokenStream {
+    runtime_checks::prusti_assertion(tokens.into()).into()
+}
+
+#[cfg(all(feature = "runtime_checks", not(feature = "prusti")))]
+#[proc_macro_attribute]
+pub fn contract_tests(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    runtime_checks::contract_tests(attr.into(), tokens.into()).into()
+}
+
 // ----------------------
 // --- PRUSTI ENABLED ---
 
 #[cfg(feature = "prusti")]
-use prusti_specs::{rewrite_prusti_attributes, SpecAttributeKind};
+use prusti_specs::rewrite_prusti_attributes;
+#[cfg(any(feature = "prusti", feature = "runtime_checks"))]
+use prusti_specs::SpecAttributeKind;
 
 #[cfg(feature = "prusti")]
 #[proc_macro_attribute]
@@ -148,6 +225,23 @@ pub fn ensures(attr: TokenStream, tokens: TokenStream) -> TokenStream {
     rewrite_prusti_attributes(SpecAttributeKind::Ensures, attr.into(), tokens.into()).into()
 }
 
+#[cfg(feature = "prusti")]
+#[proc_macro_attribute]
+pub fn panics_if(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    rewrite_prusti_attributes(SpecAttributeKind::PanicsIf, attr.into(), tokens.into()).into()
+}
+
+#[cfg(feature = "prusti")]
+#[proc_macro_attribute]
+pub fn ensures_on_panic(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    rewrite_prusti_attributes(
+        SpecAttributeKind::EnsuresOnPanic,
+        attr.into(),
+        tokens.into(),
+    )
+    .into()
+}
+
 #[cfg(feature = "prusti")]
 #[proc_macro_attribute]
 pub fn after_expiry(attr: TokenStream, tokens: TokenStream) -> TokenStream {
@@ -273,5 +367,13 @@ pub fn body_variant(tokens: TokenStream) -> TokenStream {
     prusti_specs::body_variant(tokens.into()).into()
 }
 
+// The contracts are verified instead of tested.
+#[cfg(feature = "prusti")]
+#[proc_macro_attribute]
+pub fn contract_tests(_attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    tokens
+}
+
 // Ensure that you've also crated a transparent `#[cfg(not(feature = "prusti"))]`
-// version of your new macro above!
+// version of your new macro above! If the macro is a specification that can be
+// executed, also consider adding a `runtime_checks` version.
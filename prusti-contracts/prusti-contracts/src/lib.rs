@@ -6,6 +6,12 @@ pub use prusti_contracts_proc_macros::requires;
 /// A macro for writing a postcondition on a function.
 pub use prusti_contracts_proc_macros::ensures;
 
+/// A macro for writing the condition under which a function panics.
+pub use prusti_contracts_proc_macros::panics_if;
+
+/// A macro for writing a postcondition that holds when a function panics.
+pub use prusti_contracts_proc_macros::ensures_on_panic;
+
 /// A macro for writing a pledge on a function.
 pub use prusti_contracts_proc_macros::after_expiry;
 
@@ -66,6 +72,9 @@ pub use prusti_contracts_proc_macros::terminates;
 /// A macro to annotate body variant of a loop to prove termination
 pub use prusti_contracts_proc_macros::body_variant;
 
+/// A macro for generating property tests from the contracts of a function
+pub use prusti_contracts_proc_macros::contract_tests;
+
 #[cfg(not(feature = "prusti"))]
 mod private {
     use core::marker::PhantomData;
@@ -110,6 +119,26 @@ mod private {
         _val_phantom: PhantomData<V>,
     }
 
+    /// A set type
+    ///
+    /// Only supported with `UNSAFE_CORE_PROOF`; counterexamples show set
+    /// values as unknown.
+    #[non_exhaustive]
+    #[derive(PartialEq, Eq, Copy, Clone)]
+    pub struct Set<T> {
+        _phantom: PhantomData<T>,
+    }
+
+    /// A multiset type
+    ///
+    /// Only supported with `UNSAFE_CORE_PROOF`; counterexamples show multiset
+    /// values as unknown.
+    #[non_exhaustive]
+    #[derive(PartialEq, Eq, Copy, Clone)]
+    pub struct Multiset<T> {
+        _phantom: PhantomData<T>,
+    }
+
     /// a mathematical (unbounded) integer type
     /// it should not be constructed from running rust code, hence the private unit inside
     pub struct Int(());
@@ -124,6 +153,9 @@ mod private {
 #[cfg(feature = "prusti")]
 pub mod core_spec;
 
+#[cfg(all(feature = "runtime_checks", not(feature = "prusti")))]
+pub mod testing;
+
 #[cfg(feature = "prusti")]
 mod private {
     use core::{marker::PhantomData, ops::*};
@@ -223,6 +255,46 @@ mod private {
         pub fn len(self) -> Int {
             panic!()
         }
+        pub fn contains(self, _element: T) -> bool {
+            panic!()
+        }
+        /// The first `_count` elements of the sequence.
+        pub fn take(self, _count: usize) -> Self {
+            panic!()
+        }
+        /// The sequence without its first `_count` elements.
+        pub fn drop(self, _count: usize) -> Self {
+            panic!()
+        }
+        /// The elements from index `_lo` (inclusive) to index `_hi` (exclusive).
+        pub fn slice(self, _lo: usize, _hi: usize) -> Self {
+            panic!()
+        }
+        /// The sequence with the element at `_index` replaced by `_element`.
+        pub fn update(self, _index: usize, _element: T) -> Self {
+            panic!()
+        }
+    }
+
+    /// A view of a collection as a sequence, for use in specifications.
+    ///
+    /// Only supported with `UNSAFE_CORE_PROOF`. There is no implementation
+    /// for `Vec`: the unsafe core proof sees a vector only through its raw
+    /// buffer, whose contents it cannot view as a sequence.
+    pub trait ToSeq<T: Copy> {
+        fn to_seq(&self) -> Seq<T>;
+    }
+
+    impl<T: Copy> ToSeq<T> for [T] {
+        fn to_seq(&self) -> Seq<T> {
+            panic!()
+        }
+    }
+
+    impl<T: Copy, const N: usize> ToSeq<T> for [T; N] {
+        fn to_seq(&self) -> Seq<T> {
+            panic!()
+        }
     }
 
     #[macro_export]
@@ -279,6 +351,13 @@ mod private {
         pub fn contains(self, _key: K) -> bool {
             panic!()
         }
+        /// The set of the keys of the map.
+        pub fn domain(self) -> Set<K>
+        where
+            K: Copy,
+        {
+            panic!()
+        }
     }
 
     #[macro_export]
@@ -301,6 +380,97 @@ mod private {
         }
     }
 
+    /// A set type
+    ///
+    /// Only supported with `UNSAFE_CORE_PROOF`; counterexamples show set
+    /// values as unknown.
+    #[non_exhaustive]
+    #[derive(PartialEq, Eq, Copy, Clone)]
+    pub struct Set<T: Copy> {
+        _phantom: PhantomData<T>,
+    }
+
+    impl<T: Copy> Set<T> {
+        pub fn empty() -> Self {
+            panic!()
+        }
+        pub fn single(_: T) -> Self {
+            panic!()
+        }
+        pub fn union(self, _: Self) -> Self {
+            panic!()
+        }
+        pub fn intersection(self, _: Self) -> Self {
+            panic!()
+        }
+        pub fn difference(self, _: Self) -> Self {
+            panic!()
+        }
+        pub fn contains(self, _element: T) -> bool {
+            panic!()
+        }
+        pub fn len(self) -> Int {
+            panic!()
+        }
+    }
+
+    #[macro_export]
+    macro_rules! set {
+        ($($val:expr),*) => {
+            $crate::Set::empty()
+            $(
+                .union($crate::Set::single($val))
+            )*
+        };
+    }
+
+    /// A multiset type
+    ///
+    /// Only supported with `UNSAFE_CORE_PROOF`; counterexamples show multiset
+    /// values as unknown.
+    #[non_exhaustive]
+    #[derive(PartialEq, Eq, Copy, Clone)]
+    pub struct Multiset<T: Copy> {
+        _phantom: PhantomData<T>,
+    }
+
+    impl<T: Copy> Multiset<T> {
+        pub fn empty() -> Self {
+            panic!()
+        }
+        pub fn single(_: T) -> Self {
+            panic!()
+        }
+        pub fn union(self, _: Self) -> Self {
+            panic!()
+        }
+        pub fn intersection(self, _: Self) -> Self {
+            panic!()
+        }
+        pub fn difference(self, _: Self) -> Self {
+            panic!()
+        }
+        pub fn contains(self, _element: T) -> bool {
+            panic!()
+        }
+        pub fn count(self, _element: T) -> Int {
+            panic!()
+        }
+        pub fn len(self) -> Int {
+            panic!()
+        }
+    }
+
+    #[macro_export]
+    macro_rules! multiset {
+        ($($val:expr),*) => {
+            $crate::Multiset::empty()
+            $(
+                .union($crate::Multiset::single($val))
+            )*
+        };
+    }
+
     #[non_exhaustive]
     #[derive(PartialEq, Eq, Copy, Clone)]
     pub struct Ghost<T> {
@@ -0,0 +1,134 @@
+//! Generation of the inputs of the property tests generated from the
+//! contracts of a function with `#[contract_tests]`.
+
+extern crate alloc;
+
+pub use alloc::{boxed::Box, string::String, vec::Vec};
+
+/// The maximal length of the generated collections.
+const MAX_LEN: u64 = 8;
+
+/// A small deterministic pseudo-random number generator (SplitMix64).
+pub struct TestRng {
+    state: u64,
+}
+
+impl TestRng {
+    pub fn new(seed: u64) -> Self {
+        TestRng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..bound`, which must not be zero.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+}
+
+/// Types whose values can be generated as the arguments of a contract test.
+pub trait Arbitrary: Sized {
+    fn arbitrary(rng: &mut TestRng) -> Self;
+}
+
+macro_rules! impl_arbitrary_for_integers {
+    ($($ty:ty),*) => {
+        $(
+            impl Arbitrary for $ty {
+                /// Small values and the extreme values are generated more
+                /// often, since they are more likely to satisfy
+                /// preconditions and to uncover bugs.
+                fn arbitrary(rng: &mut TestRng) -> Self {
+                    match rng.below(4) {
+                        0 => [0, 1, <$ty>::MIN, <$ty>::MAX][rng.below(4) as usize],
+                        1 | 2 if <$ty>::MIN == 0 => rng.below(17) as $ty,
+                        1 | 2 => (rng.below(33) as i8 - 16) as $ty,
+                        _ => ((u128::from(rng.next_u64()) << 64) | u128::from(rng.next_u64())) as $ty,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_arbitrary_for_integers!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl Arbitrary for bool {
+    fn arbitrary(rng: &mut TestRng) -> Self {
+        rng.below(2) == 1
+    }
+}
+
+impl Arbitrary for char {
+    fn arbitrary(rng: &mut TestRng) -> Self {
+        if rng.below(4) == 0 {
+            loop {
+                if let Some(c) = char::from_u32(rng.below(0x11_0000) as u32) {
+                    return c;
+                }
+            }
+        } else {
+            (b' ' + rng.below(95) as u8) as char
+        }
+    }
+}
+
+impl Arbitrary for () {
+    fn arbitrary(_rng: &mut TestRng) -> Self {}
+}
+
+macro_rules! impl_arbitrary_for_tuples {
+    ($(($($name:ident),*)),*) => {
+        $(
+            impl<$($name: Arbitrary),*> Arbitrary for ($($name,)*) {
+                fn arbitrary(rng: &mut TestRng) -> Self {
+                    ($($name::arbitrary(rng),)*)
+                }
+            }
+        )*
+    };
+}
+
+impl_arbitrary_for_tuples!((A), (A, B), (A, B, C), (A, B, C, D));
+
+impl<T: Arbitrary, const N: usize> Arbitrary for [T; N] {
+    fn arbitrary(rng: &mut TestRng) -> Self {
+        core::array::from_fn(|_| T::arbitrary(rng))
+    }
+}
+
+impl<T: Arbitrary> Arbitrary for Option<T> {
+    fn arbitrary(rng: &mut TestRng) -> Self {
+        if rng.below(4) == 0 {
+            None
+        } else {
+            Some(T::arbitrary(rng))
+        }
+    }
+}
+
+impl<T: Arbitrary> Arbitrary for Box<T> {
+    fn arbitrary(rng: &mut TestRng) -> Self {
+        Box::new(T::arbitrary(rng))
+    }
+}
+
+impl<T: Arbitrary> Arbitrary for Vec<T> {
+    fn arbitrary(rng: &mut TestRng) -> Self {
+        let len = rng.below(MAX_LEN + 1);
+        (0..len).map(|_| T::arbitrary(rng)).collect()
+    }
+}
+
+impl Arbitrary for String {
+    fn arbitrary(rng: &mut TestRng) -> Self {
+        let len = rng.below(MAX_LEN + 1);
+        (0..len).map(|_| char::arbitrary(rng)).collect()
+    }
+}
@@ -0,0 +1,236 @@
+//! Generation of property tests from the contracts of a function, requested
+//! with the `#[contract_tests]` attribute when the `runtime_checks` feature
+//! of `prusti-contracts` is enabled.
+//!
+//! The generated test draws the arguments of the function from
+//! `prusti_contracts::testing::Arbitrary`, discards the ones that violate a
+//! precondition or satisfy the panic condition (rejection sampling) and
+//! checks the postconditions after each call, so that even trusted functions
+//! are tested against their contracts.
+
+use crate::{
+    runtime_checks::{executable_spec, generate_check},
+    untyped, SpecAttributeKind,
+};
+use proc_macro2::{TokenStream, TokenTree};
+use quote::{format_ident, quote_spanned};
+use syn::{parse_quote_spanned, spanned::Spanned};
+
+/// The number of test cases if the attribute does not specify it.
+const DEFAULT_CASES: u64 = 256;
+
+/// How many inputs are generated on average per test case before the test
+/// gives up finding inputs that satisfy the preconditions.
+const MAX_ATTEMPTS_PER_CASE: u64 = 100;
+
+/// Remove the `#[contract_tests]` attribute of `item`, returning its
+/// arguments.
+pub(crate) fn take_attribute(item: &mut untyped::AnyFnItem) -> Option<TokenStream> {
+    let attrs = item.attrs_mut();
+    let index = attrs.iter().position(|attr| {
+        let segments = &attr.path.segments;
+        (segments.len() == 1 || (segments.len() == 2 && segments[0].ident == "prusti_contracts"))
+            && segments.last().unwrap().ident == "contract_tests"
+    })?;
+    let attr = attrs.remove(index);
+    // Drop the surrounding parentheses, like for the tokens passed to the
+    // macro.
+    match attr.tokens.into_iter().next() {
+        Some(TokenTree::Group(group)) => Some(group.stream()),
+        _ => Some(TokenStream::new()),
+    }
+}
+
+/// Generate a test that calls the function `item` with random arguments
+/// satisfying the preconditions in `prusti_attributes` and checks its
+/// postconditions. `attr` can set the number of test cases with
+/// `cases = <number>`.
+pub(crate) fn generate_contract_test(
+    item: &untyped::AnyFnItem,
+    attr: TokenStream,
+    prusti_attributes: &[(SpecAttributeKind, TokenStream)],
+) -> syn::Result<TokenStream> {
+    let cases = parse_cases(attr)?;
+    let untyped::AnyFnItem::Fn(syn::ItemFn { sig, .. }) = item else {
+        return Err(syn::Error::new(
+            item.span(),
+            "contract tests can be generated only for free functions",
+        ));
+    };
+    if !sig.generics.params.is_empty()
+        || sig.asyncness.is_some()
+        || sig.unsafety.is_some()
+        || sig.variadic.is_some()
+    {
+        return Err(syn::Error::new(
+            sig.span(),
+            "contract tests can be generated only for functions that are not generic, \
+             `async` or `unsafe`",
+        ));
+    }
+    let span = sig.span();
+    let mut inputs = vec![];
+    let mut args = vec![];
+    for input in &sig.inputs {
+        let syn::FnArg::Typed(syn::PatType { pat, ty, .. }) = input else {
+            unreachable!("receiver of a free function");
+        };
+        let syn::Pat::Ident(syn::PatIdent { ident, .. }) = &**pat else {
+            return Err(syn::Error::new(
+                pat.span(),
+                "the arguments of functions with contract tests must be identifiers",
+            ));
+        };
+        let input_span = input.span();
+        if let syn::Type::Reference(syn::TypeReference {
+            mutability, elem, ..
+        }) = &**ty
+        {
+            // The referenced value lives in a variable of the test, and
+            // unsized values are generated in owned containers.
+            let value = format_ident!("prusti_value_{}", ident);
+            let (value_ty, borrowed) = match &**elem {
+                syn::Type::Slice(syn::TypeSlice { elem, .. }) => (
+                    quote_spanned! {input_span=> ::prusti_contracts::testing::Vec<#elem> },
+                    quote_spanned! {input_span=> #value[..] },
+                ),
+                syn::Type::Path(syn::TypePath { qself: None, path }) if path.is_ident("str") => (
+                    quote_spanned! {input_span=> ::prusti_contracts::testing::String },
+                    quote_spanned! {input_span=> #value[..] },
+                ),
+                elem => (
+                    quote_spanned! {input_span=> #elem },
+                    quote_spanned! {input_span=> #value },
+                ),
+            };
+            inputs.push(quote_spanned! {input_span=>
+                let mut #value: #value_ty = ::prusti_contracts::testing::Arbitrary::arbitrary(&mut rng);
+                let #ident = &#mutability #borrowed;
+            });
+            args.push(quote_spanned! {input_span=> #ident });
+        } else {
+            // The arguments passed by value are cloned, so that the
+            // postconditions can refer to them after the call.
+            inputs.push(quote_spanned! {input_span=>
+                let #ident: #ty = ::prusti_contracts::testing::Arbitrary::arbitrary(&mut rng);
+            });
+            args.push(quote_spanned! {input_span=> ::core::clone::Clone::clone(&#ident) });
+        }
+    }
+    let mut preconditions = vec![];
+    let mut postcondition_checks = vec![];
+    let mut old_values = Some(vec![]);
+    for (attr_kind, attr_tokens) in prusti_attributes {
+        match attr_kind {
+            SpecAttributeKind::Requires => {
+                preconditions.push(executable_spec(attr_tokens.clone(), &mut None)?)
+            }
+            // The inputs that make the function panic are not tested.
+            SpecAttributeKind::PanicsIf => {
+                let condition = executable_spec(attr_tokens.clone(), &mut None)?;
+                preconditions.push(parse_quote_spanned! {condition.span()=> !(#condition) })
+            }
+            SpecAttributeKind::Ensures => postcondition_checks.push(generate_check(
+                attr_tokens.clone(),
+                "postcondition",
+                &mut old_values,
+            )?),
+            _ => {}
+        }
+    }
+    let (old_names, old_exprs): (Vec<_>, Vec<_>) = old_values.unwrap().into_iter().unzip();
+    let name = &sig.ident;
+    let test_name = format_ident!("prusti_contract_test_{}", name);
+    let seed = seed(&name.to_string());
+    let max_attempts = cases * MAX_ATTEMPTS_PER_CASE;
+    let message = format!(
+        "too few of the generated inputs satisfy the preconditions of `{name}` \
+         (at most {max_attempts} inputs are tried)"
+    );
+    Ok(quote_spanned! {span=>
+        #[cfg(test)]
+        #[test]
+        #[allow(
+            unused_mut,
+            unused_variables,
+            unused_parens,
+            unused_comparisons,
+            clippy::let_unit_value
+        )]
+        fn #test_name() {
+            let mut rng = ::prusti_contracts::testing::TestRng::new(#seed);
+            let mut cases = 0;
+            let mut attempts = 0;
+            while cases < #cases {
+                attempts += 1;
+                ::core::assert!(attempts <= #max_attempts, "{}", #message);
+                #(#inputs)*
+                if !(true #(&& (#preconditions))*) {
+                    continue;
+                }
+                cases += 1;
+                #(
+                    let #old_names = ::core::clone::Clone::clone(&(#old_exprs));
+                )*
+                let result = #name(#(#args),*);
+                #(#postcondition_checks)*
+            }
+        }
+    })
+}
+
+fn parse_cases(attr: TokenStream) -> syn::Result<u64> {
+    if attr.is_empty() {
+        return Ok(DEFAULT_CASES);
+    }
+    let arg: syn::MetaNameValue = syn::parse2(attr)?;
+    match &arg.lit {
+        syn::Lit::Int(cases) if arg.path.is_ident("cases") => cases.base10_parse(),
+        _ => Err(syn::Error::new(arg.span(), "expected `cases = <number>`")),
+    }
+}
+
+/// A seed for the inputs of the test of the function `name` (the FNV-1a hash
+/// of the name), so that the inputs of a test are always the same while the
+/// tests of different functions use different inputs.
+fn seed(name: &str) -> u64 {
+    name.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generate(attr: &str, item: &str) -> syn::Result<String> {
+        let mut item: untyped::AnyFnItem = syn::parse_str(item).unwrap();
+        let prusti_attributes = crate::extract_prusti_attributes(&mut item);
+        generate_contract_test(&item, attr.parse().unwrap(), &prusti_attributes)
+            .map(|tokens| tokens.to_string())
+    }
+
+    #[test]
+    fn rejection_sampling() {
+        let test = generate(
+            "cases = 10",
+            "#[requires(x < 100)] #[ensures(result == old(v.len()) + x)] fn f(x: usize, v: &mut Vec<u8>) -> usize { 0 }",
+        )
+        .unwrap();
+        assert!(test.contains("fn prusti_contract_test_f ()"));
+        assert!(test.contains("while cases < 10u64"));
+        assert!(test.contains("let mut prusti_value_v : Vec < u8 > = :: prusti_contracts :: testing :: Arbitrary :: arbitrary (& mut rng) ; let v = & mut prusti_value_v ;"));
+        assert!(test.contains("if ! (true && (x < 100)) { continue ; }"));
+        assert!(test.contains(
+            "let prusti_old_value_0 = :: core :: clone :: Clone :: clone (& (v . len ())) ;"
+        ));
+        assert!(test.contains("let result = f (:: core :: clone :: Clone :: clone (& x) , v) ;"));
+    }
+
+    #[test]
+    fn unsupported_functions() {
+        assert!(generate("", "fn f<T>(x: T) {}").is_err());
+        assert!(generate("", "fn f((a, b): (u8, u8)) {}").is_err());
+        assert!(generate("cases", "fn f() {}").is_err());
+    }
+}
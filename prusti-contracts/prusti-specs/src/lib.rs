@@ -10,12 +10,14 @@
 
 #[macro_use]
 mod common;
+mod contract_tests;
 mod extern_spec_rewriter;
 mod type_cond_specs;
 mod parse_closure_macro;
 mod parse_quote_spanned;
 mod predicate;
 mod rewriter;
+pub mod runtime_checks;
 mod span_overrider;
 mod spec_attribute_kind;
 pub mod specifications;
@@ -73,7 +75,9 @@ fn extract_prusti_attributes(
                     | SpecAttributeKind::Ensures
                     | SpecAttributeKind::AfterExpiry
                     | SpecAttributeKind::AssertOnExpiry
-                    | SpecAttributeKind::RefineSpec => {
+                    | SpecAttributeKind::RefineSpec
+                    | SpecAttributeKind::PanicsIf
+                    | SpecAttributeKind::EnsuresOnPanic => {
                         // We need to drop the surrounding parenthesis to make the
                         // tokens identical to the ones passed by the native procedural
                         // macro call.
@@ -140,6 +144,33 @@ pub fn rewrite_prusti_attributes(
         .to_compile_error();
     }
 
+    // Pure functions are encoded as Viper functions, which cannot panic.
+    if prusti_attributes
+        .iter()
+        .any(|(ak, _)| ak == &SpecAttributeKind::Pure)
+    {
+        if let Some((_, tokens)) = prusti_attributes.iter().find(|(ak, _)| {
+            ak == &SpecAttributeKind::PanicsIf || ak == &SpecAttributeKind::EnsuresOnPanic
+        }) {
+            return syn::Error::new(
+                tokens.span(),
+                "panic specifications are not supported on pure functions",
+            )
+            .to_compile_error();
+        }
+    }
+    if let Some((_, tokens)) = prusti_attributes
+        .iter()
+        .filter(|(ak, _)| ak == &SpecAttributeKind::PanicsIf)
+        .nth(1)
+    {
+        return syn::Error::new(
+            tokens.span(),
+            "a function can have at most one `#[panics_if(..)]` attribute",
+        )
+        .to_compile_error();
+    }
+
     let (generated_spec_items, generated_attributes) =
         handle_result!(generate_spec_and_assertions(prusti_attributes, &item));
 
@@ -171,6 +202,8 @@ fn generate_spec_and_assertions(
             SpecAttributeKind::Verified => generate_for_verified(attr_tokens, item),
             SpecAttributeKind::Terminates => generate_for_terminates(attr_tokens, item),
             SpecAttributeKind::Trusted => generate_for_trusted(attr_tokens, item),
+            SpecAttributeKind::PanicsIf => generate_for_panics_if(attr_tokens, item),
+            SpecAttributeKind::EnsuresOnPanic => generate_for_ensures_on_panic(attr_tokens, item),
             // Predicates are handled separately below; the entry in the SpecAttributeKind enum
             // only exists so we successfully parse it and emit an error in
             // `check_incompatible_attrs`; so we'll never reach here.
@@ -218,6 +251,40 @@ fn generate_for_ensures(attr: TokenStream, item: &untyped::AnyFnItem) -> Generat
     ))
 }
 
+/// Generate spec items and attributes to typecheck and later retrieve "panics_if" annotations.
+fn generate_for_panics_if(attr: TokenStream, item: &untyped::AnyFnItem) -> GeneratedResult {
+    let mut rewriter = rewriter::AstRewriter::new();
+    let spec_id = rewriter.generate_spec_id();
+    let spec_id_str = spec_id.to_string();
+    // The panic condition is evaluated in the pre-state, like a precondition.
+    let spec_item =
+        rewriter.process_assertion(rewriter::SpecItemType::Precondition, spec_id, attr, item)?;
+    Ok((
+        vec![spec_item],
+        vec![parse_quote_spanned! {item.span()=>
+            #[prusti::panics_if_spec_id_ref = #spec_id_str]
+        }],
+    ))
+}
+
+/// Generate spec items and attributes to typecheck and later retrieve "ensures_on_panic"
+/// annotations.
+fn generate_for_ensures_on_panic(attr: TokenStream, item: &untyped::AnyFnItem) -> GeneratedResult {
+    let mut rewriter = rewriter::AstRewriter::new();
+    let spec_id = rewriter.generate_spec_id();
+    let spec_id_str = spec_id.to_string();
+    // A panicking function has no result, so the postcondition on panic is
+    // typechecked like a precondition, which can refer to `old` values.
+    let spec_item =
+        rewriter.process_assertion(rewriter::SpecItemType::Precondition, spec_id, attr, item)?;
+    Ok((
+        vec![spec_item],
+        vec![parse_quote_spanned! {item.span()=>
+            #[prusti::post_on_panic_spec_id_ref = #spec_id_str]
+        }],
+    ))
+}
+
 /// Generate spec items and attributes to typecheck and later retrieve "after_expiry" annotations.
 fn generate_for_after_expiry(attr: TokenStream, item: &untyped::AnyFnItem) -> GeneratedResult {
     let mut rewriter = rewriter::AstRewriter::new();
@@ -880,6 +947,10 @@ fn extract_prusti_attributes_for_types(
                     SpecAttributeKind::Invariant => unreachable!("invariant on type"),
                     SpecAttributeKind::Predicate => unreachable!("predicate on type"),
                     SpecAttributeKind::Terminates => unreachable!("terminates on type"),
+                    SpecAttributeKind::PanicsIf => unreachable!("panics_if on type"),
+                    SpecAttributeKind::EnsuresOnPanic => {
+                        unreachable!("ensures_on_panic on type")
+                    }
                     SpecAttributeKind::Trusted | SpecAttributeKind::Model => {
                         assert!(attr.tokens.is_empty(), "Unexpected shape of an attribute.");
                         attr.tokens
@@ -927,6 +998,8 @@ fn generate_spec_and_assertions_for_types(
             SpecAttributeKind::Invariant => unreachable!(),
             SpecAttributeKind::RefineSpec => unreachable!(),
             SpecAttributeKind::Terminates => unreachable!(),
+            SpecAttributeKind::PanicsIf => unreachable!(),
+            SpecAttributeKind::EnsuresOnPanic => unreachable!(),
             SpecAttributeKind::Trusted => generate_for_trusted_for_types(attr_tokens, item),
             SpecAttributeKind::Model => generate_for_model(attr_tokens, item),
             SpecAttributeKind::PrintCounterexample => {
@@ -0,0 +1,495 @@
+//! Compilation of specifications into runtime checks, used instead of erasing
+//! them when the `runtime_checks` feature of `prusti-contracts` is enabled
+//! and Prusti is not verifying the crate.
+//!
+//! Preconditions are asserted when a function is entered and postconditions
+//! when it returns, with the `old(..)` expressions evaluated (and cloned) at
+//! entry. A function that returns normally although its panic condition
+//! (`#[panics_if(..)]`) held at entry fails the check as well. Quantifiers
+//! are executed as loops over the ranges bounding their variables.
+//! Specifications that cannot be executed (e.g. pledges, type
+//! invariants or termination measures) are ignored.
+
+use crate::{contract_tests, specifications::preparser::parse_prusti, untyped, SpecAttributeKind};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote_spanned, ToTokens};
+use syn::{parse_quote_spanned, spanned::Spanned, visit_mut::VisitMut};
+
+macro_rules! handle_result {
+    ($parse_result: expr) => {
+        match $parse_result {
+            Ok(data) => data,
+            Err(err) => return err.to_compile_error(),
+        }
+    };
+}
+
+/// Rewrite a function such that it checks the pre- and postconditions given
+/// by *all* its specification attributes.
+pub fn rewrite_prusti_attributes(
+    outer_attr_kind: SpecAttributeKind,
+    outer_attr_tokens: TokenStream,
+    item_tokens: TokenStream,
+) -> TokenStream {
+    let mut item: untyped::AnyFnItem = handle_result!(syn::parse2(item_tokens));
+    let mut prusti_attributes = vec![(outer_attr_kind, outer_attr_tokens)];
+    prusti_attributes.extend(crate::extract_prusti_attributes(&mut item));
+    // A `#[contract_tests]` attribute below the specifications sees the item
+    // without them, so its tests are generated here.
+    let contract_test = match contract_tests::take_attribute(&mut item) {
+        Some(attr_tokens) => handle_result!(contract_tests::generate_contract_test(
+            &item,
+            attr_tokens,
+            &prusti_attributes
+        )),
+        None => TokenStream::new(),
+    };
+    handle_result!(insert_contract_checks(&mut item, prusti_attributes));
+    let mut tokens = item.into_token_stream();
+    tokens.extend(contract_test);
+    tokens
+}
+
+/// Keep a function unchanged and generate a property test that calls it with
+/// random inputs satisfying its preconditions and checks its postconditions.
+pub fn contract_tests(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    // The specifications are extracted from a copy of the item, since the
+    // macros of their attributes still have to process it.
+    let mut item: untyped::AnyFnItem = handle_result!(syn::parse2(tokens.clone()));
+    let prusti_attributes = crate::extract_prusti_attributes(&mut item);
+    let contract_test = handle_result!(contract_tests::generate_contract_test(
+        &item,
+        attr,
+        &prusti_attributes
+    ));
+    let mut tokens = tokens;
+    tokens.extend(contract_test);
+    tokens
+}
+
+/// Check a loop body invariant each time it is reached.
+pub fn body_invariant(tokens: TokenStream) -> TokenStream {
+    handle_result!(generate_check(tokens, "loop invariant", &mut None))
+}
+
+/// Check a `prusti_assert!` when it is reached.
+pub fn prusti_assertion(tokens: TokenStream) -> TokenStream {
+    handle_result!(generate_check(tokens, "assertion", &mut None))
+}
+
+fn insert_contract_checks(
+    item: &mut untyped::AnyFnItem,
+    prusti_attributes: Vec<(SpecAttributeKind, TokenStream)>,
+) -> syn::Result<()> {
+    let mut precondition_checks = vec![];
+    let mut postcondition_checks = vec![];
+    let mut old_values = Some(vec![]);
+    for (attr_kind, attr_tokens) in prusti_attributes {
+        match attr_kind {
+            SpecAttributeKind::Requires => {
+                precondition_checks.push(generate_check(attr_tokens, "precondition", &mut None)?)
+            }
+            SpecAttributeKind::Ensures => postcondition_checks.push(generate_check(
+                attr_tokens,
+                "postcondition",
+                &mut old_values,
+            )?),
+            SpecAttributeKind::PanicsIf => {
+                let span = attr_tokens.span();
+                let message = format!(
+                    "the function returned although its panic condition holds: {attr_tokens}"
+                );
+                let condition =
+                    executable_spec(quote_spanned! {span=> old(#attr_tokens) }, &mut old_values)?;
+                postcondition_checks.push(quote_spanned! {span=>
+                    #[allow(unused_parens, unused_comparisons)]
+                    {
+                        ::core::assert!(!(#condition), "{}", #message);
+                    }
+                });
+            }
+            // The other specifications cannot be checked at runtime.
+            _ => {}
+        }
+    }
+    let sig = crate::common::HasSignature::sig(item).clone();
+    // The body is moved into a closure, which cannot be constant or awaited.
+    if (precondition_checks.is_empty() && postcondition_checks.is_empty())
+        || sig.constness.is_some()
+        || sig.asyncness.is_some()
+    {
+        return Ok(());
+    }
+    let block = match item {
+        untyped::AnyFnItem::Fn(item) => &mut item.block,
+        untyped::AnyFnItem::ImplMethod(item) => &mut item.block,
+        untyped::AnyFnItem::TraitMethod(syn::TraitItemMethod {
+            default: Some(block),
+            ..
+        }) => block,
+        _ => return Ok(()),
+    };
+    let span = block.span();
+    // Running the body in a closure makes `return` and `?` exit only the
+    // body, so the postconditions are checked on every path.
+    let return_type = match &sig.output {
+        syn::ReturnType::Type(_, ty) if !contains_impl_trait(ty) => {
+            quote_spanned! {span=> -> #ty }
+        }
+        _ => TokenStream::new(),
+    };
+    let (old_names, old_exprs): (Vec<_>, Vec<_>) = old_values.unwrap().into_iter().unzip();
+    let body = &*block;
+    *block = parse_quote_spanned! {span=>
+        {
+            #(#precondition_checks)*
+            #(
+                #[allow(unused_variables)]
+                let #old_names = ::core::clone::Clone::clone(&(#old_exprs));
+            )*
+            #[allow(clippy::redundant_closure_call, clippy::let_unit_value)]
+            let result = (|| #return_type #body)();
+            #(#postcondition_checks)*
+            result
+        }
+    };
+    Ok(())
+}
+
+/// Generate an assertion that checks the specification in `tokens`. The
+/// `old(..)` expressions are replaced by variables that are added to
+/// `old_values`, or rejected if `old_values` is `None`.
+pub(crate) fn generate_check(
+    tokens: TokenStream,
+    kind: &str,
+    old_values: &mut Option<Vec<(syn::Ident, syn::Expr)>>,
+) -> syn::Result<TokenStream> {
+    let span = tokens.span();
+    let message = format!("the {kind} might not hold: {tokens}");
+    let expr = executable_spec(tokens, old_values)?;
+    Ok(quote_spanned! {span=>
+        #[allow(unused_parens, unused_comparisons)]
+        {
+            ::core::assert!(#expr, "{}", #message);
+        }
+    })
+}
+
+/// Translate the specification in `tokens` into a Rust expression that
+/// evaluates it, replacing `old(..)` expressions as in `generate_check`.
+pub(crate) fn executable_spec(
+    tokens: TokenStream,
+    old_values: &mut Option<Vec<(syn::Ident, syn::Expr)>>,
+) -> syn::Result<syn::Expr> {
+    let mut expr: syn::Expr = syn::parse2(parse_prusti(tokens)?)?;
+    let mut rewriter = ExecutableSpecRewriter {
+        old_values,
+        error: None,
+    };
+    rewriter.visit_expr_mut(&mut expr);
+    match rewriter.error {
+        Some(error) => Err(error),
+        None => Ok(expr),
+    }
+}
+
+fn contains_impl_trait(ty: &syn::Type) -> bool {
+    struct ImplTraitFinder {
+        found: bool,
+    }
+    impl<'ast> syn::visit::Visit<'ast> for ImplTraitFinder {
+        fn visit_type_impl_trait(&mut self, _: &'ast syn::TypeImplTrait) {
+            self.found = true;
+        }
+    }
+    let mut finder = ImplTraitFinder { found: false };
+    syn::visit::Visit::visit_type(&mut finder, ty);
+    finder.found
+}
+
+/// Rewrites a preparsed specification into an executable expression.
+struct ExecutableSpecRewriter<'a> {
+    old_values: &'a mut Option<Vec<(syn::Ident, syn::Expr)>>,
+    error: Option<syn::Error>,
+}
+
+impl<'a> ExecutableSpecRewriter<'a> {
+    fn report(&mut self, error: syn::Error) {
+        match &mut self.error {
+            Some(existing) => existing.combine(error),
+            None => self.error = Some(error),
+        }
+    }
+
+    fn rewrite_call(&mut self, call: &syn::ExprCall) -> Option<syn::Expr> {
+        let syn::Expr::Path(syn::ExprPath { path, .. }) = &*call.func else {
+            return None;
+        };
+        let is_prusti_function = path.segments.len() == 1
+            || (path.segments.len() == 2 && path.segments[0].ident == "prusti_contracts");
+        if !is_prusti_function {
+            return None;
+        }
+        let name = path.segments.last().unwrap().ident.to_string();
+        match name.as_str() {
+            "old" if call.args.len() == 1 => {
+                let span = call.span();
+                let Some(old_values) = self.old_values else {
+                    self.report(syn::Error::new(
+                        span,
+                        "`old` expressions can be checked at runtime only in postconditions",
+                    ));
+                    return None;
+                };
+                let name = format_ident!("prusti_old_value_{}", old_values.len(), span = span);
+                old_values.push((name.clone(), call.args[0].clone()));
+                Some(parse_quote_spanned! {span=> #name })
+            }
+            "forall" | "exists" if call.args.len() == 2 => {
+                let syn::Expr::Closure(closure) = &call.args[1] else {
+                    return None;
+                };
+                match quantifier_to_loop(name == "forall", closure) {
+                    Ok(expr) => Some(expr),
+                    Err(error) => {
+                        self.report(error);
+                        None
+                    }
+                }
+            }
+            "snapshot_equality" if call.args.len() == 2 => {
+                let span = call.span();
+                let (left, right) = (&call.args[0], &call.args[1]);
+                Some(parse_quote_spanned! {span=>
+                    ::core::cmp::PartialEq::eq(#left, #right)
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<'a> VisitMut for ExecutableSpecRewriter<'a> {
+    fn visit_expr_mut(&mut self, expr: &mut syn::Expr) {
+        // Rewrite the innermost expressions first, so that the arguments of
+        // the rewritten calls are already executable.
+        syn::visit_mut::visit_expr_mut(self, expr);
+        if let syn::Expr::Call(call) = expr {
+            if let Some(rewritten) = self.rewrite_call(call) {
+                *expr = rewritten;
+            }
+        }
+    }
+}
+
+/// Execute a quantifier preparsed into `forall((triggers), |x: T| -> bool {
+/// body })` as nested loops over the ranges of its variables, which are
+/// taken from the comparisons in the antecedent of the implication (for
+/// `forall`) or in the conjunction (for `exists`) of the body.
+fn quantifier_to_loop(is_forall: bool, closure: &syn::ExprClosure) -> syn::Result<syn::Expr> {
+    let span = closure.span();
+    let body = match &*closure.body {
+        syn::Expr::Block(syn::ExprBlock { block, .. }) if block.stmts.len() == 1 => {
+            match &block.stmts[0] {
+                syn::Stmt::Expr(expr) => expr,
+                _ => &*closure.body,
+            }
+        }
+        body => body,
+    };
+    let mut conjuncts = vec![];
+    match (is_forall, strip_parens(body)) {
+        (
+            true,
+            syn::Expr::Binary(syn::ExprBinary {
+                left:
+                    box syn::Expr::Unary(syn::ExprUnary {
+                        op: syn::UnOp::Not(_),
+                        expr: antecedent,
+                        ..
+                    }),
+                op: syn::BinOp::Or(_),
+                ..
+            }),
+        ) => collect_conjuncts(antecedent, &mut conjuncts),
+        (false, body) => collect_conjuncts(body, &mut conjuncts),
+        _ => {}
+    }
+    let mut loop_body = if is_forall {
+        quote_spanned! {span=> if !(#body) { return false; } }
+    } else {
+        quote_spanned! {span=> if #body { return true; } }
+    };
+    for input in closure.inputs.iter().rev() {
+        let syn::Pat::Type(syn::PatType { pat, ty, .. }) = input else {
+            return Err(syn::Error::new(
+                input.span(),
+                "the variables of quantifiers must have a type",
+            ));
+        };
+        let syn::Pat::Ident(syn::PatIdent { ident, .. }) = &**pat else {
+            return Err(syn::Error::new(
+                pat.span(),
+                "the variables of quantifiers must be identifiers",
+            ));
+        };
+        let range = find_range(ident, ty, &conjuncts).ok_or_else(|| {
+            syn::Error::new(
+                input.span(),
+                format!(
+                    "quantifiers can be checked at runtime only if their variables range \
+                     over bounded intervals, e.g. `0 <= {ident} && {ident} < n ==> ...`"
+                ),
+            )
+        })?;
+        loop_body = quote_spanned! {span=>
+            for #ident in #range {
+                let #ident: #ty = #ident;
+                #loop_body
+            }
+        };
+    }
+    // The result if no counterexample (for `forall`) or witness (for
+    // `exists`) is found.
+    let result = is_forall;
+    Ok(parse_quote_spanned! {span=>
+        (|| -> bool {
+            #loop_body
+            #result
+        })()
+    })
+}
+
+fn strip_parens(expr: &syn::Expr) -> &syn::Expr {
+    match expr {
+        syn::Expr::Paren(syn::ExprParen { expr, .. }) => strip_parens(expr),
+        _ => expr,
+    }
+}
+
+fn collect_conjuncts<'a>(expr: &'a syn::Expr, conjuncts: &mut Vec<&'a syn::Expr>) {
+    match strip_parens(expr) {
+        syn::Expr::Binary(syn::ExprBinary {
+            left,
+            op: syn::BinOp::And(_),
+            right,
+            ..
+        }) => {
+            collect_conjuncts(left, conjuncts);
+            collect_conjuncts(right, conjuncts);
+        }
+        expr => conjuncts.push(expr),
+    }
+}
+
+/// The range `lower..upper` or `lower..=upper` of `var` given by the
+/// comparisons in `conjuncts`, if it is bounded on both sides. Variables of
+/// an unsigned type `ty` are always bounded from below.
+fn find_range(var: &syn::Ident, ty: &syn::Type, conjuncts: &[&syn::Expr]) -> Option<TokenStream> {
+    let is_var = |expr: &syn::Expr| matches!(strip_parens(expr), syn::Expr::Path(syn::ExprPath { path, .. }) if path.is_ident(var));
+    let mut lower = None;
+    let mut upper = None;
+    for conjunct in conjuncts {
+        let syn::Expr::Binary(syn::ExprBinary {
+            left, op, right, ..
+        }) = conjunct
+        else {
+            continue;
+        };
+        // Normalize the comparison to `var <op> bound`.
+        let (op, bound) = if is_var(left) {
+            (*op, &**right)
+        } else if is_var(right) {
+            let op = match op {
+                syn::BinOp::Lt(token) => syn::BinOp::Gt(syn::Token![>](token.span)),
+                syn::BinOp::Le(token) => syn::BinOp::Ge(syn::Token![>=](token.spans)),
+                syn::BinOp::Gt(token) => syn::BinOp::Lt(syn::Token![<](token.span)),
+                syn::BinOp::Ge(token) => syn::BinOp::Le(syn::Token![<=](token.spans)),
+                _ => continue,
+            };
+            (op, &**left)
+        } else {
+            continue;
+        };
+        let span = bound.span();
+        match op {
+            syn::BinOp::Ge(_) => lower = Some(quote_spanned! {span=> (#bound) }),
+            syn::BinOp::Gt(_) => lower = Some(quote_spanned! {span=> (#bound) + 1 }),
+            syn::BinOp::Lt(_) => upper = Some(quote_spanned! {span=> ..(#bound) }),
+            syn::BinOp::Le(_) => upper = Some(quote_spanned! {span=> ..=(#bound) }),
+            _ => {}
+        }
+    }
+    // Unsigned variables are bounded from below even without a conjunct.
+    let is_unsigned = matches!(
+        ty,
+        syn::Type::Path(syn::TypePath { qself: None, path })
+            if ["u8", "u16", "u32", "u64", "u128", "usize"]
+                .iter()
+                .any(|name| path.is_ident(name))
+    );
+    if lower.is_none() && is_unsigned {
+        lower = Some(quote::quote! { 0 });
+    }
+    let (lower, upper) = (lower?, upper?);
+    Some(quote::quote! { #lower #upper })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(spec: &str) -> String {
+        generate_check(spec.parse().unwrap(), "assertion", &mut Some(vec![]))
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn implication() {
+        assert_eq!(
+            check("a ==> b"),
+            "# [allow (unused_parens , unused_comparisons)] { :: core :: assert ! (! (a) || (b) , \"{}\" , \"the assertion might not hold: a ==> b\") ; }",
+        );
+    }
+
+    #[test]
+    fn old_values() {
+        let mut old_values = Some(vec![]);
+        let check = generate_check(
+            "x == old(x) + 1".parse().unwrap(),
+            "postcondition",
+            &mut old_values,
+        )
+        .unwrap()
+        .to_string();
+        assert!(check.contains("x == prusti_old_value_0 + 1"));
+        let old_values = old_values.unwrap();
+        assert_eq!(old_values.len(), 1);
+        assert_eq!(old_values[0].1.to_token_stream().to_string(), "x");
+        assert!(generate_check("old(x) == x".parse().unwrap(), "precondition", &mut None).is_err());
+    }
+
+    #[test]
+    fn quantifiers() {
+        let forall = check("forall(|i: usize| 0 <= i && i < n ==> a[i] > 0)");
+        assert!(forall.contains(
+            "for i in (0) .. (n) { let i : usize = i ; if ! (! ((0 <= i) && (i < n)) || (a [i] > 0)) { return false ; } } true"
+        ));
+        let exists = check("exists(|i: usize| i > 0 && n >= i && a[i] == 0)");
+        assert!(exists.contains("for i in (0) + 1 ..= (n) { let i : usize = i ; if"));
+        assert!(exists.contains("{ return true ; } } false"));
+        assert!(check("forall(|i: usize| i < n ==> a[i] > 0)").contains("for i in 0 .. (n)"));
+        assert!(generate_check(
+            "forall(|i: isize| i < n ==> a[i] > 0)".parse().unwrap(),
+            "assertion",
+            &mut None,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn snapshot_equality() {
+        assert!(check("a === b").contains(":: core :: cmp :: PartialEq :: eq (& (a) , & (b))"));
+    }
+}
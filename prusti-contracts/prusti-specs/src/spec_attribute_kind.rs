@@ -18,6 +18,8 @@ pub enum SpecAttributeKind {
     Terminates = 10,
     PrintCounterexample = 11,
     Verified = 12,
+    PanicsIf = 13,
+    EnsuresOnPanic = 14,
 }
 
 impl TryFrom<String> for SpecAttributeKind {
@@ -37,6 +39,8 @@ impl TryFrom<String> for SpecAttributeKind {
             "model" => Ok(SpecAttributeKind::Model),
             "print_counterexample" => Ok(SpecAttributeKind::PrintCounterexample),
             "verified" => Ok(SpecAttributeKind::Verified),
+            "panics_if" => Ok(SpecAttributeKind::PanicsIf),
+            "ensures_on_panic" => Ok(SpecAttributeKind::EnsuresOnPanic),
             _ => Err(name),
         }
     }
@@ -63,6 +63,8 @@ pub enum SpecIdRef {
     },
     Predicate(SpecificationId),
     Terminates(SpecificationId),
+    PanicCondition(SpecificationId),
+    PanicPostcondition(SpecificationId),
 }
 
 impl Display for SpecificationId {
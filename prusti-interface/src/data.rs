@@ -7,7 +7,9 @@
 //! This module defines data structures exchanged between a verifier and
 //! its environment.
 
+use prusti_common::config;
 use prusti_rustc_interface::{hir::def_id::DefId, middle::ty};
+use regex::Regex;
 
 /// A unique identifier of the Rust procedure.
 pub type ProcedureDefId = DefId;
@@ -30,3 +32,115 @@ pub enum VerificationResult {
     /// the verifier.
     Failure,
 }
+
+/// The procedures selected for verification by the `verify_only_items` and
+/// `verify_skip_items` configuration flags, which are glob patterns of
+/// procedure names such as `my_crate::module::func`.
+pub struct ItemSelection {
+    only: Vec<Regex>,
+    skip: Vec<Regex>,
+}
+
+impl ItemSelection {
+    /// Read the patterns of the configuration, in which a leading `crate::`
+    /// refers to the crate `crate_name`.
+    pub fn from_config(crate_name: &str) -> Self {
+        Self::new(
+            &config::verify_only_items(),
+            &config::verify_skip_items(),
+            crate_name,
+        )
+    }
+
+    fn new(only: &[String], skip: &[String], crate_name: &str) -> Self {
+        let to_regexes = |patterns: &[String]| {
+            patterns
+                .iter()
+                .map(|pattern| glob_to_regex(pattern, crate_name))
+                .collect()
+        };
+        Self {
+            only: to_regexes(only),
+            skip: to_regexes(skip),
+        }
+    }
+
+    /// Whether all procedures are selected, whatever their name.
+    pub fn selects_all(&self) -> bool {
+        self.only.is_empty() && self.skip.is_empty()
+    }
+
+    /// Whether the procedure named `name` (including its crate name) is
+    /// selected for verification.
+    pub fn is_selected(&self, name: &str) -> bool {
+        (self.only.is_empty() || self.only.iter().any(|regex| regex.is_match(name)))
+            && !self.skip.iter().any(|regex| regex.is_match(name))
+    }
+}
+
+/// A regex matching the names matched by the glob `pattern`, in which `*`
+/// matches any sequence of characters and `?` matches a single character.
+fn glob_to_regex(pattern: &str, crate_name: &str) -> Regex {
+    let pattern = match pattern.strip_prefix("crate::") {
+        Some(path) => format!("{crate_name}::{path}"),
+        None => pattern.to_string(),
+    };
+    let mut regex = String::from("^");
+    for c in pattern.chars() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            c => regex.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+        }
+    }
+    regex.push('$');
+    Regex::new(&regex).expect("an escaped glob pattern is a valid regex")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ItemSelection;
+
+    fn item_selection(only: &[&str], skip: &[&str]) -> ItemSelection {
+        let to_strings = |patterns: &[&str]| patterns.iter().map(|p| p.to_string()).collect();
+        let only: Vec<String> = to_strings(only);
+        let skip: Vec<String> = to_strings(skip);
+        ItemSelection::new(&only, &skip, "my_crate")
+    }
+
+    #[test]
+    fn selects_everything_without_patterns() {
+        let selection = item_selection(&[], &[]);
+        assert!(selection.selects_all());
+        assert!(selection.is_selected("my_crate::module::func"));
+    }
+
+    #[test]
+    fn matches_whole_names() {
+        let selection = item_selection(&["crate::module::func"], &[]);
+        assert!(selection.is_selected("my_crate::module::func"));
+        assert!(!selection.is_selected("my_crate::module::func2"));
+        assert!(!selection.is_selected("other::my_crate::module::func"));
+    }
+
+    #[test]
+    fn matches_globs() {
+        let selection = item_selection(&["my_crate::module::*", "my_crate::f?o"], &[]);
+        assert!(selection.is_selected("my_crate::module::func"));
+        assert!(selection.is_selected("my_crate::module::Stack::push"));
+        assert!(selection.is_selected("my_crate::foo"));
+        assert!(!selection.is_selected("my_crate::fooo"));
+        assert!(!selection.is_selected("my_crate::other::func"));
+    }
+
+    #[test]
+    fn skip_takes_precedence() {
+        let selection = item_selection(&["crate::*"], &["*::Stack::*"]);
+        assert!(selection.is_selected("my_crate::module::func"));
+        assert!(!selection.is_selected("my_crate::module::Stack::push"));
+        let selection = item_selection(&[], &["crate::slow"]);
+        assert!(!selection.selects_all());
+        assert!(selection.is_selected("my_crate::fast"));
+        assert!(!selection.is_selected("my_crate::slow"));
+    }
+}
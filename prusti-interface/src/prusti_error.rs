@@ -160,6 +160,26 @@ impl PrustiError {
         self.is_disabled
     }
 
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn span(&self) -> &MultiSpan {
+        &self.span
+    }
+
+    /// Label a verification error as found by bounded model checking, which
+    /// only verifies some of the executions.
+    #[must_use]
+    pub fn set_bounded(mut self) -> Self {
+        self.message = self.message.replacen(
+            "[Prusti: verification error]",
+            "[Prusti: bounded verification error]",
+            1,
+        );
+        self
+    }
+
     #[must_use]
     pub fn set_help<S: ToString>(mut self, message: S) -> Self {
         self.help = Some(message.to_string());
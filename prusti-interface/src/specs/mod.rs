@@ -2,7 +2,7 @@ use crate::{
     environment::Environment,
     utils::{
         has_abstract_predicate_attr, has_extern_spec_attr, has_prusti_attr, has_to_model_fn_attr,
-        read_prusti_attr, read_prusti_attrs,
+        read_prusti_attr, read_prusti_attr_args, read_prusti_attrs,
     },
     PrustiError,
 };
@@ -86,6 +86,12 @@ pub struct SpecCollector<'a, 'tcx> {
     prusti_refutations: Vec<LocalDefId>,
     ghost_begin: Vec<LocalDefId>,
     ghost_end: Vec<LocalDefId>,
+
+    /// Map from items (including modules and impls) to their configuration
+    /// overrides, including the inherited ones.
+    item_configs: FxHashMap<LocalDefId, config::ItemConfig>,
+    /// The procedures with configuration overrides.
+    procedure_configs: Vec<LocalDefId>,
 }
 
 impl<'a, 'tcx> SpecCollector<'a, 'tcx> {
@@ -103,6 +109,8 @@ impl<'a, 'tcx> SpecCollector<'a, 'tcx> {
             prusti_refutations: vec![],
             ghost_begin: vec![],
             ghost_end: vec![],
+            item_configs: FxHashMap::default(),
+            procedure_configs: vec![],
         }
     }
 
@@ -123,6 +131,7 @@ impl<'a, 'tcx> SpecCollector<'a, 'tcx> {
         self.determine_prusti_assumptions(&mut def_spec);
         self.determine_prusti_refutations(&mut def_spec);
         self.determine_ghost_begin_ends(&mut def_spec);
+        self.determine_item_configs(&mut def_spec);
         // TODO: remove spec functions (make sure none are duplicated or left over)
         // Load all local spec MIR bodies, for export and later use
         self.ensure_local_mirs_fetched(&def_spec);
@@ -167,6 +176,12 @@ impl<'a, 'tcx> SpecCollector<'a, 'tcx> {
                             self.spec_functions.get(spec_id).unwrap().to_def_id(),
                         )));
                     }
+                    SpecIdRef::PanicCondition(spec_id) => {
+                        spec.set_panics_if(*self.spec_functions.get(spec_id).unwrap());
+                    }
+                    SpecIdRef::PanicPostcondition(spec_id) => {
+                        spec.add_postcondition_on_panic(*self.spec_functions.get(spec_id).unwrap());
+                    }
                     SpecIdRef::Terminates(spec_id) => {
                         spec.set_terminates(*self.spec_functions.get(spec_id).unwrap());
                     }
@@ -302,6 +317,44 @@ impl<'a, 'tcx> SpecCollector<'a, 'tcx> {
         }
     }
 
+    fn determine_item_configs(&self, def_spec: &mut typed::DefSpecificationMap) {
+        for local_id in self.procedure_configs.iter() {
+            def_spec.item_configs.insert(
+                local_id.to_def_id(),
+                self.item_configs.get(local_id).unwrap().clone(),
+            );
+        }
+    }
+
+    /// The configuration overrides of the `#[prusti::config(...)]` attributes
+    /// of the item and of the items that contain it (e.g. its impl and its
+    /// modules). Invalid overrides are reported when an attribute is read for
+    /// the first time.
+    fn item_config(&mut self, local_id: LocalDefId) -> &config::ItemConfig {
+        if !self.item_configs.contains_key(&local_id) {
+            let mut item_config = config::ItemConfig::default();
+            let attrs = self.env.query.get_local_attributes(local_id);
+            for (flag_value, span) in read_prusti_attr_args("config", attrs) {
+                let result = match flag_value {
+                    Some((flag, value)) => item_config.set(&flag, value),
+                    None => Err("expected arguments of the form `flag = value`".to_string()),
+                };
+                if let Err(message) = result {
+                    PrustiError::incorrect(
+                        format!("invalid `prusti::config` attribute: {message}"),
+                        MultiSpan::from(span),
+                    )
+                    .emit(&self.env.diagnostic);
+                }
+            }
+            if let Some(parent_id) = self.env.tcx().opt_local_parent(local_id) {
+                item_config.inherit(self.item_config(parent_id));
+            }
+            self.item_configs.insert(local_id, item_config);
+        }
+        self.item_configs.get(&local_id).unwrap()
+    }
+
     fn ensure_local_mirs_fetched(&mut self, def_spec: &typed::DefSpecificationMap) {
         let (specs, pure_fns, predicates) = def_spec.defid_for_export();
         for def_id in &specs {
@@ -386,6 +439,16 @@ fn get_procedure_spec_ids(def_id: DefId, attrs: &[ast::Attribute]) -> Option<Pro
             .into_iter()
             .map(|raw_spec_id| SpecIdRef::Purity(parse_spec_id(raw_spec_id, def_id))),
     );
+    spec_id_refs.extend(
+        read_prusti_attrs("panics_if_spec_id_ref", attrs)
+            .into_iter()
+            .map(|raw_spec_id| SpecIdRef::PanicCondition(parse_spec_id(raw_spec_id, def_id))),
+    );
+    spec_id_refs.extend(
+        read_prusti_attrs("post_on_panic_spec_id_ref", attrs)
+            .into_iter()
+            .map(|raw_spec_id| SpecIdRef::PanicPostcondition(parse_spec_id(raw_spec_id, def_id))),
+    );
     spec_id_refs.extend(
         read_prusti_attrs("terminates_spec_id_ref", attrs)
             .into_iter()
@@ -566,6 +629,11 @@ impl<'a, 'tcx> intravisit::Visitor<'tcx> for SpecCollector<'a, 'tcx> {
                 self.procedure_specs.insert(local_id, procedure_spec_ref);
             }
 
+            // Collect configuration overrides
+            if !self.item_config(local_id).is_empty() {
+                self.procedure_configs.push(local_id);
+            }
+
             // Collect model type flag
             if has_to_model_fn_attr(attrs) {
                 if let FnRetTy::Return(ty) = fn_decl.output {
@@ -1,5 +1,6 @@
 use crate::{environment::Environment, utils::has_trait_bounds_type_cond_spec, PrustiError};
 pub use common::{SpecIdRef, SpecType, SpecificationId};
+use prusti_common::config;
 use prusti_rustc_interface::{
     hir::def_id::{DefId, LocalDefId},
     macros::{TyDecodable, TyEncodable},
@@ -20,6 +21,7 @@ pub struct DefSpecificationMap {
     pub prusti_refutations: FxHashMap<DefId, PrustiRefutation>,
     pub ghost_begin: FxHashMap<DefId, GhostBegin>,
     pub ghost_end: FxHashMap<DefId, GhostEnd>,
+    pub item_configs: FxHashMap<DefId, config::ItemConfig>,
 }
 
 impl DefSpecificationMap {
@@ -59,6 +61,12 @@ impl DefSpecificationMap {
         self.ghost_end.get(def_id)
     }
 
+    /// The configuration overrides of a procedure, set with the
+    /// `#[prusti::config(...)]` attribute on it or on an enclosing item.
+    pub fn get_item_config(&self, def_id: &DefId) -> Option<&config::ItemConfig> {
+        self.item_configs.get(def_id)
+    }
+
     pub(crate) fn defid_for_export(
         &self,
     ) -> (
@@ -82,6 +90,14 @@ impl DefSpecificationMap {
                 if let Some(posts) = spec.posts.extract_with_selective_replacement() {
                     specs.extend(posts);
                 }
+                if let Some(Some(panics_if)) = spec.panics_if.extract_with_selective_replacement() {
+                    specs.push(*panics_if);
+                }
+                if let Some(posts_on_panic) =
+                    spec.posts_on_panic.extract_with_selective_replacement()
+                {
+                    specs.extend(posts_on_panic);
+                }
                 if let Some(Some(term)) = spec.terminates.extract_with_selective_replacement() {
                     specs.push(term.to_def_id());
                 }
@@ -211,6 +227,11 @@ pub struct ProcedureSpecification {
     pub pres: SpecificationItem<Vec<DefId>>,
     pub posts: SpecificationItem<Vec<DefId>>,
     pub pledges: SpecificationItem<Vec<Pledge>>,
+    /// The condition under which the procedure panics (`#[panics_if(..)]`).
+    pub panics_if: SpecificationItem<Option<DefId>>,
+    /// The postconditions that hold when the procedure panics
+    /// (`#[ensures_on_panic(..)]`).
+    pub posts_on_panic: SpecificationItem<Vec<DefId>>,
     pub trusted: SpecificationItem<bool>,
     pub terminates: SpecificationItem<Option<LocalDefId>>,
     pub purity: SpecificationItem<Option<DefId>>, // for type-conditional spec refinements
@@ -226,6 +247,8 @@ impl ProcedureSpecification {
             pres: SpecificationItem::Empty,
             posts: SpecificationItem::Empty,
             pledges: SpecificationItem::Empty,
+            panics_if: SpecificationItem::Empty,
+            posts_on_panic: SpecificationItem::Empty,
             trusted: SpecificationItem::Inherent(false),
             terminates: SpecificationItem::Inherent(None),
             purity: SpecificationItem::Inherent(None),
@@ -486,6 +509,23 @@ impl SpecGraph<ProcedureSpecification> {
             .for_each(|s| s.trusted.set(trusted));
     }
 
+    /// Sets the panic condition for the base spec and all constrained specs.
+    pub fn set_panics_if(&mut self, panics_if: LocalDefId) {
+        self.base_spec.panics_if.set(Some(panics_if.to_def_id()));
+        self.specs_with_constraints
+            .values_mut()
+            .for_each(|s| s.panics_if.set(Some(panics_if.to_def_id())));
+    }
+
+    /// Attaches the postcondition on panic `post` to the base spec and all
+    /// constrained specs.
+    pub fn add_postcondition_on_panic(&mut self, post: LocalDefId) {
+        self.base_spec.posts_on_panic.push(post.to_def_id());
+        self.specs_with_constraints
+            .values_mut()
+            .for_each(|s| s.posts_on_panic.push(post.to_def_id()));
+    }
+
     /// Sets the termination flag for the base spec and all constrained specs.
     pub fn set_terminates(&mut self, terminates: LocalDefId) {
         self.base_spec.terminates.set(Some(terminates));
@@ -767,7 +807,11 @@ impl Refinable for ProcedureSpecification {
         type SpecVec<T> = SpecificationItem<Vec<T>>;
         static EMPTYL: SpecVec<DefId> = SpecificationItem::Inherent(vec![]);
         static EMPTYP: SpecVec<Pledge> = SpecificationItem::Inherent(vec![]);
-        fn replace_empty<'a, T>(empty: &'a SpecVec<T>, spec: &'a SpecVec<T>) -> &'a SpecVec<T> {
+        static NONE: SpecificationItem<Option<DefId>> = SpecificationItem::Inherent(None);
+        fn replace_empty<'a, T>(
+            empty: &'a SpecificationItem<T>,
+            spec: &'a SpecificationItem<T>,
+        ) -> &'a SpecificationItem<T> {
             match spec {
                 SpecificationItem::Empty => empty,
                 other => other,
@@ -778,6 +822,12 @@ impl Refinable for ProcedureSpecification {
             pres: self.pres.refine(replace_empty(&EMPTYL, &other.pres)),
             posts: self.posts.refine(replace_empty(&EMPTYL, &other.posts)),
             pledges: self.pledges.refine(replace_empty(&EMPTYP, &other.pledges)),
+            panics_if: self
+                .panics_if
+                .refine(replace_empty(&NONE, &other.panics_if)),
+            posts_on_panic: self
+                .posts_on_panic
+                .refine(replace_empty(&EMPTYL, &other.posts_on_panic)),
             kind: self.kind.refine(&other.kind),
             trusted: self.trusted.refine(&other.trusted),
             terminates: self.terminates.refine(&other.terminates),
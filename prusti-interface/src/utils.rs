@@ -10,6 +10,7 @@ use prusti_rustc_interface::{
     ast::ast,
     data_structures::fx::FxHashSet,
     middle::{mir, ty::TyCtxt},
+    span::Span,
 };
 use std::borrow::Borrow;
 
@@ -279,3 +280,44 @@ pub fn read_prusti_attrs<T: Borrow<ast::Attribute>>(attr_name: &str, attrs: &[T]
 pub fn read_prusti_attr<T: Borrow<ast::Attribute>>(attr_name: &str, attrs: &[T]) -> Option<String> {
     read_prusti_attrs(attr_name, attrs).pop()
 }
+
+/// Read the arguments of the Prusti attributes `prusti::<attr_name>(...)`,
+/// each of which must be of the form `key = literal`. Returns the key and
+/// the value of each argument, or `None` if it is not of this form, with
+/// the span of the argument.
+pub fn read_prusti_attr_args(
+    attr_name: &str,
+    attrs: &[ast::Attribute],
+) -> Vec<(Option<(String, String)>, Span)> {
+    let mut args = vec![];
+    for attr in attrs {
+        let ast::AttrKind::Normal(normal_attr) = &attr.kind else {
+            continue;
+        };
+        let segments = &normal_attr.item.path.segments;
+        if !(segments.len() == 2
+            && segments[0].ident.as_str() == "prusti"
+            && segments[1].ident.as_str() == attr_name)
+        {
+            continue;
+        }
+        let Some(items) = attr.meta_item_list() else {
+            args.push((None, attr.span));
+            continue;
+        };
+        for item in items {
+            let key_value = item.meta_item().and_then(|meta_item| {
+                let key = meta_item.ident()?.to_string();
+                let value = match meta_item.name_value_literal()?.kind {
+                    ast::LitKind::Bool(value) => value.to_string(),
+                    ast::LitKind::Int(value, _) => value.to_string(),
+                    ast::LitKind::Str(value, _) => value.to_string(),
+                    _ => return None,
+                };
+                Some((key, value))
+            });
+            args.push((key_value, item.span()));
+        }
+    }
+    args
+}
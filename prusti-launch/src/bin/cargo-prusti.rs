@@ -5,8 +5,15 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 #![feature(let_chains)]
 
-use prusti_utils::{config, launch};
-use std::{env, fs, io, path::PathBuf, process::Command};
+use prusti_utils::{
+    config,
+    launch::{self, verification_summary},
+};
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+};
 
 fn main() {
     if let Err(code) = process(env::args().skip(1)) {
@@ -30,23 +37,56 @@ where
     let args = args.skip_while(|arg| arg == "prusti");
     // Remove the "-- -Pflag" arguments since these won't apply to `cargo check`.
     // They have already been loaded (and the Category B flags are used below).
-    let args = args.take_while(|arg| arg != "--");
+    let mut args = args.take_while(|arg| arg != "--").peekable();
+
+    let cargo_target = env::var("CARGO_TARGET_DIR").unwrap_or_else(|_| "target".to_string());
+    let cargo_target: PathBuf = [cargo_target, "verify".to_string()].into_iter().collect();
+
+    if args.peek().map(String::as_str) == Some("cache") {
+        return maintain_cache(args, &cargo_target);
+    }
+
+    let selection = match take_item_selection(args) {
+        Ok(selection) => selection,
+        Err(message) => {
+            eprintln!("error: {message}");
+            return Err(1);
+        }
+    };
 
     // Category B flags (see dev-guide flags table):
     let cargo_path = config::cargo_path();
     let command = config::cargo_command();
 
+    // Each crate writes a report there, which are merged after the run.
+    let report_dir =
+        config::report_dir().map_or_else(|| cargo_target.join("report"), PathBuf::from);
+
     let features = if launch::enable_prusti_feature(&cargo_path) && !config::be_rustc() {
         ["--features", "prusti-contracts/prusti"].iter()
     } else {
         [].iter()
     };
-    let cargo_target = env::var("CARGO_TARGET_DIR").unwrap_or_else(|_| "target".to_string());
-    let cargo_target: PathBuf = [cargo_target, "verify".to_string()].into_iter().collect();
-    let exit_status = Command::new(cargo_path)
+    let mut cargo = Command::new(cargo_path);
+    for (var, patterns) in [
+        ("PRUSTI_VERIFY_ONLY_ITEMS", selection.only),
+        ("PRUSTI_VERIFY_SKIP_ITEMS", selection.skip),
+    ] {
+        if !patterns.is_empty() {
+            let mut value = env::var(var).unwrap_or_default();
+            for pattern in patterns {
+                if !value.is_empty() {
+                    value.push(' ');
+                }
+                value.push_str(&pattern);
+            }
+            cargo.env(var, value);
+        }
+    }
+    let exit_status = cargo
         .arg(&command)
         .args(features)
-        .args(args)
+        .args(selection.cargo_args)
         .env("RUST_TOOLCHAIN", launch::get_rust_toolchain_channel())
         .env("RUSTUP_TOOLCHAIN", launch::get_rust_toolchain_channel())
         .env("RUSTC", prusti_rustc_path)
@@ -63,9 +103,18 @@ where
         .env("DEFAULT_PRUSTI_FULL_COMPILATION", "true")
         .env("DEFAULT_PRUSTI_LOG_DIR", cargo_target.join("log"))
         .env("DEFAULT_PRUSTI_CACHE_PATH", cargo_target.join("cache.bin"))
+        .env("DEFAULT_PRUSTI_REPORT_DIR", &report_dir)
+        .env(
+            "DEFAULT_PRUSTI_FINGERPRINT_DIR",
+            cargo_target.join("fingerprints"),
+        )
         .status()
         .expect("could not run cargo");
 
+    if !config::be_rustc() {
+        summarize_verification(&report_dir);
+    }
+
     if exit_status.success() {
         if command == "build" {
             copy_exported_specs(cargo_target).ok();
@@ -76,6 +125,101 @@ where
     }
 }
 
+/// The procedures selected with `--only` and `--skip`, and the arguments
+/// that are passed on to cargo.
+#[derive(Default)]
+struct ItemSelection {
+    cargo_args: Vec<String>,
+    only: Vec<String>,
+    skip: Vec<String>,
+}
+
+/// Remove the `--only <pattern>` and `--skip <pattern>` arguments, which
+/// select the procedures to verify (see the `verify_only_items` and
+/// `verify_skip_items` flags). The arguments after `--` are kept as they are.
+fn take_item_selection<I>(mut args: I) -> Result<ItemSelection, String>
+where
+    I: Iterator<Item = String>,
+{
+    let mut selection = ItemSelection::default();
+    while let Some(arg) = args.next() {
+        let (option, patterns) = match arg.split('=').next() {
+            Some("--only") => ("--only", &mut selection.only),
+            Some("--skip") => ("--skip", &mut selection.skip),
+            Some("--") => {
+                selection.cargo_args.push(arg);
+                selection.cargo_args.extend(args);
+                break;
+            }
+            _ => {
+                selection.cargo_args.push(arg);
+                continue;
+            }
+        };
+        let pattern = match arg.split_once('=') {
+            Some((_, pattern)) => Some(pattern.to_string()),
+            None => args.next(),
+        };
+        match pattern {
+            Some(pattern) if !pattern.is_empty() && !pattern.starts_with("--") => {
+                patterns.push(pattern)
+            }
+            _ => {
+                return Err(format!(
+                    "`{option}` requires a pattern, e.g. `{option} 'crate::module::*'`"
+                ))
+            }
+        }
+    }
+    Ok(selection)
+}
+
+/// Merge the verification reports of the crates into a JUnit XML report and
+/// print a summary per crate. Crates that were not rebuilt keep the report of
+/// their last verification, which is why the report directory is not cleared
+/// before running cargo; the reports of removed crates are merged as well.
+fn summarize_verification(report_dir: &Path) {
+    let reports = match verification_summary::load_reports(report_dir) {
+        Ok(reports) if !reports.is_empty() => reports,
+        _ => return,
+    };
+    let junit_path = report_dir.join("junit.xml");
+    if let Err(error) = fs::write(&junit_path, verification_summary::junit_xml(&reports)) {
+        eprintln!(
+            "warning: could not write the JUnit report {}: {error}",
+            junit_path.display()
+        );
+    }
+    println!(
+        "\nVerification summary (JUnit report: {}):\n{}",
+        junit_path.display(),
+        verification_summary::summary_table(&reports)
+    );
+}
+
+/// Run `prusti-server cache ...` on the cache used by `cargo prusti`.
+fn maintain_cache<I>(args: I, cargo_target: &Path) -> Result<(), i32>
+where
+    I: Iterator<Item = String>,
+{
+    let mut prusti_server_path = env::current_exe()
+        .expect("current executable path invalid")
+        .with_file_name("prusti-server");
+    if cfg!(windows) {
+        prusti_server_path.set_extension("exe");
+    }
+    let exit_status = Command::new(prusti_server_path)
+        .args(args)
+        .env("DEFAULT_PRUSTI_CACHE_PATH", cargo_target.join("cache.bin"))
+        .status()
+        .expect("could not run prusti-server");
+    if exit_status.success() {
+        Ok(())
+    } else {
+        Err(exit_status.code().unwrap_or(-1))
+    }
+}
+
 /// Copy specs from '{cargo_target}/*/deps/*.specs' to '{cargo_target}/*/*.specs'
 fn copy_exported_specs(cargo_target: PathBuf) -> io::Result<()> {
     for de in fs::read_dir(cargo_target)? {
@@ -99,3 +243,69 @@ fn copy_exported_specs(cargo_target: PathBuf) -> io::Result<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn select(args: &[&str]) -> Result<ItemSelection, String> {
+        take_item_selection(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn no_selection() {
+        let selection = select(&["--release", "-p", "foo"]).unwrap();
+        assert_eq!(selection.cargo_args, strings(&["--release", "-p", "foo"]));
+        assert!(selection.only.is_empty());
+        assert!(selection.skip.is_empty());
+    }
+
+    #[test]
+    fn pattern_after_equals_sign() {
+        let selection = select(&["--only=foo::*", "--skip=foo::bar"]).unwrap();
+        assert!(selection.cargo_args.is_empty());
+        assert_eq!(selection.only, strings(&["foo::*"]));
+        assert_eq!(selection.skip, strings(&["foo::bar"]));
+    }
+
+    #[test]
+    fn pattern_in_next_argument() {
+        let selection = select(&["--only", "foo", "--release", "--skip", "bar"]).unwrap();
+        assert_eq!(selection.cargo_args, strings(&["--release"]));
+        assert_eq!(selection.only, strings(&["foo"]));
+        assert_eq!(selection.skip, strings(&["bar"]));
+    }
+
+    #[test]
+    fn repeated_option() {
+        let selection = select(&["--only", "foo", "--only=bar", "--only", "baz"]).unwrap();
+        assert_eq!(selection.only, strings(&["foo", "bar", "baz"]));
+        assert!(selection.skip.is_empty());
+    }
+
+    #[test]
+    fn missing_pattern() {
+        assert!(select(&["--only"]).is_err());
+        assert!(select(&["--skip="]).is_err());
+        assert!(select(&["--only", "--skip", "foo"]).is_err());
+    }
+
+    #[test]
+    fn arguments_after_separator() {
+        let selection = select(&["--only", "foo", "--", "--skip", "bar"]).unwrap();
+        assert_eq!(selection.cargo_args, strings(&["--", "--skip", "bar"]));
+        assert_eq!(selection.only, strings(&["foo"]));
+        assert!(selection.skip.is_empty());
+    }
+
+    #[test]
+    fn similar_option_is_not_a_selection() {
+        let selection = select(&["--only-foo", "--skipped"]).unwrap();
+        assert_eq!(selection.cargo_args, strings(&["--only-foo", "--skipped"]));
+        assert!(selection.only.is_empty());
+    }
+}
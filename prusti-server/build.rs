@@ -0,0 +1,35 @@
+use std::{path::Path, process::Command};
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    // Rerun when HEAD moves, i.e. when another commit is checked out or the
+    // checked out branch gets a new commit, so that the handshake reports the
+    // current commit.
+    let mut git_files = vec!["HEAD".to_string()];
+    if let Some(reference) = git(&["symbolic-ref", "-q", "HEAD"]) {
+        git_files.push(reference);
+        git_files.push("packed-refs".to_string());
+    }
+    for git_file in git_files {
+        if let Some(path) = git(&["rev-parse", "--git-path", &git_file]) {
+            if Path::new(&path).exists() {
+                println!("cargo:rerun-if-changed={path}");
+            }
+        }
+    }
+
+    if let Some(commit_hash) = git(&["rev-parse", "--short", "HEAD"]) {
+        println!("cargo:rustc-env=COMMIT_HASH={commit_hash}");
+    }
+}
+
+/// The trimmed output of a successful git command.
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let output = String::from_utf8(output.stdout).ok()?;
+    Some(output.trim().to_string())
+}
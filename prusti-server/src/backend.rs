@@ -1,4 +1,4 @@
-use crate::dump_viper_program;
+use crate::{dump_viper_program, SmtLibBackend};
 use prusti_common::{
     config,
     vir::{LoweringContext, ToViper},
@@ -8,6 +8,7 @@ use viper::{VerificationContext, VerificationResult};
 
 pub enum Backend<'a> {
     Viper(viper::Verifier<'a>, &'a VerificationContext<'a>),
+    SmtLib(SmtLibBackend),
 }
 
 impl<'a> Backend<'a> {
@@ -36,6 +37,10 @@ impl<'a> Backend<'a> {
                     viper.verify(viper_program)
                 })
             }
+            Backend::SmtLib(backend) => {
+                let _stopwatch = Stopwatch::start("prusti-server backend", "SMT-LIB verification");
+                backend.verify(program)
+            }
         }
     }
 }
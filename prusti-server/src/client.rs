@@ -4,41 +4,164 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use crate::VerificationRequest;
+use crate::{ProtocolVersion, VerificationRequest, PROTOCOL_VERSION_HEADER};
+use log::{info, warn};
 use prusti_common::config;
-use reqwest::Client;
+use reqwest::{blocking, Client, StatusCode};
+use std::time::Duration;
 use url::{ParseError, Url};
 use viper::VerificationResult;
 
+/// The delay before retrying a failed request for the first time. It is
+/// doubled on each subsequent retry.
+const INITIAL_RETRY_DELAY: Duration = Duration::from_millis(250);
+
+/// How much longer than the deadline of a request the client waits for the
+/// reply of the server, which is expected to enforce the deadline itself.
+const DEADLINE_GRACE_PERIOD: Duration = Duration::from_secs(10);
+
+#[derive(Debug)]
+pub enum ClientError {
+    /// The address of the server could not be parsed.
+    InvalidAddress(ParseError),
+    /// The server runs a version of Prusti that is incompatible with the
+    /// version of the client.
+    IncompatibleServer(ProtocolVersion),
+    /// The request was not verified within its deadline.
+    DeadlineExceeded(Duration),
+    /// The request has a deadline or a portfolio, but the server has no workers
+    /// to enforce or run it.
+    RequiresWorkers,
+    /// The request failed, even after retrying.
+    Request(reqwest::Error),
+}
+
+impl std::fmt::Display for ClientError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClientError::InvalidAddress(error) => write!(f, "invalid server address: {error}"),
+            ClientError::IncompatibleServer(server_version) => write!(
+                f,
+                "the server runs {server_version}, which is incompatible with the client, \
+                which runs {}",
+                ProtocolVersion::current()
+            ),
+            ClientError::DeadlineExceeded(timeout) => write!(
+                f,
+                "the verification did not finish within {} seconds",
+                timeout.as_secs()
+            ),
+            ClientError::RequiresWorkers => write!(
+                f,
+                "the server cannot enforce the deadline or run the portfolio of the request, \
+                because it was not started with `--workers`"
+            ),
+            ClientError::Request(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct PrustiClient {
     client: Client,
     server_url: Url,
 }
 
 impl PrustiClient {
-    pub fn new<S: ToString>(server_address: S) -> Result<Self, ParseError> {
+    /// Connect to the server and check that it runs a compatible version of
+    /// Prusti.
+    ///
+    /// This blocks until the server replies, so it must not be called from an
+    /// asynchronous context.
+    pub fn new<S: ToString>(server_address: S) -> Result<Self, ClientError> {
         let mut address = server_address.to_string();
         if !address.starts_with("http") {
             address = format!("http://{address}");
         }
+        let server_url = Url::parse(address.as_str()).map_err(ClientError::InvalidAddress)?;
+        let server_version = Self::fetch_version(&server_url).map_err(ClientError::Request)?;
+        if !server_version.is_compatible_with(&ProtocolVersion::current()) {
+            return Err(ClientError::IncompatibleServer(server_version));
+        }
+        info!("Connected to a server running {server_version}");
         Ok(Self {
             client: Client::new(),
-            server_url: Url::parse(address.as_str())?,
+            server_url,
         })
     }
 
+    /// Ask the server which version of Prusti it runs.
+    fn fetch_version(server_url: &Url) -> reqwest::Result<ProtocolVersion> {
+        blocking::Client::new()
+            .get(server_url.join("version").unwrap())
+            .send()?
+            .error_for_status()?
+            .json()
+    }
+
+    /// Verify the request, retrying with an exponential backoff if the server
+    /// cannot be reached or fails. Requests that exceeded their deadline or
+    /// that were rejected by the server are not retried.
     pub async fn verify(
         &self,
         request: VerificationRequest,
+    ) -> Result<VerificationResult, ClientError> {
+        let max_retries = config::server_max_retries();
+        let mut retry_delay = INITIAL_RETRY_DELAY;
+        let mut retries = 0;
+        loop {
+            match self.verify_once(request.clone()).await {
+                Ok(result) => return Ok(result),
+                Err(error)
+                    if error.is_timeout()
+                        || error.status() == Some(StatusCode::GATEWAY_TIMEOUT) =>
+                {
+                    let timeout = request.timeout.unwrap_or_default();
+                    return Err(ClientError::DeadlineExceeded(timeout));
+                }
+                Err(error) if error.status() == Some(StatusCode::UNPROCESSABLE_ENTITY) => {
+                    return Err(ClientError::RequiresWorkers);
+                }
+                Err(error)
+                    if retries < max_retries
+                        && !error
+                            .status()
+                            .map_or(false, |status| status.is_client_error()) =>
+                {
+                    retries += 1;
+                    warn!(
+                        "Verification request of program {} failed ({error}), \
+                        retrying in {retry_delay:?} ({retries}/{max_retries})",
+                        request.program.get_name()
+                    );
+                    tokio::time::sleep(retry_delay).await;
+                    retry_delay *= 2;
+                }
+                Err(error) => return Err(ClientError::Request(error)),
+            }
+        }
+    }
+
+    /// Send the request to the server once, without retrying.
+    pub async fn verify_once(
+        &self,
+        request: VerificationRequest,
     ) -> reqwest::Result<VerificationResult> {
         let use_json = config::json_communication();
-        let base = self.client.post(
+        let mut base = self.client.post(
             self.server_url
                 .join(if use_json { "json/" } else { "bincode/" })
                 .unwrap()
                 .join("verify/")
                 .unwrap(),
         );
+        base = base.header(
+            PROTOCOL_VERSION_HEADER,
+            ProtocolVersion::current().fingerprint(),
+        );
+        if let Some(timeout) = request.timeout {
+            base = base.timeout(timeout + DEADLINE_GRACE_PERIOD);
+        }
         let response = if use_json {
             base.json(&request)
                 .send()
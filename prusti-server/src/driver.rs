@@ -4,8 +4,13 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use prusti_utils::config;
+use std::{
+    net::{IpAddr, Ipv4Addr},
+    time::Duration,
+};
+use viper::PersistentCache;
 
 /// A verification server to handle Prusti verification requests.
 #[derive(Parser, Debug)]
@@ -15,6 +20,44 @@ struct Args {
     /// Pass 0 to get a free one assigned by the OS.
     #[clap(short, long, value_name = "PORT", default_value_t = 0)]
     port: u16,
+
+    /// Sets the address on which to listen for incoming verification requests.
+    /// Pass 0.0.0.0 to accept requests from other machines, e.g. to share the
+    /// verification cache.
+    #[clap(short, long, value_name = "ADDRESS", default_value_t = Ipv4Addr::LOCALHOST.into())]
+    bind_address: IpAddr,
+
+    /// Verifies requests in parallel on the given number of worker processes,
+    /// each with its own JVM. Pass 0 to verify requests in the server process.
+    #[clap(short, long, value_name = "WORKERS", default_value_t = 0)]
+    workers: usize,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Inspects or maintains the verification cache at `CACHE_PATH` instead
+    /// of starting a server.
+    Cache {
+        #[clap(subcommand)]
+        action: CacheAction,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum CacheAction {
+    /// Prints statistics about the cache.
+    Stats,
+    /// Removes the entries that have not been used recently.
+    Prune {
+        /// Removes the entries not used for longer than this number of seconds.
+        #[clap(long, value_name = "SECONDS")]
+        max_age: u64,
+    },
+    /// Removes all entries.
+    Clear,
 }
 
 fn main() {
@@ -26,5 +69,37 @@ fn main() {
 
     let args = Args::parse();
 
-    prusti_server::start_server_on_port(args.port);
+    match args.command {
+        Some(Command::Cache { action }) => maintain_cache(action),
+        None if args.workers == 0 => {
+            prusti_server::start_server_on_port(args.bind_address, args.port)
+        }
+        None => prusti_server::start_worker_pool_server_on_port(
+            args.bind_address,
+            args.port,
+            args.workers,
+        ),
+    }
+}
+
+fn maintain_cache(action: CacheAction) {
+    let cache_path = config::cache_path();
+    if cache_path.as_os_str().is_empty() {
+        eprintln!("No cache to maintain, because the cache path is empty.");
+        std::process::exit(1);
+    }
+    let mut cache = PersistentCache::load_cache(cache_path);
+    cache.set_max_entries(config::cache_max_entries());
+    match action {
+        CacheAction::Stats => println!("{}", cache.statistics()),
+        CacheAction::Prune { max_age } => {
+            let pruned = cache.prune(Duration::from_secs(max_age));
+            println!("Pruned {pruned} entries");
+        }
+        CacheAction::Clear => {
+            cache.clear();
+            println!("Cleared the cache");
+        }
+    }
+    cache.save();
 }
@@ -7,16 +7,26 @@
 #![warn(clippy::disallowed_types)]
 
 mod client;
+mod metrics;
 mod process_verification;
+mod remote_cache;
 mod server;
+mod smt_lib;
 mod verification_request;
+mod version;
 mod backend;
+mod worker_pool;
 
 pub use backend::*;
 pub use client::*;
+pub use metrics::*;
 pub use process_verification::*;
+pub use remote_cache::*;
 pub use server::*;
+pub use smt_lib::*;
 pub use verification_request::*;
+pub use version::*;
+pub use worker_pool::*;
 
 // Futures returned by `Client` need to be executed in a compatible tokio runtime.
 pub use tokio;
@@ -0,0 +1,286 @@
+// © 2023, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use once_cell::sync::Lazy;
+use std::{
+    collections::VecDeque,
+    fmt::Write,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+/// How many of the most recent verifications are remembered to report the
+/// slowest ones.
+const RECENT_VERIFICATIONS: usize = 100;
+
+/// How many of the slowest recent verifications are reported.
+const SLOWEST_VERIFICATIONS: usize = 10;
+
+/// The upper bounds (in seconds) of the buckets of the histogram of the
+/// verification times.
+const DURATION_BUCKETS: [f64; 9] = [0.1, 0.5, 1.0, 5.0, 10.0, 30.0, 60.0, 300.0, 900.0];
+
+/// The metrics of the server process, collected while handling requests.
+pub static METRICS: Lazy<ServerMetrics> = Lazy::new(ServerMetrics::new);
+
+pub struct ServerMetrics {
+    start: Instant,
+    in_flight: AtomicUsize,
+    queued: AtomicUsize,
+    state: Mutex<MetricsState>,
+}
+
+#[derive(Default)]
+struct MetricsState {
+    jvm_startup: Option<Duration>,
+    verifications: u64,
+    verification_time: Duration,
+    /// The number of verifications that took at most the corresponding
+    /// duration of `DURATION_BUCKETS`.
+    duration_buckets: [u64; DURATION_BUCKETS.len()],
+    cache_hits: u64,
+    cache_misses: u64,
+    java_exceptions: u64,
+    recent_verifications: VecDeque<ProgramTiming>,
+}
+
+/// Keeps a gauge incremented while it is alive.
+pub struct GaugeGuard<'a>(&'a AtomicUsize);
+
+impl<'a> GaugeGuard<'a> {
+    fn new(gauge: &'a AtomicUsize) -> Self {
+        gauge.fetch_add(1, Ordering::SeqCst);
+        GaugeGuard(gauge)
+    }
+}
+
+impl Drop for GaugeGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ProgramTiming {
+    pub program: String,
+    pub seconds: f64,
+}
+
+/// A snapshot of the metrics, as reported by the `/status` endpoint.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ServerStatus {
+    pub uptime_seconds: f64,
+    pub in_flight_requests: usize,
+    pub queued_requests: usize,
+    /// `None` until the JVM has been started, which happens on the first
+    /// request.
+    pub jvm_startup_seconds: Option<f64>,
+    pub verifications: u64,
+    pub verification_seconds: f64,
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    /// `None` if the cache has not been used yet.
+    pub cache_hit_ratio: Option<f64>,
+    pub java_exceptions: u64,
+    pub slowest_recent_programs: Vec<ProgramTiming>,
+}
+
+impl ServerMetrics {
+    fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            in_flight: AtomicUsize::new(0),
+            queued: AtomicUsize::new(0),
+            state: Mutex::new(MetricsState::default()),
+        }
+    }
+
+    /// Count a request as being processed until the guard is dropped.
+    pub fn track_in_flight(&self) -> GaugeGuard<'_> {
+        GaugeGuard::new(&self.in_flight)
+    }
+
+    /// Count a request as waiting to be processed until the guard is dropped.
+    pub fn track_queued(&self) -> GaugeGuard<'_> {
+        GaugeGuard::new(&self.queued)
+    }
+
+    pub fn record_jvm_startup(&self, duration: Duration) {
+        self.state.lock().unwrap().jvm_startup = Some(duration);
+    }
+
+    pub fn record_cache_lookup(&self, hit: bool) {
+        let mut state = self.state.lock().unwrap();
+        if hit {
+            state.cache_hits += 1;
+        } else {
+            state.cache_misses += 1;
+        }
+    }
+
+    /// Record a program that was verified by a backend, i.e. not found in the
+    /// cache.
+    pub fn record_verification(&self, program: &str, duration: Duration) {
+        let mut state = self.state.lock().unwrap();
+        state.verifications += 1;
+        state.verification_time += duration;
+        let seconds = duration.as_secs_f64();
+        for (bucket, &bound) in state.duration_buckets.iter_mut().zip(&DURATION_BUCKETS) {
+            if seconds <= bound {
+                *bucket += 1;
+            }
+        }
+        if state.recent_verifications.len() == RECENT_VERIFICATIONS {
+            state.recent_verifications.pop_front();
+        }
+        state.recent_verifications.push_back(ProgramTiming {
+            program: program.to_string(),
+            seconds,
+        });
+    }
+
+    pub fn record_java_exception(&self) {
+        self.state.lock().unwrap().java_exceptions += 1;
+    }
+
+    pub fn status(&self) -> ServerStatus {
+        let state = self.state.lock().unwrap();
+        let cache_lookups = state.cache_hits + state.cache_misses;
+        let mut slowest_recent_programs: Vec<_> =
+            state.recent_verifications.iter().cloned().collect();
+        slowest_recent_programs.sort_by(|a, b| b.seconds.total_cmp(&a.seconds));
+        slowest_recent_programs.truncate(SLOWEST_VERIFICATIONS);
+        ServerStatus {
+            uptime_seconds: self.start.elapsed().as_secs_f64(),
+            in_flight_requests: self.in_flight.load(Ordering::SeqCst),
+            queued_requests: self.queued.load(Ordering::SeqCst),
+            jvm_startup_seconds: state.jvm_startup.map(|duration| duration.as_secs_f64()),
+            verifications: state.verifications,
+            verification_seconds: state.verification_time.as_secs_f64(),
+            cache_hits: state.cache_hits,
+            cache_misses: state.cache_misses,
+            cache_hit_ratio: (cache_lookups > 0)
+                .then(|| state.cache_hits as f64 / cache_lookups as f64),
+            java_exceptions: state.java_exceptions,
+            slowest_recent_programs,
+        }
+    }
+
+    /// The metrics in the text format of Prometheus.
+    pub fn prometheus(&self) -> String {
+        let status = self.status();
+        let duration_buckets = self.state.lock().unwrap().duration_buckets;
+        let mut out = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, samples: &[(String, f64)]| {
+            writeln!(out, "# HELP {name} {help}").unwrap();
+            writeln!(out, "# TYPE {name} {kind}").unwrap();
+            for (suffix, value) in samples {
+                writeln!(out, "{name}{suffix} {value}").unwrap();
+            }
+        };
+
+        metric(
+            "prusti_uptime_seconds",
+            "gauge",
+            "Time since the server started.",
+            &[(String::new(), status.uptime_seconds)],
+        );
+        metric(
+            "prusti_requests_in_flight",
+            "gauge",
+            "Verification requests that are being processed.",
+            &[(String::new(), status.in_flight_requests as f64)],
+        );
+        metric(
+            "prusti_requests_queued",
+            "gauge",
+            "Verification requests waiting for an idle worker.",
+            &[(String::new(), status.queued_requests as f64)],
+        );
+        if let Some(jvm_startup) = status.jvm_startup_seconds {
+            metric(
+                "prusti_jvm_startup_seconds",
+                "gauge",
+                "Time it took to start the JVM.",
+                &[(String::new(), jvm_startup)],
+            );
+        }
+
+        let mut samples: Vec<_> = DURATION_BUCKETS
+            .iter()
+            .zip(duration_buckets)
+            .map(|(bound, count)| (format!("_bucket{{le=\"{bound}\"}}"), count as f64))
+            .collect();
+        samples.push((
+            "_bucket{le=\"+Inf\"}".to_string(),
+            status.verifications as f64,
+        ));
+        samples.push(("_sum".to_string(), status.verification_seconds));
+        samples.push(("_count".to_string(), status.verifications as f64));
+        metric(
+            "prusti_verification_duration_seconds",
+            "histogram",
+            "Time spent verifying programs that were not found in the cache.",
+            &samples,
+        );
+
+        metric(
+            "prusti_cache_hits_total",
+            "counter",
+            "Verification requests whose result was found in the cache.",
+            &[(String::new(), status.cache_hits as f64)],
+        );
+        metric(
+            "prusti_cache_misses_total",
+            "counter",
+            "Verification requests whose result was not found in the cache.",
+            &[(String::new(), status.cache_misses as f64)],
+        );
+        if let Some(cache_hit_ratio) = status.cache_hit_ratio {
+            metric(
+                "prusti_cache_hit_ratio",
+                "gauge",
+                "Fraction of the cache lookups that were hits.",
+                &[(String::new(), cache_hit_ratio)],
+            );
+        }
+        metric(
+            "prusti_java_exceptions_total",
+            "counter",
+            "Verifications that failed with a Java exception.",
+            &[(String::new(), status.java_exceptions as f64)],
+        );
+
+        let samples: Vec<_> = status
+            .slowest_recent_programs
+            .iter()
+            .map(|timing| {
+                (
+                    format!("{{program=\"{}\"}}", escape_label_value(&timing.program)),
+                    timing.seconds,
+                )
+            })
+            .collect();
+        metric(
+            "prusti_slowest_recent_verification_seconds",
+            "gauge",
+            "Verification time of the slowest recently verified programs.",
+            &samples,
+        );
+        out
+    }
+}
+
+/// Escape a label value as required by the text format of Prometheus.
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
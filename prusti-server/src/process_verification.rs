@@ -4,7 +4,7 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use crate::{Backend, VerificationRequest, ViperBackendConfig};
+use crate::{Backend, SmtLibBackend, VerificationRequest, ViperBackendConfig, METRICS};
 use log::info;
 use once_cell::sync::Lazy;
 use prusti_common::{
@@ -13,7 +13,7 @@ use prusti_common::{
     vir::{program_normalization::NormalizationInfo, ToViper},
     Stopwatch,
 };
-use std::{fs::create_dir_all, path::PathBuf};
+use std::{fs::create_dir_all, path::PathBuf, time::Instant};
 use viper::{
     smt_manager::SmtManager, Cache, VerificationBackend, VerificationContext, VerificationResult,
 };
@@ -22,9 +22,18 @@ use viper::{
 pub fn process_verification_request<'v, 't: 'v>(
     verification_context: &'v Lazy<VerificationContext<'t>, impl Fn() -> VerificationContext<'t>>,
     mut request: VerificationRequest,
-    cache: impl Cache,
+    mut cache: impl Cache,
 ) -> viper::VerificationResult {
-    let ast_utils = verification_context.new_ast_utils();
+    let _in_flight = METRICS.track_in_flight();
+    let _span = tracing::info_span!(
+        target: tracing::PHASE_TARGET,
+        "verification request",
+        program = %request.program.get_name()
+    )
+    .entered();
+    // The SMT-LIB backend doesn't need the JVM, so only start it when needed.
+    let ast_utils = Lazy::new(|| verification_context.new_ast_utils());
+    let uses_viper = request.backend_config.backend != VerificationBackend::SmtLib;
 
     // Only for testing: Check that the normalization is reversible.
     if config::print_hash() {
@@ -83,13 +92,15 @@ pub fn process_verification_request<'v, 't: 'v>(
 
     // Early return in case of cache hit
     if config::enable_cache() {
-        if let Some(mut result) = cache.get(hash) {
+        let cached_result = cache.get(hash);
+        METRICS.record_cache_lookup(cached_result.is_some());
+        if let Some(mut result) = cached_result {
             info!(
                 "Using cached result {:?} for program {}",
                 &result,
                 request.program.get_name()
             );
-            if config::dump_viper_program() {
+            if config::dump_viper_program() && uses_viper {
                 ast_utils.with_local_frame(16, || {
                     let _ = build_or_dump_viper_program();
                 });
@@ -100,6 +111,11 @@ pub fn process_verification_request<'v, 't: 'v>(
     };
 
     let mut stopwatch = Stopwatch::start("prusti-server", "verifier startup");
+    let start = Instant::now();
+
+    // The backends of a portfolio are verified concurrently by the workers
+    // of a worker pool, which never send a portfolio to their worker.
+    debug_assert!(request.portfolio.is_empty());
 
     // Create a new verifier each time.
     // Workaround for https://github.com/viperproject/prusti-dev/issues/744
@@ -112,10 +128,17 @@ pub fn process_verification_request<'v, 't: 'v>(
             ),
             verification_context,
         ),
+        VerificationBackend::SmtLib => {
+            Backend::SmtLib(SmtLibBackend::new(config::smt_solver_path()))
+        }
     };
 
     stopwatch.start_next("backend verification");
     let mut result = backend.verify(&request.program);
+    METRICS.record_verification(request.program.get_name(), start.elapsed());
+    if matches!(result, VerificationResult::JavaException(_)) {
+        METRICS.record_java_exception();
+    }
 
     // Don't cache Java exceptions, which might be due to misconfigured paths.
     if config::enable_cache() && !matches!(result, VerificationResult::JavaException(_)) {
@@ -170,6 +193,7 @@ fn new_viper_verifier<'v, 't: 'v>(
                 format!("/logPrefix {log_dir_str}"),
                 //"--print".to_string(), "./log/boogie_program/program.bpl".to_string(),
             ]),
+            VerificationBackend::SmtLib => unreachable!("the SMT-LIB backend does not use Viper"),
         }
     } else {
         report_path = None;
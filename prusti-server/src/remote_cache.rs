@@ -0,0 +1,152 @@
+// © 2023, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use log::{info, warn};
+use prusti_common::config;
+use reqwest::{blocking::Client, StatusCode};
+use std::sync::{Arc, Mutex};
+use url::{ParseError, Url};
+use viper::{Cache, PersistentCache, VerificationBackend, VerificationResult};
+
+/// The header with which clients authenticate the results they upload.
+pub const REMOTE_CACHE_TOKEN_HEADER: &str = "x-prusti-cache-token";
+
+/// A client of the `/cache/<hash>` endpoints of a `prusti-server`, used to
+/// share verification results between machines.
+///
+/// Since the hash of a request is computed after normalizing the program, the
+/// hash identifies the content of the request and the stored results are
+/// independent of the machine that produced them.
+///
+/// The client blocks, so it must not be used from an asynchronous context.
+pub struct RemoteCacheClient {
+    client: Client,
+    server_url: Url,
+    token: Option<String>,
+}
+
+impl RemoteCacheClient {
+    pub fn new<S: ToString>(server_address: S) -> Result<Self, ParseError> {
+        let mut address = server_address.to_string();
+        if !address.starts_with("http") {
+            address = format!("http://{address}");
+        }
+        Ok(Self {
+            client: Client::new(),
+            server_url: Url::parse(address.as_str())?.join("cache/")?,
+            token: config::remote_cache_token(),
+        })
+    }
+
+    fn entry_url(&self, request: u64) -> Url {
+        self.server_url.join(&request.to_string()).unwrap()
+    }
+
+    /// Look up a result in the remote cache. Errors are logged and reported
+    /// as cache misses, so that verification can continue locally.
+    pub fn get(&self, request: u64) -> Option<VerificationResult> {
+        let response = self
+            .client
+            .get(self.entry_url(request))
+            .send()
+            .and_then(|response| response.error_for_status());
+        match response {
+            Ok(response) => match response.bytes() {
+                Ok(bytes) => bincode::deserialize(&bytes)
+                    .map_err(|err| warn!("Invalid result in the remote cache: {err}"))
+                    .ok(),
+                Err(err) => {
+                    warn!("Failed to read from the remote cache: {err}");
+                    None
+                }
+            },
+            Err(err) if err.status() == Some(StatusCode::NOT_FOUND) => None,
+            Err(err) => {
+                warn!("Failed to read from the remote cache: {err}");
+                None
+            }
+        }
+    }
+
+    /// Upload a result to the remote cache, authenticated with the token of
+    /// `REMOTE_CACHE_TOKEN`. Errors are only logged.
+    pub fn put(&self, request: u64, result: &VerificationResult) {
+        let body = bincode::serialize(result).expect("error encoding verification result");
+        let mut put = self.client.put(self.entry_url(request)).body(body);
+        if let Some(token) = &self.token {
+            put = put.header(REMOTE_CACHE_TOKEN_HEADER, token);
+        }
+        let response = put.send().and_then(|response| response.error_for_status());
+        if let Err(err) = response {
+            warn!("Failed to upload to the remote cache: {err}");
+        }
+    }
+}
+
+/// The local cache of a `RemoteCache`, which is either used by a single
+/// thread or shared, e.g. by the requests of a server.
+enum LocalCache<'a> {
+    Exclusive(&'a mut PersistentCache),
+    Shared(&'a Arc<Mutex<PersistentCache>>),
+}
+
+impl LocalCache<'_> {
+    fn with<R>(&mut self, f: impl FnOnce(&mut PersistentCache) -> R) -> R {
+        match self {
+            LocalCache::Exclusive(cache) => f(cache),
+            LocalCache::Shared(cache) => f(&mut cache.lock().unwrap()),
+        }
+    }
+}
+
+/// A cache that looks up results first in the local `PersistentCache` and then
+/// in a remote cache. New results are stored in both caches.
+pub struct RemoteCache<'a> {
+    remote: &'a RemoteCacheClient,
+    local: LocalCache<'a>,
+}
+
+impl<'a> RemoteCache<'a> {
+    pub fn new(remote: &'a RemoteCacheClient, local: &'a mut PersistentCache) -> Self {
+        Self {
+            remote,
+            local: LocalCache::Exclusive(local),
+        }
+    }
+
+    /// Like `new`, but for a local cache that is shared between threads. The
+    /// local cache is not locked while the remote cache is accessed.
+    pub fn shared(remote: &'a RemoteCacheClient, local: &'a Arc<Mutex<PersistentCache>>) -> Self {
+        Self {
+            remote,
+            local: LocalCache::Shared(local),
+        }
+    }
+}
+
+impl Cache for RemoteCache<'_> {
+    fn get(&mut self, request: u64) -> Option<VerificationResult> {
+        if let Some(result) = self.local.with(|local| local.get(request)) {
+            return Some(result);
+        }
+        let result = self.remote.get(request)?;
+        info!("Found result of request {request} in the remote cache");
+        self.local
+            .with(|local| local.insert(request, result.clone()));
+        Some(result)
+    }
+    /// The remote cache only stores the result, not the backend.
+    fn insert_from_backend(
+        mut self,
+        request: u64,
+        result: VerificationResult,
+        backend: Option<VerificationBackend>,
+    ) -> Option<VerificationResult> {
+        self.remote.put(request, &result);
+        self.local
+            .with(|local| local.insert_from_backend(request, result, backend))
+    }
+}
@@ -4,25 +4,125 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use crate::{process_verification_request, VerificationRequest};
+use crate::{
+    process_verification_request, ProtocolVersion, RemoteCache, RemoteCacheClient,
+    VerificationRequest, WorkerError, WorkerPool, METRICS, PROTOCOL_VERSION_HEADER,
+    REMOTE_CACHE_TOKEN_HEADER,
+};
 use log::info;
 use once_cell::sync::Lazy;
 use prusti_common::{config, Stopwatch};
 use std::{
-    net::{Ipv4Addr, SocketAddr},
+    net::{IpAddr, Ipv4Addr, SocketAddr},
     sync::{mpsc, Arc, Mutex},
     thread,
+    time::{Duration, Instant},
 };
 use tokio::runtime::Builder;
-use viper::{PersistentCache, Viper};
-use warp::Filter;
+use viper::{PersistentCache, VerificationResult, Viper};
+use warp::{http::StatusCode, Filter};
 
 #[derive(Debug)]
 struct BincodeReject(bincode::Error);
 impl warp::reject::Reject for BincodeReject {}
 
-pub fn start_server_on_port(port: u16) {
-    listen_on_port_with_address_callback(port, move |address| {
+#[derive(Debug)]
+struct DeadlineExceeded;
+impl warp::reject::Reject for DeadlineExceeded {}
+
+#[derive(Debug)]
+struct RequiresWorkers;
+impl warp::reject::Reject for RequiresWorkers {}
+
+#[derive(Debug)]
+struct IncompatibleClient(Option<String>);
+impl warp::reject::Reject for IncompatibleClient {}
+
+/// Report requests that exceeded their deadline, that need a server with
+/// workers or that come from an incompatible client with a dedicated status
+/// code, so that clients don't retry them.
+async fn handle_rejection(rejection: warp::Rejection) -> Result<impl warp::Reply, warp::Rejection> {
+    if rejection.find::<DeadlineExceeded>().is_some() {
+        Ok(warp::reply::with_status(
+            "the deadline of the request was exceeded".to_string(),
+            StatusCode::GATEWAY_TIMEOUT,
+        ))
+    } else if rejection.find::<RequiresWorkers>().is_some() {
+        Ok(warp::reply::with_status(
+            "the request has a deadline or a portfolio of backends, which only a server \
+            started with `--workers` supports"
+                .to_string(),
+            StatusCode::UNPROCESSABLE_ENTITY,
+        ))
+    } else if let Some(IncompatibleClient(client_version)) = rejection.find() {
+        Ok(warp::reply::with_status(
+            format!(
+                "the client has protocol version {}, but the server runs {}",
+                client_version.as_deref().unwrap_or("<unknown>"),
+                ProtocolVersion::current()
+            ),
+            StatusCode::CONFLICT,
+        ))
+    } else {
+        Err(rejection)
+    }
+}
+
+/// Reject verification requests of clients with an incompatible protocol
+/// version, if the server is configured to do so.
+fn check_client_version() -> impl Filter<Extract = (), Error = warp::Rejection> + Clone {
+    warp::header::optional::<String>(PROTOCOL_VERSION_HEADER)
+        .and_then(|client_version: Option<String>| async move {
+            let server_version = ProtocolVersion::current().fingerprint();
+            if !config::server_refuse_incompatible_clients()
+                || client_version.as_ref() == Some(&server_version)
+            {
+                Ok(())
+            } else {
+                info!(
+                    "Refusing a request with protocol version {:?}, expected {}",
+                    client_version, server_version
+                );
+                Err(warp::reject::custom(IncompatibleClient(client_version)))
+            }
+        })
+        .untuple_one()
+}
+
+/// The endpoints reporting the metrics of the server, in the text format of
+/// Prometheus (`/metrics`) and as JSON (`/status`).
+fn metrics_endpoints() -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone
+{
+    let metrics = warp::get()
+        .and(warp::path("metrics"))
+        .and(warp::path::end())
+        .map(|| {
+            warp::reply::with_header(
+                METRICS.prometheus(),
+                "content-type",
+                "text/plain; version=0.0.4",
+            )
+        });
+
+    let status = warp::get()
+        .and(warp::path("status"))
+        .and(warp::path::end())
+        .map(|| warp::reply::json(&METRICS.status()));
+
+    metrics.or(status)
+}
+
+/// The endpoint with which clients check that the server is compatible.
+fn version_endpoint() -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone
+{
+    warp::get()
+        .and(warp::path("version"))
+        .and(warp::path::end())
+        .map(|| warp::reply::json(&ProtocolVersion::current()))
+}
+
+pub fn start_server_on_port(bind_address: IpAddr, port: u16) {
+    listen_on_port_with_address_callback(bind_address, port, move |address| {
         if port == 0 {
             return;
         }
@@ -30,10 +130,219 @@ pub fn start_server_on_port(port: u16) {
     });
 }
 
+/// Start a server that verifies requests in parallel, by dispatching them to
+/// `workers` worker processes that each run their own JVM.
+pub fn start_worker_pool_server_on_port(bind_address: IpAddr, port: u16, workers: usize) {
+    let stopwatch = Stopwatch::start("prusti-server", "worker startup");
+    let pool = Arc::new(WorkerPool::new(workers).expect("failed to start the worker processes"));
+    stopwatch.finish();
+
+    let mut cache_data = PersistentCache::load_cache(config::cache_path());
+    cache_data.set_max_entries(config::cache_max_entries());
+    let cache = Arc::new(Mutex::new(cache_data));
+    let build_verification_request_handler =
+        |pool: Arc<WorkerPool>, cache: Arc<Mutex<PersistentCache>>| {
+            move |request: VerificationRequest| {
+                let pool = pool.clone();
+                let cache = cache.clone();
+                async move {
+                    pool.verify(request, &cache).await.map_err(|err| match err {
+                        WorkerError::DeadlineExceeded => warp::reject::custom(DeadlineExceeded),
+                        err => warp::reject::custom(err),
+                    })
+                }
+            }
+        };
+
+    let json_verify = warp::path!("json" / "verify")
+        .and(check_client_version())
+        .and(warp::body::json())
+        .and_then(build_verification_request_handler(
+            pool.clone(),
+            cache.clone(),
+        ))
+        .map(|response| warp::reply::json(&response));
+
+    let bincode_verify = warp::path!("bincode" / "verify")
+        .and(check_client_version())
+        .and(warp::body::bytes())
+        .and_then(|buf: warp::hyper::body::Bytes| async move {
+            bincode::deserialize(&buf).map_err(|err| {
+                info!("request bincode body error: {}", err);
+                warp::reject::custom(BincodeReject(err))
+            })
+        })
+        .and_then(build_verification_request_handler(pool, cache.clone()))
+        .map(|result| {
+            warp::http::Response::new(
+                bincode::serialize(&result).expect("could not encode verification result"),
+            )
+        });
+
+    let endpoints = json_verify
+        .or(bincode_verify)
+        .or(version_endpoint())
+        .or(metrics_endpoints())
+        .or(cache_endpoints(cache))
+        .recover(handle_rejection);
+
+    // The JVMs live in the worker processes, so here requests can be handled
+    // concurrently.
+    let runtime = Builder::new_multi_thread()
+        .thread_name("prusti-server")
+        .enable_all()
+        .build()
+        .expect("failed to construct Tokio runtime");
+
+    runtime.block_on(async {
+        info!(
+            "Prusti Server binding to {}:{} with {} workers",
+            bind_address, port, workers
+        );
+        let (address, server_loop) = warp::serve(endpoints).bind_ephemeral((bind_address, port));
+
+        println!("port: {}", address.port());
+        assert!(
+            port == 0 || address.port() == port,
+            "Server could not bind to port {port}"
+        );
+
+        info!("Prusti Server listening on port {}", address.port());
+        server_loop.await
+    });
+
+    unreachable!("The server unexpectedly stopped.");
+}
+
+#[derive(serde::Deserialize)]
+struct PruneQuery {
+    /// Entries not accessed for longer than this number of seconds are removed.
+    max_age: u64,
+}
+
+/// The endpoints to save, inspect and maintain the verification cache.
+fn cache_endpoints(
+    cache: Arc<Mutex<PersistentCache>>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let save_cache = warp::post()
+        .and(warp::path("save"))
+        .and(warp::path::end())
+        .map({
+            let cache = cache.clone();
+            move || {
+                cache.lock().unwrap().save();
+                warp::reply::html("Saved")
+            }
+        });
+
+    let cache_stats = warp::get().and(warp::path!("cache" / "stats")).map({
+        let cache = cache.clone();
+        move || warp::reply::json(&cache.lock().unwrap().statistics())
+    });
+
+    let prune_cache = warp::post()
+        .and(warp::path!("cache" / "prune"))
+        .and(warp::query::<PruneQuery>())
+        .map({
+            let cache = cache.clone();
+            move |query: PruneQuery| {
+                let mut cache = cache.lock().unwrap();
+                let pruned = cache.prune(Duration::from_secs(query.max_age));
+                cache.save();
+                warp::reply::html(format!("Pruned {pruned} entries"))
+            }
+        });
+
+    let clear_cache = warp::post().and(warp::path!("cache" / "clear")).map({
+        let cache = cache.clone();
+        move || {
+            let mut cache = cache.lock().unwrap();
+            cache.clear();
+            cache.save();
+            warp::reply::html("Cleared")
+        }
+    });
+
+    // Used by `RemoteCacheClient` to share the results between machines.
+    let get_result = warp::get().and(warp::path!("cache" / u64)).map({
+        let cache = cache.clone();
+        move |hash: u64| match cache.lock().unwrap().get(hash) {
+            Some(result) => warp::http::Response::new(
+                bincode::serialize(&result).expect("could not encode verification result"),
+            ),
+            None => warp::http::Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .body(vec![])
+                .unwrap(),
+        }
+    });
+
+    // Only clients that know the token may store results, since the results
+    // are used without being checked.
+    let upload_token = config::remote_cache_token();
+    let put_result = warp::put()
+        .and(warp::path!("cache" / u64))
+        .and(warp::header::optional::<String>(REMOTE_CACHE_TOKEN_HEADER))
+        .and(warp::body::bytes())
+        .map(
+            move |hash: u64, token: Option<String>, buf: warp::hyper::body::Bytes| {
+                let Some(upload_token) = &upload_token else {
+                    return StatusCode::FORBIDDEN;
+                };
+                if !token.map_or(false, |token| tokens_match(&token, upload_token)) {
+                    info!("Refusing to store a cache entry without a valid token");
+                    return StatusCode::UNAUTHORIZED;
+                }
+                match bincode::deserialize::<VerificationResult>(&buf) {
+                    // Java exceptions might be due to a misconfigured client.
+                    Ok(VerificationResult::JavaException(_)) => StatusCode::BAD_REQUEST,
+                    Ok(result) => {
+                        cache.lock().unwrap().insert(hash, result);
+                        StatusCode::OK
+                    }
+                    Err(err) => {
+                        info!("cache entry bincode body error: {}", err);
+                        StatusCode::BAD_REQUEST
+                    }
+                }
+            },
+        );
+
+    save_cache
+        .or(cache_stats)
+        .or(prune_cache)
+        .or(clear_cache)
+        .or(get_result)
+        .or(put_result)
+}
+
+/// Compare two tokens in a time that does not depend on the length of their
+/// common prefix, so that a token cannot be guessed character by character.
+fn tokens_match(token: &str, expected: &str) -> bool {
+    token.len() == expected.len()
+        && token
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+/// The client of the remote cache configured with `REMOTE_CACHE_ADDRESS`,
+/// if any. This must not be called from an asynchronous context.
+pub(crate) fn remote_cache_client() -> Option<Arc<RemoteCacheClient>> {
+    config::remote_cache_address().map(|address| {
+        info!("Using the remote cache at {}", address);
+        Arc::new(RemoteCacheClient::new(&address).unwrap_or_else(|error| {
+            panic!("Could not parse remote cache address ({address}) due to {error:?}")
+        }))
+    })
+}
+
 pub fn spawn_server_thread() -> SocketAddr {
     let (sender, receiver) = mpsc::channel();
     thread::spawn(move || {
         listen_on_port_with_address_callback(
+            Ipv4Addr::LOCALHOST.into(),
             0, // ask system for port
             move |address| sender.send(address).unwrap(),
         );
@@ -42,37 +351,83 @@ pub fn spawn_server_thread() -> SocketAddr {
     receiver.recv().unwrap()
 }
 
-fn listen_on_port_with_address_callback<F>(port: u16, address_callback: F) -> !
+fn listen_on_port_with_address_callback<F>(
+    bind_address: IpAddr,
+    port: u16,
+    address_callback: F,
+) -> !
 where
     F: FnOnce(SocketAddr),
 {
     let stopwatch = Stopwatch::start("prusti-server", "JVM startup");
     let viper = Arc::new(Lazy::new(|| {
-        Viper::new_with_args(&config::viper_home(), config::extra_jvm_args())
+        let start = Instant::now();
+        let viper = Viper::new_with_args(&config::viper_home(), config::extra_jvm_args());
+        METRICS.record_jvm_startup(start.elapsed());
+        viper
     }));
 
     stopwatch.finish();
 
-    let cache_data = PersistentCache::load_cache(config::cache_path());
+    let mut cache_data = PersistentCache::load_cache(config::cache_path());
+    cache_data.set_max_entries(config::cache_max_entries());
     let cache = Arc::new(Mutex::new(cache_data));
-    let build_verification_request_handler = |viper_arc: Arc<Lazy<Viper, _>>, cache| {
-        move |request: VerificationRequest| {
-            let stopwatch = Stopwatch::start("prusti-server", "attach thread to JVM");
-            let viper_thread = Lazy::new(|| viper_arc.attach_current_thread());
-            stopwatch.finish();
-            process_verification_request(&viper_thread, request, &cache)
-        }
-    };
+    let remote_cache = remote_cache_client();
+    // Viper is not thread safe, so the requests are verified one at a time.
+    let verification_lock = Arc::new(Mutex::new(()));
+    let build_verification_request_handler =
+        |viper_arc: Arc<Lazy<Viper, _>>, cache: Arc<Mutex<PersistentCache>>| {
+            let remote_cache = remote_cache.clone();
+            let verification_lock = verification_lock.clone();
+            move |request: VerificationRequest| {
+                let viper_arc = viper_arc.clone();
+                let cache = cache.clone();
+                let remote_cache = remote_cache.clone();
+                let verification_lock = verification_lock.clone();
+                async move {
+                    // Without workers a running verification cannot be aborted,
+                    // and the backends of a portfolio cannot run concurrently.
+                    if request.requires_workers() {
+                        info!(
+                            "Refusing request for program {}, which has a deadline or a portfolio",
+                            request.program.get_name()
+                        );
+                        return Err(warp::reject::custom(RequiresWorkers));
+                    }
+                    // The verification (including the remote cache) blocks, so it
+                    // runs outside of the asynchronous runtime.
+                    let result = tokio::task::spawn_blocking(move || {
+                        let _verification_lock = verification_lock.lock().unwrap();
+                        let stopwatch = Stopwatch::start("prusti-server", "attach thread to JVM");
+                        let viper_thread = Lazy::new(|| viper_arc.attach_current_thread());
+                        stopwatch.finish();
+                        match &remote_cache {
+                            Some(remote_cache) => process_verification_request(
+                                &viper_thread,
+                                request,
+                                RemoteCache::shared(remote_cache, &cache),
+                            ),
+                            None => process_verification_request(&viper_thread, request, &cache),
+                        }
+                    })
+                    .await
+                    .expect("the verification of a request panicked");
+                    Ok(result)
+                }
+            }
+        };
 
     let json_verify = warp::path!("json" / "verify")
+        .and(check_client_version())
         .and(warp::body::json())
-        .map(build_verification_request_handler(
+        .and_then(build_verification_request_handler(
             viper.clone(),
             cache.clone(),
         ))
         .map(|response| warp::reply::json(&response));
 
     let bincode_verify = warp::path!("bincode" / "verify")
+        .and(check_client_version())
         .and(warp::body::bytes())
         .and_then(|buf: warp::hyper::body::Bytes| async move {
             bincode::deserialize(&buf).map_err(|err| {
@@ -80,25 +435,23 @@ where
                 warp::reject::custom(BincodeReject(err))
             })
         })
-        .map(build_verification_request_handler(viper, cache.clone()))
+        .and_then(build_verification_request_handler(viper, cache.clone()))
         .map(|result| {
             warp::http::Response::new(
                 bincode::serialize(&result).expect("could not encode verification result"),
             )
         });
 
-    let save_cache = warp::post()
-        .and(warp::path("save"))
-        .and(warp::path::end())
-        .map(move || {
-            cache.lock().unwrap().save();
-            warp::reply::html("Saved")
-        });
-
-    let endpoints = json_verify.or(bincode_verify).or(save_cache);
+    let endpoints = json_verify
+        .or(bincode_verify)
+        .or(version_endpoint())
+        .or(metrics_endpoints())
+        .or(cache_endpoints(cache))
+        .recover(handle_rejection);
 
     // Here we use a single thread because
-    // 1. Viper is not thread safe yet (Silicon issue #578), and
+    // 1. Viper is not thread safe yet (Silicon issue #578), so the requests
+    //    are verified one at a time anyway, and
     // 2. By default Silicon already uses as many cores as possible.
     let runtime = Builder::new_current_thread()
         .thread_name("prusti-server")
@@ -107,9 +460,8 @@ where
         .expect("failed to construct Tokio runtime");
 
     runtime.block_on(async {
-        info!("Prusti Server binding to port {}", port);
-        let (address, server_loop) =
-            warp::serve(endpoints).bind_ephemeral((Ipv4Addr::LOCALHOST, port));
+        info!("Prusti Server binding to {}:{}", bind_address, port);
+        let (address, server_loop) = warp::serve(endpoints).bind_ephemeral((bind_address, port));
 
         println!("port: {}", address.port());
         address_callback(address);
@@ -0,0 +1,162 @@
+// © 2023, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! A backend that verifies predicate-free `vir::low` programs by translating
+//! them to SMT-LIB and running the SMT solver directly, without a JVM.
+//!
+//! The solver must be Z3: it is run with Z3's command line options and the
+//! script relies on Z3's options to instantiate quantifiers only with their
+//! triggers, as Viper does.
+
+mod translation;
+
+use log::{debug, info};
+use prusti_common::{config, report::log::report, vir::program::Program};
+use rustc_hash::FxHashSet;
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+    thread,
+};
+use translation::{Check, Script};
+use viper::{VerificationError, VerificationResult};
+
+pub struct SmtLibBackend {
+    solver_path: String,
+}
+
+impl SmtLibBackend {
+    /// A backend running the Z3 executable at `solver_path`.
+    pub fn new(solver_path: String) -> Self {
+        Self { solver_path }
+    }
+
+    pub fn verify(&self, program: &Program) -> VerificationResult {
+        let Program::Low(low_program) = program else {
+            return unsupported("programs that are not encoded with the core proof");
+        };
+        let script = match translation::translate(low_program) {
+            Ok(script) => script,
+            Err(translation::Unsupported(construct)) => return unsupported(construct),
+        };
+        let text = format!(
+            "(set-option :print-success false)\n\
+            (set-option :auto_config false)\n\
+            (set-option :smt.mbqi false)\n\
+            (set-option :timeout {})\n\
+            {}",
+            config::assert_timeout(),
+            script.text
+        );
+        if config::dump_viper_program() {
+            let filename = format!("{}.smt2", program.get_name_with_check_mode());
+            info!("Dumping SMT-LIB program to 'smt_lib_program/{}'", filename);
+            report("smt_lib_program", filename, &text);
+        }
+
+        let mut command = Command::new(&self.solver_path);
+        command.arg("-smt2").arg("-in");
+        let child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(err) => {
+                return VerificationResult::ConsistencyErrors(vec![format!(
+                    "could not run the SMT solver {}: {err}",
+                    self.solver_path
+                )])
+            }
+        };
+        // Write the script on a separate thread, so that the solver never
+        // blocks on a full output pipe.
+        let mut stdin = child.stdin.take().unwrap();
+        let writer = thread::spawn(move || stdin.write_all(text.as_bytes()));
+        let output = child.wait_with_output();
+        let _ = writer.join();
+        match output {
+            Ok(output) => interpret_output(
+                &script,
+                &String::from_utf8_lossy(&output.stdout),
+                &String::from_utf8_lossy(&output.stderr),
+            ),
+            Err(err) => VerificationResult::ConsistencyErrors(vec![format!(
+                "could not read the output of the SMT solver: {err}"
+            )]),
+        }
+    }
+}
+
+fn unsupported(construct: impl std::fmt::Display) -> VerificationResult {
+    VerificationResult::ConsistencyErrors(vec![format!(
+        "the SMT-LIB backend does not support {construct}"
+    )])
+}
+
+/// Map the answer of each `check-sat` command of the script to the assertion
+/// it checks.
+fn interpret_output(script: &Script, stdout: &str, stderr: &str) -> VerificationResult {
+    debug!("SMT solver output:\n{stdout}{stderr}");
+    let mut answers = 0;
+    let mut timed_out = false;
+    let mut solver_errors = vec![];
+    let mut reported = FxHashSet::default();
+    let mut errors = vec![];
+    let mut report_failure = |check: &Check, errors: &mut Vec<_>| {
+        if reported.insert((check.full_id, check.position.id, check.reason.id)) {
+            errors.push(verification_error(check));
+        }
+    };
+    for line in stdout.lines().map(str::trim) {
+        match line {
+            "" => {}
+            "unsat" => answers += 1,
+            "sat" | "unknown" => {
+                if let Some(check) = script.checks.get(answers) {
+                    report_failure(check, &mut errors);
+                }
+                answers += 1;
+            }
+            "timeout" => timed_out = true,
+            other => solver_errors.push(format!("the SMT solver reported: {other}")),
+        }
+    }
+    if !solver_errors.is_empty() {
+        return VerificationResult::ConsistencyErrors(solver_errors);
+    }
+    if answers < script.checks.len() {
+        if !timed_out {
+            return VerificationResult::ConsistencyErrors(vec![format!(
+                "the SMT solver stopped after {answers} of {} checks: {}",
+                script.checks.len(),
+                stderr.trim()
+            )]);
+        }
+        // The checks that were not completed might fail.
+        for check in &script.checks[answers..] {
+            report_failure(check, &mut errors);
+        }
+    }
+    if errors.is_empty() {
+        VerificationResult::Success
+    } else {
+        VerificationResult::Failure(errors)
+    }
+}
+
+fn verification_error(check: &Check) -> VerificationError {
+    let position_id = check.position.id.to_string();
+    VerificationError::new(
+        check.full_id.to_string(),
+        Some(position_id.clone()),
+        Some(position_id),
+        (!check.reason.is_default()).then(|| check.reason.id.to_string()),
+        check.message.clone(),
+        None,
+    )
+}
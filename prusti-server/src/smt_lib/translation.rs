@@ -0,0 +1,1500 @@
+// © 2023, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Translation of predicate-free `vir::low` programs to SMT-LIB scripts.
+//!
+//! Procedures are translated in static single assignment form. Each basic
+//! block is visited once, in topological order, and is guarded by a constant
+//! that holds iff the block is reached; the versions of the variables coming
+//! from different predecessors are merged when entering a block. As in Viper,
+//! a loop that remains in the control flow graph has the invariant `true`: the
+//! variables assigned in the loop are havocked at its head and its back edges
+//! are not followed. Every assertion, including the well-definedness of the
+//! evaluated expressions, becomes a `check-sat` command whose `sat` or
+//! `unknown` answer means that the assertion might not hold, mirroring how
+//! Viper reports the same program.
+//!
+//! Functions are encoded like Silicon does: the definition and the
+//! postconditions of a function are axiomatised with a limited version of the
+//! function as trigger, and the body of each function is checked to be
+//! well-defined and to satisfy the postconditions. The postconditions of
+//! functions without a body are assumed.
+
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::{collections::BTreeSet, fmt::Write};
+use vir::{
+    common::position::Positioned,
+    low::{
+        self as vir_low,
+        ast::statement,
+        expression::{self, BinaryOpKind, ConstantValue, QuantifierKind, UnaryOpKind},
+        Expression, FunctionDecl, MethodDecl, Position, Statement, Successor, Type,
+    },
+};
+
+/// The name of the variable holding the result in function postconditions.
+const RESULT_VARIABLE: &str = "__result";
+
+/// A construct that the SMT-LIB backend cannot translate.
+#[derive(Debug)]
+pub(super) struct Unsupported(pub String);
+
+type TranslationResult<T> = Result<T, Unsupported>;
+
+fn unsupported<T>(message: impl ToString) -> TranslationResult<T> {
+    Err(Unsupported(message.to_string()))
+}
+
+/// An assertion checked by a `check-sat` command of the script.
+pub(super) struct Check {
+    pub full_id: &'static str,
+    /// The position of the statement performing the check.
+    pub position: Position,
+    /// The position of the assertion that might not hold.
+    pub reason: Position,
+    pub message: String,
+}
+
+pub(super) struct Script {
+    pub text: String,
+    /// The checks, in the order of their `check-sat` commands.
+    pub checks: Vec<Check>,
+}
+
+pub(super) fn translate(program: &vir_low::Program) -> TranslationResult<Script> {
+    if !program.predicates.is_empty() {
+        return unsupported("programs with predicates");
+    }
+    let mut translator = Translator {
+        functions: program
+            .functions
+            .iter()
+            .map(|function| (function.name.as_str(), function))
+            .collect(),
+        methods: program
+            .methods
+            .iter()
+            .map(|method| (method.name.as_str(), method))
+            .collect(),
+        text: String::new(),
+        checks: vec![],
+        next_version: 0,
+        variable_sorts: FxHashMap::default(),
+        label_environments: FxHashMap::default(),
+        limited_function_calls: false,
+    };
+    translator.declarations(program)?;
+    for function in &program.functions {
+        translator.function(function)?;
+    }
+    for method in &program.methods {
+        translator.method(method)?;
+    }
+    for procedure in &program.procedures {
+        translator.procedure(procedure)?;
+    }
+    Ok(Script {
+        text: translator.text,
+        checks: translator.checks,
+    })
+}
+
+/// Maps the name of each variable to the SMT term holding its current value.
+type Environment = FxHashMap<String, String>;
+
+/// The states an expression can refer to, in addition to the current one.
+struct States<'a> {
+    /// The state referred to by `old(..)`.
+    old: &'a Environment,
+    /// The labels that were visited on every path to the current statement,
+    /// whose states can be referred to by `old[l](..)`.
+    labels: &'a FxHashSet<String>,
+}
+
+/// The symbolic state of the execution of a procedure.
+#[derive(Clone)]
+struct ExecutionState {
+    environment: Environment,
+    old: Environment,
+    labels: FxHashSet<String>,
+    /// The conditions under which the current statement is executed, besides
+    /// those already asserted in the current scope.
+    path_condition: Vec<String>,
+}
+
+/// A control flow edge into a block that was not visited yet.
+struct Incoming {
+    /// The condition under which the edge is taken.
+    condition: String,
+    environment: Environment,
+    labels: FxHashSet<String>,
+}
+
+/// What evaluates an expression, which determines how the failures of its
+/// well-definedness checks are reported.
+#[derive(Clone, Copy)]
+struct Evaluation {
+    position: Position,
+    division_full_id: &'static str,
+    message: &'static str,
+}
+
+impl Evaluation {
+    fn new(position: Position, division_full_id: &'static str, message: &'static str) -> Self {
+        Self {
+            position,
+            division_full_id,
+            message,
+        }
+    }
+
+    fn contract(position: Position) -> Self {
+        Self::new(
+            position,
+            "wellformedness.failed:division.by.zero",
+            "Contract might not be well-formed.",
+        )
+    }
+}
+
+/// A condition that must hold for an expression to be well-defined.
+struct Obligation {
+    /// The variables of the quantifiers around the subexpression.
+    variables: Vec<vir_low::VariableDecl>,
+    /// The conditions under which the subexpression is evaluated.
+    guards: Vec<String>,
+    term: String,
+    check: Check,
+}
+
+/// Collects the conditions under which an expression is well-defined: the
+/// preconditions of the applied functions hold and the divisors are not zero.
+struct WellDefinedness<'t, 'p, 's> {
+    translator: &'t Translator<'p>,
+    states: &'s States<'s>,
+    exhale: bool,
+    evaluation: Evaluation,
+    /// The variables of the quantifiers around the current subexpression.
+    variables: Vec<vir_low::VariableDecl>,
+    /// The conditions under which the current subexpression is evaluated.
+    guards: Vec<String>,
+    obligations: Vec<Obligation>,
+}
+
+impl WellDefinedness<'_, '_, '_> {
+    fn translate(
+        &self,
+        expression: &Expression,
+        environment: &Environment,
+    ) -> TranslationResult<String> {
+        self.translator
+            .expression(expression, environment, self.states, self.exhale)
+    }
+
+    fn obligation(&mut self, term: String, check: Check) {
+        self.obligations.push(Obligation {
+            variables: self.variables.clone(),
+            guards: self.guards.clone(),
+            term,
+            check,
+        });
+    }
+
+    /// Visit an expression that is only evaluated if `guard` holds.
+    fn guarded_visit(
+        &mut self,
+        guard: String,
+        expression: &Expression,
+        environment: &Environment,
+    ) -> TranslationResult<()> {
+        self.guards.push(guard);
+        self.visit(expression, environment)?;
+        self.guards.pop();
+        Ok(())
+    }
+
+    fn visit(
+        &mut self,
+        expression: &Expression,
+        environment: &Environment,
+    ) -> TranslationResult<()> {
+        match expression {
+            Expression::Local(_) | Expression::Constant(_) => {}
+            Expression::LabelledOld(old) => {
+                let translator = self.translator;
+                let states = self.states;
+                let old_environment = match &old.label {
+                    None => states.old,
+                    Some(label) => translator.label_environment(label, states)?,
+                };
+                self.visit(&old.base, old_environment)?;
+            }
+            Expression::UnaryOp(unary_op) => self.visit(&unary_op.argument, environment)?,
+            Expression::BinaryOp(binary_op) => {
+                self.visit(&binary_op.left, environment)?;
+                // The right operand of a short-circuiting operator is only
+                // evaluated if the left one does not determine the result.
+                match binary_op.op_kind {
+                    BinaryOpKind::And | BinaryOpKind::Implies => {
+                        let left = self.translate(&binary_op.left, environment)?;
+                        self.guarded_visit(left, &binary_op.right, environment)?;
+                    }
+                    BinaryOpKind::Or => {
+                        let left = self.translate(&binary_op.left, environment)?;
+                        self.guarded_visit(format!("(not {left})"), &binary_op.right, environment)?;
+                    }
+                    _ => self.visit(&binary_op.right, environment)?,
+                }
+                if matches!(binary_op.op_kind, BinaryOpKind::Div | BinaryOpKind::Mod) {
+                    let divisor = self.translate(&binary_op.right, environment)?;
+                    let check = Check {
+                        full_id: self.evaluation.division_full_id,
+                        position: self.evaluation.position,
+                        reason: binary_op.right.position(),
+                        message: format!(
+                            "{} Divisor {} might be zero.",
+                            self.evaluation.message, binary_op.right
+                        ),
+                    };
+                    self.obligation(format!("(not (= {divisor} 0))"), check);
+                }
+            }
+            Expression::Conditional(conditional) => {
+                self.visit(&conditional.guard, environment)?;
+                let guard = self.translate(&conditional.guard, environment)?;
+                self.guarded_visit(guard.clone(), &conditional.then_expr, environment)?;
+                self.guarded_visit(
+                    format!("(not {guard})"),
+                    &conditional.else_expr,
+                    environment,
+                )?;
+            }
+            Expression::Quantifier(quantifier) => {
+                let mut body_environment = environment.clone();
+                for variable in &quantifier.variables {
+                    body_environment.insert(variable.name.clone(), symbol(&variable.name));
+                }
+                let outer_variables = self.variables.len();
+                self.variables.extend(quantifier.variables.iter().cloned());
+                self.visit(&quantifier.body, &body_environment)?;
+                self.variables.truncate(outer_variables);
+            }
+            Expression::LetExpr(let_expr) => {
+                self.visit(&let_expr.def, environment)?;
+                // The obligations are stated outside of the `let`, so they
+                // refer to the definition of the variable.
+                let mut body_environment = environment.clone();
+                body_environment.insert(
+                    let_expr.variable.name.clone(),
+                    self.translate(&let_expr.def, environment)?,
+                );
+                self.visit(&let_expr.body, &body_environment)?;
+            }
+            Expression::FuncApp(app) => {
+                let mut arguments = vec![];
+                for argument in &app.arguments {
+                    self.visit(argument, environment)?;
+                    arguments.push(self.translate(argument, environment)?);
+                }
+                let translator = self.translator;
+                let Some(&function) = translator.functions.get(app.function_name.as_str()) else {
+                    return unsupported(format!(
+                        "calls of the unknown function {}",
+                        app.function_name
+                    ));
+                };
+                let function_environment: Environment = function
+                    .parameters
+                    .iter()
+                    .map(|parameter| parameter.name.clone())
+                    .zip(arguments)
+                    .collect();
+                let no_labels = FxHashSet::default();
+                let function_states = States {
+                    old: &function_environment,
+                    labels: &no_labels,
+                };
+                for pre in &function.pres {
+                    for conjunct in conjuncts(pre) {
+                        let term = translator.expression(
+                            conjunct,
+                            &function_environment,
+                            &function_states,
+                            true,
+                        )?;
+                        let check = Check {
+                            full_id: "application.precondition:assertion.false",
+                            position: app.position,
+                            reason: conjunct.position(),
+                            message: format!(
+                                "Precondition of function {} might not hold. \
+                                Assertion {conjunct} might not hold.",
+                                function.name
+                            ),
+                        };
+                        self.obligation(term, check);
+                    }
+                }
+            }
+            Expression::DomainFuncApp(app) => {
+                for argument in &app.arguments {
+                    self.visit(argument, environment)?;
+                }
+            }
+            Expression::InhaleExhale(inhale_exhale) => {
+                if self.exhale {
+                    self.visit(&inhale_exhale.exhale_expression, environment)?;
+                } else {
+                    self.visit(&inhale_exhale.inhale_expression, environment)?;
+                }
+            }
+            // These expressions cannot be translated, which is reported when
+            // translating them.
+            Expression::Field(_)
+            | Expression::MagicWand(_)
+            | Expression::PredicateAccessPredicate(_)
+            | Expression::FieldAccessPredicate(_)
+            | Expression::Unfolding(_)
+            | Expression::PermBinaryOp(_)
+            | Expression::ContainerOp(_) => {}
+        }
+        Ok(())
+    }
+}
+
+struct Translator<'p> {
+    functions: FxHashMap<&'p str, &'p FunctionDecl>,
+    methods: FxHashMap<&'p str, &'p MethodDecl>,
+    text: String,
+    checks: Vec<Check>,
+    /// Used to give a fresh name to each version of a variable.
+    next_version: usize,
+    /// The sorts of the variables of the current procedure.
+    variable_sorts: FxHashMap<String, String>,
+    /// The state at each label of the current procedure.
+    label_environments: FxHashMap<String, Environment>,
+    /// Whether calls of functions are translated to their limited versions,
+    /// which is the case in the axioms about functions.
+    limited_function_calls: bool,
+}
+
+/// Quote a VIR identifier so that it is a valid SMT-LIB symbol.
+fn symbol(name: &str) -> String {
+    format!("|{}|", name.replace(['|', '\\'], "_"))
+}
+
+/// The name of the version of a function whose applications do not trigger
+/// the axioms about the function.
+fn limited(function: &str) -> String {
+    format!("{function}%limited")
+}
+
+fn application(function: &str, arguments: &[String]) -> String {
+    if arguments.is_empty() {
+        symbol(function)
+    } else {
+        format!("({} {})", symbol(function), arguments.join(" "))
+    }
+}
+
+fn conjunction(terms: &[String]) -> String {
+    match terms {
+        [] => "true".to_string(),
+        [term] => term.clone(),
+        terms => format!("(and {})", terms.join(" ")),
+    }
+}
+
+fn disjunction(terms: &[String]) -> String {
+    match terms {
+        [] => "false".to_string(),
+        [term] => term.clone(),
+        terms => format!("(or {})", terms.join(" ")),
+    }
+}
+
+fn implication(assumptions: &[String], term: String) -> String {
+    if assumptions.is_empty() {
+        term
+    } else {
+        format!("(=> {} {term})", conjunction(assumptions))
+    }
+}
+
+/// The top-level conjuncts of an assertion, which are checked separately so
+/// that errors point to the conjunct that might not hold.
+fn conjuncts(expression: &Expression) -> Vec<&Expression> {
+    match expression {
+        Expression::BinaryOp(expression::BinaryOp {
+            op_kind: BinaryOpKind::And,
+            left,
+            right,
+            ..
+        }) => {
+            let mut result = conjuncts(left);
+            result.extend(conjuncts(right));
+            result
+        }
+        _ => vec![expression],
+    }
+}
+
+/// Collect the variables assigned by the statements.
+fn assigned_variables(statements: &[Statement], variables: &mut BTreeSet<String>) {
+    for statement in statements {
+        match statement {
+            Statement::Assign(statement) => {
+                variables.insert(statement.target.name.clone());
+            }
+            Statement::MethodCall(statement) => {
+                for target in &statement.targets {
+                    if let Expression::Local(target) = target {
+                        variables.insert(target.variable.name.clone());
+                    }
+                }
+            }
+            Statement::Conditional(statement) => {
+                assigned_variables(&statement.then_branch, variables);
+                assigned_variables(&statement.else_branch, variables);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn sort(ty: &Type) -> TranslationResult<String> {
+    match ty {
+        Type::Int => Ok("Int".to_string()),
+        Type::Bool => Ok("Bool".to_string()),
+        Type::Domain(domain) => Ok(symbol(&domain.name)),
+        _ => unsupported(format!("values of type {ty}")),
+    }
+}
+
+fn sorted_variables(variables: &[vir_low::VariableDecl]) -> TranslationResult<String> {
+    variables
+        .iter()
+        .map(|variable| {
+            Ok(format!(
+                "({} {})",
+                symbol(&variable.name),
+                sort(&variable.ty)?
+            ))
+        })
+        .collect::<TranslationResult<Vec<_>>>()
+        .map(|variables| variables.join(" "))
+}
+
+/// Quantify `body` over `variables`, if any.
+fn forall(
+    variables: &[vir_low::VariableDecl],
+    patterns: &[String],
+    body: String,
+) -> TranslationResult<String> {
+    if variables.is_empty() {
+        return Ok(body);
+    }
+    let patterns: String = patterns
+        .iter()
+        .map(|pattern| format!(" :pattern ({pattern})"))
+        .collect();
+    let body = if patterns.is_empty() {
+        body
+    } else {
+        format!("(! {body}{patterns})")
+    };
+    Ok(format!(
+        "(forall ({}) {body})",
+        sorted_variables(variables)?
+    ))
+}
+
+/// The control flow graph of a procedure.
+struct ControlFlowGraph {
+    /// The successors of each block, in the order in which they are tried.
+    successors: Vec<Vec<usize>>,
+    /// The blocks reachable from the entry block, in a topological order of
+    /// the edges that are not back edges.
+    order: Vec<usize>,
+    /// The edges that close loops.
+    back_edges: FxHashSet<(usize, usize)>,
+}
+
+impl ControlFlowGraph {
+    fn new(procedure: &vir_low::ProcedureDecl) -> TranslationResult<Self> {
+        let blocks: FxHashMap<_, _> = procedure
+            .basic_blocks
+            .iter()
+            .enumerate()
+            .map(|(index, block)| (block.label.name.as_str(), index))
+            .collect();
+        let target = |label: &vir_low::Label| match blocks.get(label.name.as_str()) {
+            Some(&target) => Ok(target),
+            None => unsupported(format!("jumps to the unknown label {}", label.name)),
+        };
+        let successors = procedure
+            .basic_blocks
+            .iter()
+            .map(|block| match &block.successor {
+                Successor::Return => Ok(vec![]),
+                Successor::Goto(label) => Ok(vec![target(label)?]),
+                Successor::GotoSwitch(targets) => {
+                    targets.iter().map(|(_, label)| target(label)).collect()
+                }
+            })
+            .collect::<TranslationResult<Vec<_>>>()?;
+
+        // A depth-first search, in which an edge to a block that is still on
+        // the stack is a back edge.
+        #[derive(Clone, Copy, PartialEq, Eq)]
+        enum Visit {
+            New,
+            OnStack,
+            Done,
+        }
+        let mut visits = vec![Visit::New; successors.len()];
+        let mut postorder = vec![];
+        let mut back_edges = FxHashSet::default();
+        let mut stack = vec![(0, 0)];
+        visits[0] = Visit::OnStack;
+        while let Some(top) = stack.last_mut() {
+            let block = top.0;
+            if let Some(&successor) = successors[block].get(top.1) {
+                top.1 += 1;
+                match visits[successor] {
+                    Visit::New => {
+                        visits[successor] = Visit::OnStack;
+                        stack.push((successor, 0));
+                    }
+                    Visit::OnStack => {
+                        back_edges.insert((block, successor));
+                    }
+                    Visit::Done => {}
+                }
+            } else {
+                visits[block] = Visit::Done;
+                postorder.push(block);
+                stack.pop();
+            }
+        }
+        postorder.reverse();
+        Ok(Self {
+            successors,
+            order: postorder,
+            back_edges,
+        })
+    }
+
+    /// The blocks of the loops closed by the back edges to each loop head:
+    /// the blocks that are reachable from the head and from which the source
+    /// of a back edge to the head is reachable.
+    fn loops(&self) -> FxHashMap<usize, FxHashSet<usize>> {
+        let mut predecessors = vec![vec![]; self.successors.len()];
+        for (block, successors) in self.successors.iter().enumerate() {
+            for &successor in successors {
+                predecessors[successor].push(block);
+            }
+        }
+        let reachable = |start: usize, edges: &[Vec<usize>]| {
+            let mut visited = FxHashSet::default();
+            let mut stack = vec![start];
+            while let Some(block) = stack.pop() {
+                if visited.insert(block) {
+                    stack.extend(edges[block].iter().copied());
+                }
+            }
+            visited
+        };
+        let mut loops: FxHashMap<usize, FxHashSet<usize>> = FxHashMap::default();
+        for &(source, head) in &self.back_edges {
+            let from_head = reachable(head, &self.successors);
+            let to_source = reachable(source, &predecessors);
+            loops
+                .entry(head)
+                .or_default()
+                .extend(from_head.intersection(&to_source));
+        }
+        loops
+    }
+}
+
+impl<'p> Translator<'p> {
+    fn declarations(&mut self, program: &'p vir_low::Program) -> TranslationResult<()> {
+        for domain in &program.domains {
+            writeln!(self.text, "(declare-sort {} 0)", symbol(&domain.name)).unwrap();
+        }
+
+        let mut unique_functions: FxHashMap<String, Vec<String>> = FxHashMap::default();
+        for domain in &program.domains {
+            for function in &domain.functions {
+                self.declare_function(&function.name, &function.parameters, &function.return_type)?;
+                // Unique functions are constants that differ from each other.
+                if function.is_unique {
+                    unique_functions
+                        .entry(sort(&function.return_type)?)
+                        .or_default()
+                        .push(symbol(&function.name));
+                }
+            }
+        }
+        for function in &program.functions {
+            self.declare_function(&function.name, &function.parameters, &function.return_type)?;
+            self.declare_function(
+                &limited(&function.name),
+                &function.parameters,
+                &function.return_type,
+            )?;
+        }
+
+        let no_labels = FxHashSet::default();
+        let no_state = Environment::default();
+        let states = States {
+            old: &no_state,
+            labels: &no_labels,
+        };
+        for constants in unique_functions.into_values() {
+            if constants.len() > 1 {
+                writeln!(self.text, "(assert (distinct {}))", constants.join(" ")).unwrap();
+            }
+        }
+        for domain in &program.domains {
+            for axiom in &domain.axioms {
+                let body = self.expression(&axiom.body, &no_state, &states, false)?;
+                writeln!(self.text, "; axiom {}\n(assert {body})", axiom.name).unwrap();
+            }
+        }
+
+        // The axioms about a function are triggered by its applications and
+        // only mention its limited version, so that they cannot be
+        // instantiated indefinitely. The function checks below make sure that
+        // they hold.
+        self.limited_function_calls = true;
+        for function in &program.functions {
+            let mut environment: Environment = function
+                .parameters
+                .iter()
+                .map(|parameter| (parameter.name.clone(), symbol(&parameter.name)))
+                .collect();
+            let arguments: Vec<_> = function
+                .parameters
+                .iter()
+                .map(|parameter| symbol(&parameter.name))
+                .collect();
+            let application = application(&function.name, &arguments);
+            let limited_application = self::application(&limited(&function.name), &arguments);
+            let axiom = forall(
+                &function.parameters,
+                &[application.clone()],
+                format!("(= {application} {limited_application})"),
+            )?;
+            writeln!(self.text, "(assert {axiom})").unwrap();
+            let pres = function
+                .pres
+                .iter()
+                .map(|pre| self.expression(pre, &environment, &states, false))
+                .collect::<TranslationResult<Vec<_>>>()?;
+            if let Some(body) = &function.body {
+                let body = self.expression(body, &environment, &states, false)?;
+                let definition = implication(&pres, format!("(= {application} {body})"));
+                let axiom = forall(&function.parameters, &[application.clone()], definition)?;
+                writeln!(
+                    self.text,
+                    "; definition of {}\n(assert {axiom})",
+                    function.name
+                )
+                .unwrap();
+            }
+            environment.insert(RESULT_VARIABLE.to_string(), limited_application.clone());
+            for post in &function.posts {
+                let post = self.expression(post, &environment, &states, false)?;
+                let axiom = forall(
+                    &function.parameters,
+                    &[limited_application.clone()],
+                    implication(&pres, post),
+                )?;
+                writeln!(
+                    self.text,
+                    "; postcondition of {}\n(assert {axiom})",
+                    function.name
+                )
+                .unwrap();
+            }
+        }
+        self.limited_function_calls = false;
+        Ok(())
+    }
+
+    fn declare_function(
+        &mut self,
+        name: &str,
+        parameters: &[vir_low::VariableDecl],
+        return_type: &Type,
+    ) -> TranslationResult<()> {
+        let parameter_sorts = parameters
+            .iter()
+            .map(|parameter| sort(&parameter.ty))
+            .collect::<TranslationResult<Vec<_>>>()?;
+        writeln!(
+            self.text,
+            "(declare-fun {} ({}) {})",
+            symbol(name),
+            parameter_sorts.join(" "),
+            sort(return_type)?
+        )
+        .unwrap();
+        Ok(())
+    }
+
+    /// Declare a fresh version of the variable and return its symbol.
+    fn fresh_version(&mut self, variable: &vir_low::VariableDecl) -> TranslationResult<String> {
+        let version = self.fresh_symbol(&variable.name);
+        let sort = sort(&variable.ty)?;
+        writeln!(self.text, "(declare-const {version} {sort})").unwrap();
+        self.variable_sorts.insert(variable.name.clone(), sort);
+        Ok(version)
+    }
+
+    fn fresh_symbol(&mut self, name: &str) -> String {
+        let version = symbol(&format!("{name}@{}", self.next_version));
+        self.next_version += 1;
+        version
+    }
+
+    /// Check that the contract and the body of the function are well-defined
+    /// and that the body satisfies the postconditions.
+    fn function(&mut self, function: &FunctionDecl) -> TranslationResult<()> {
+        writeln!(self.text, "; function {}\n(push 1)", function.name).unwrap();
+        let mut environment = Environment::default();
+        for parameter in &function.parameters {
+            let version = self.fresh_version(parameter)?;
+            environment.insert(parameter.name.clone(), version);
+        }
+        let old = environment.clone();
+        let no_labels = FxHashSet::default();
+        for pre in &function.pres {
+            let states = States {
+                old: &old,
+                labels: &no_labels,
+            };
+            let evaluation = Evaluation::contract(pre.position());
+            self.check_well_definedness(pre, &environment, &states, false, evaluation, &[])?;
+            let pre = self.expression(pre, &environment, &states, false)?;
+            self.assume(pre, &[]);
+        }
+        let states = States {
+            old: &old,
+            labels: &no_labels,
+        };
+        let result = if let Some(body) = &function.body {
+            let evaluation = Evaluation::new(
+                body.position(),
+                "function.not.wellformed:division.by.zero",
+                "Function might not be well-formed.",
+            );
+            self.check_well_definedness(body, &environment, &states, false, evaluation, &[])?;
+            self.expression(body, &environment, &states, false)?
+        } else {
+            let arguments: Vec<_> = function
+                .parameters
+                .iter()
+                .map(|parameter| environment[&parameter.name].clone())
+                .collect();
+            application(&function.name, &arguments)
+        };
+        environment.insert(RESULT_VARIABLE.to_string(), result);
+        for post in &function.posts {
+            let evaluation = Evaluation::contract(post.position());
+            self.check_well_definedness(post, &environment, &states, true, evaluation, &[])?;
+            for conjunct in conjuncts(post) {
+                let term = self.expression(conjunct, &environment, &states, true)?;
+                if function.body.is_some() {
+                    self.check(
+                        term.clone(),
+                        &[],
+                        Check {
+                            full_id: "postcondition.violated:assertion.false",
+                            position: Position::default(),
+                            reason: conjunct.position(),
+                            message: format!(
+                                "Postcondition of {} might not hold. \
+                                Assertion {conjunct} might not hold.",
+                                function.name
+                            ),
+                        },
+                    );
+                }
+                self.assume(term, &[]);
+            }
+        }
+        writeln!(self.text, "(pop 1)").unwrap();
+        Ok(())
+    }
+
+    /// Check that the contract of the method is well-defined.
+    fn method(&mut self, method: &MethodDecl) -> TranslationResult<()> {
+        if method.body.is_some() {
+            return unsupported(format!("the method {} with a body", method.name));
+        }
+        writeln!(self.text, "; method {}\n(push 1)", method.name).unwrap();
+        let mut environment = Environment::default();
+        for parameter in &method.parameters {
+            let version = self.fresh_version(parameter)?;
+            environment.insert(parameter.name.clone(), version);
+        }
+        let old = environment.clone();
+        let no_labels = FxHashSet::default();
+        for pre in &method.pres {
+            let states = States {
+                old: &old,
+                labels: &no_labels,
+            };
+            let evaluation = Evaluation::contract(pre.position());
+            self.check_well_definedness(pre, &environment, &states, false, evaluation, &[])?;
+            let pre = self.expression(pre, &environment, &states, false)?;
+            self.assume(pre, &[]);
+        }
+        for target in &method.targets {
+            let version = self.fresh_version(target)?;
+            environment.insert(target.name.clone(), version);
+        }
+        let states = States {
+            old: &old,
+            labels: &no_labels,
+        };
+        for post in &method.posts {
+            let evaluation = Evaluation::contract(post.position());
+            self.check_well_definedness(post, &environment, &states, false, evaluation, &[])?;
+            let post = self.expression(post, &environment, &states, false)?;
+            self.assume(post, &[]);
+        }
+        writeln!(self.text, "(pop 1)").unwrap();
+        Ok(())
+    }
+
+    fn procedure(&mut self, procedure: &vir_low::ProcedureDecl) -> TranslationResult<()> {
+        writeln!(self.text, "; procedure {}\n(push 1)", procedure.name).unwrap();
+        self.variable_sorts.clear();
+        self.label_environments.clear();
+        let mut environment = Environment::default();
+        for local in &procedure.locals {
+            let version = self.fresh_version(local)?;
+            environment.insert(local.name.clone(), version);
+        }
+        if !procedure.basic_blocks.is_empty() {
+            let cfg = ControlFlowGraph::new(procedure)?;
+            // The variables assigned in each loop are havocked at its head.
+            let havocked: FxHashMap<usize, BTreeSet<String>> = cfg
+                .loops()
+                .into_iter()
+                .map(|(head, blocks)| {
+                    let mut variables = BTreeSet::new();
+                    for block in blocks {
+                        assigned_variables(
+                            &procedure.basic_blocks[block].statements,
+                            &mut variables,
+                        );
+                    }
+                    (head, variables)
+                })
+                .collect();
+            let mut incoming: Vec<Vec<Incoming>> =
+                procedure.basic_blocks.iter().map(|_| Vec::new()).collect();
+            for &index in &cfg.order {
+                let mut state = if index == 0 {
+                    ExecutionState {
+                        old: environment.clone(),
+                        environment: environment.clone(),
+                        labels: FxHashSet::default(),
+                        path_condition: vec![],
+                    }
+                } else {
+                    self.join(std::mem::take(&mut incoming[index]), &environment)
+                };
+                if let Some(variables) = havocked.get(&index) {
+                    for variable in variables {
+                        if let Some(sort) = self.variable_sorts.get(variable).cloned() {
+                            let version = self.fresh_symbol(variable);
+                            writeln!(self.text, "(declare-const {version} {sort})").unwrap();
+                            state.environment.insert(variable.clone(), version);
+                        }
+                    }
+                }
+                self.block(procedure, &cfg, index, state, &mut incoming)?;
+            }
+        }
+        writeln!(self.text, "(pop 1)").unwrap();
+        Ok(())
+    }
+
+    /// Merge the states of the edges into a block.
+    fn join(&mut self, incoming: Vec<Incoming>, initial: &Environment) -> ExecutionState {
+        let conditions: Vec<_> = incoming.iter().map(|edge| edge.condition.clone()).collect();
+        let reached = self.fresh_symbol("reached");
+        writeln!(
+            self.text,
+            "(define-fun {reached} () Bool {})",
+            disjunction(&conditions)
+        )
+        .unwrap();
+        let mut names: Vec<_> = incoming[0].environment.keys().cloned().collect();
+        names.sort();
+        let mut environment = Environment::default();
+        for name in names {
+            let first = &incoming[0].environment[&name];
+            let version = if incoming
+                .iter()
+                .all(|edge| &edge.environment[&name] == first)
+            {
+                first.clone()
+            } else {
+                let version = self.fresh_symbol(&name);
+                writeln!(
+                    self.text,
+                    "(declare-const {version} {})",
+                    self.variable_sorts[&name]
+                )
+                .unwrap();
+                for edge in &incoming {
+                    writeln!(
+                        self.text,
+                        "(assert (=> {} (= {version} {})))",
+                        edge.condition, edge.environment[&name]
+                    )
+                    .unwrap();
+                }
+                version
+            };
+            environment.insert(name, version);
+        }
+        let mut labels = incoming[0].labels.clone();
+        for edge in &incoming[1..] {
+            labels.retain(|label| edge.labels.contains(label));
+        }
+        ExecutionState {
+            environment,
+            old: initial.clone(),
+            labels,
+            path_condition: vec![reached],
+        }
+    }
+
+    /// Execute the block and record the states on its outgoing edges.
+    fn block(
+        &mut self,
+        procedure: &vir_low::ProcedureDecl,
+        cfg: &ControlFlowGraph,
+        index: usize,
+        mut state: ExecutionState,
+        incoming: &mut [Vec<Incoming>],
+    ) -> TranslationResult<()> {
+        let block = &procedure.basic_blocks[index];
+        self.label_environments
+            .insert(block.label.name.clone(), state.environment.clone());
+        state.labels.insert(block.label.name.clone());
+        for statement in &block.statements {
+            self.statement(statement, &mut state)?;
+        }
+        let mut follow = |target: usize, guards: &[String]| {
+            if !cfg.back_edges.contains(&(index, target)) {
+                let mut condition = state.path_condition.clone();
+                condition.extend_from_slice(guards);
+                incoming[target].push(Incoming {
+                    condition: conjunction(&condition),
+                    environment: state.environment.clone(),
+                    labels: state.labels.clone(),
+                });
+            }
+        };
+        match &block.successor {
+            Successor::Return => {}
+            Successor::Goto(_) => follow(cfg.successors[index][0], &[]),
+            Successor::GotoSwitch(targets) => {
+                // The targets are tried in order and, as in the Viper
+                // encoding, it is an error if none of them is taken.
+                let mut not_taken = vec![];
+                for ((guard, _), &target) in targets.iter().zip(&cfg.successors[index]) {
+                    let evaluation = Evaluation::new(
+                        guard.position(),
+                        "if.failed:division.by.zero",
+                        "Conditional statement might fail.",
+                    );
+                    self.current_well_definedness(guard, &state, false, evaluation)?;
+                    let guard = self.current_expression(guard, &state, false)?;
+                    let mut guards = not_taken.clone();
+                    guards.push(guard.clone());
+                    follow(target, &guards);
+                    not_taken.push(format!("(not {guard})"));
+                }
+                let mut assumptions = state.path_condition.clone();
+                assumptions.extend(not_taken);
+                self.check(
+                    "false".to_string(),
+                    &assumptions,
+                    Check {
+                        full_id: "assert.failed:assertion.false",
+                        position: Position::default(),
+                        reason: Position::default(),
+                        message: "Assert might fail. Assertion false might not hold.".to_string(),
+                    },
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn statement(
+        &mut self,
+        statement: &Statement,
+        state: &mut ExecutionState,
+    ) -> TranslationResult<()> {
+        match statement {
+            Statement::Comment(statement) => {
+                writeln!(self.text, "; {}", statement.comment.replace('\n', " ")).unwrap();
+            }
+            Statement::LogEvent(statement) => {
+                let evaluation = Evaluation::new(
+                    statement.expression.position(),
+                    "inhale.failed:division.by.zero",
+                    "Inhale might fail.",
+                );
+                self.current_well_definedness(&statement.expression, state, false, evaluation)?;
+                let term = self.current_expression(&statement.expression, state, false)?;
+                self.assume(term, &state.path_condition);
+            }
+            Statement::Assume(statement::Assume {
+                expression,
+                position,
+            })
+            | Statement::Inhale(statement::Inhale {
+                expression,
+                position,
+            }) => {
+                let evaluation = Evaluation::new(
+                    *position,
+                    "inhale.failed:division.by.zero",
+                    "Inhale might fail.",
+                );
+                self.current_well_definedness(expression, state, false, evaluation)?;
+                let term = self.current_expression(expression, state, false)?;
+                self.assume(term, &state.path_condition);
+            }
+            Statement::Assert(statement) => self.assert(
+                &statement.expression,
+                "assert.failed:assertion.false",
+                Evaluation::new(
+                    statement.position,
+                    "assert.failed:division.by.zero",
+                    "Assert might fail.",
+                ),
+                state,
+            )?,
+            Statement::Exhale(statement) => self.assert(
+                &statement.expression,
+                "exhale.failed:assertion.false",
+                Evaluation::new(
+                    statement.position,
+                    "exhale.failed:division.by.zero",
+                    "Exhale might fail.",
+                ),
+                state,
+            )?,
+            Statement::Fold(_) | Statement::Unfold(_) | Statement::ApplyMagicWand(_) => {
+                return unsupported(format!("the statement `{statement}`"));
+            }
+            Statement::MethodCall(statement) => self.method_call(statement, state)?,
+            Statement::Assign(statement) => {
+                let evaluation = Evaluation::new(
+                    statement.position,
+                    "assignment.failed:division.by.zero",
+                    "Assignment might fail.",
+                );
+                self.current_well_definedness(&statement.value, state, false, evaluation)?;
+                let value = self.current_expression(&statement.value, state, false)?;
+                let version = self.fresh_version(&statement.target)?;
+                writeln!(self.text, "(assert (= {version} {value}))").unwrap();
+                state
+                    .environment
+                    .insert(statement.target.name.clone(), version);
+            }
+            Statement::Conditional(statement) => {
+                let evaluation = Evaluation::new(
+                    statement.position,
+                    "if.failed:division.by.zero",
+                    "Conditional statement might fail.",
+                );
+                self.current_well_definedness(&statement.guard, state, false, evaluation)?;
+                let guard = self.current_expression(&statement.guard, state, false)?;
+                let mut then_state = state.clone();
+                then_state.path_condition.push(guard.clone());
+                for statement in &statement.then_branch {
+                    self.statement(statement, &mut then_state)?;
+                }
+                let mut else_state = state.clone();
+                else_state.path_condition.push(format!("(not {guard})"));
+                for statement in &statement.else_branch {
+                    self.statement(statement, &mut else_state)?;
+                }
+                // Merge the variables assigned in either branch.
+                let mut merged = vec![];
+                for (name, then_value) in &then_state.environment {
+                    let else_value = &else_state.environment[name];
+                    if then_value != else_value {
+                        merged.push((name.clone(), then_value.clone(), else_value.clone()));
+                    }
+                }
+                merged.sort();
+                for (name, then_value, else_value) in merged {
+                    let version = self.fresh_symbol(&name);
+                    writeln!(
+                        self.text,
+                        "(define-fun {version} () {} (ite {guard} {then_value} {else_value}))",
+                        self.variable_sorts[&name],
+                    )
+                    .unwrap();
+                    state.environment.insert(name, version);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn method_call(
+        &mut self,
+        call: &statement::MethodCall,
+        state: &mut ExecutionState,
+    ) -> TranslationResult<()> {
+        let Some(&method) = self.methods.get(call.method_name.as_str()) else {
+            return unsupported(format!("calls of the unknown method {}", call.method_name));
+        };
+        let mut method_environment = Environment::default();
+        for (parameter, argument) in method.parameters.iter().zip(&call.arguments) {
+            let evaluation = Evaluation::new(
+                call.position,
+                "call.failed:division.by.zero",
+                "Method call might fail.",
+            );
+            self.current_well_definedness(argument, state, false, evaluation)?;
+            let argument = self.current_expression(argument, state, false)?;
+            method_environment.insert(parameter.name.clone(), argument);
+        }
+        let pre_state = method_environment.clone();
+        let no_labels = FxHashSet::default();
+
+        for pre in &method.pres {
+            for conjunct in conjuncts(pre) {
+                let states = States {
+                    old: &pre_state,
+                    labels: &no_labels,
+                };
+                let term = self.expression(conjunct, &method_environment, &states, true)?;
+                self.check(
+                    term.clone(),
+                    &state.path_condition,
+                    Check {
+                        full_id: "call.precondition:assertion.false",
+                        position: call.position,
+                        reason: conjunct.position(),
+                        message: format!(
+                            "The precondition of method {} might not hold. \
+                            Assertion {conjunct} might not hold.",
+                            method.name
+                        ),
+                    },
+                );
+                self.assume(term, &state.path_condition);
+            }
+        }
+
+        for (target, variable) in call.targets.iter().zip(&method.targets) {
+            let Expression::Local(target) = target else {
+                return unsupported(format!("the call target {target}"));
+            };
+            let version = self.fresh_version(&target.variable)?;
+            state
+                .environment
+                .insert(target.variable.name.clone(), version.clone());
+            method_environment.insert(variable.name.clone(), version);
+        }
+        for post in &method.posts {
+            let states = States {
+                old: &pre_state,
+                labels: &no_labels,
+            };
+            let term = self.expression(post, &method_environment, &states, false)?;
+            self.assume(term, &state.path_condition);
+        }
+        Ok(())
+    }
+
+    /// Check that the assertion is well-defined, then check each of its
+    /// conjuncts and assume it.
+    fn assert(
+        &mut self,
+        expression: &Expression,
+        full_id: &'static str,
+        evaluation: Evaluation,
+        state: &mut ExecutionState,
+    ) -> TranslationResult<()> {
+        self.current_well_definedness(expression, state, true, evaluation)?;
+        for conjunct in conjuncts(expression) {
+            let term = self.current_expression(conjunct, state, true)?;
+            self.check(
+                term.clone(),
+                &state.path_condition,
+                Check {
+                    full_id,
+                    position: evaluation.position,
+                    reason: conjunct.position(),
+                    message: format!(
+                        "{} Assertion {conjunct} might not hold.",
+                        evaluation.message
+                    ),
+                },
+            );
+            self.assume(term, &state.path_condition);
+        }
+        Ok(())
+    }
+
+    fn assume(&mut self, term: String, path_condition: &[String]) {
+        writeln!(self.text, "(assert {})", implication(path_condition, term)).unwrap();
+    }
+
+    /// Check whether `term` holds under the path condition.
+    fn check(&mut self, term: String, path_condition: &[String], check: Check) {
+        let mut assumptions = path_condition.to_vec();
+        assumptions.push(format!("(not {term})"));
+        writeln!(
+            self.text,
+            "(push 1)\n(assert {})\n(check-sat)\n(pop 1)",
+            conjunction(&assumptions)
+        )
+        .unwrap();
+        self.checks.push(check);
+    }
+
+    fn current_expression(
+        &self,
+        expression: &Expression,
+        state: &ExecutionState,
+        exhale: bool,
+    ) -> TranslationResult<String> {
+        let states = States {
+            old: &state.old,
+            labels: &state.labels,
+        };
+        self.expression(expression, &state.environment, &states, exhale)
+    }
+
+    fn current_well_definedness(
+        &mut self,
+        expression: &Expression,
+        state: &ExecutionState,
+        exhale: bool,
+        evaluation: Evaluation,
+    ) -> TranslationResult<()> {
+        let states = States {
+            old: &state.old,
+            labels: &state.labels,
+        };
+        self.check_well_definedness(
+            expression,
+            &state.environment,
+            &states,
+            exhale,
+            evaluation,
+            &state.path_condition,
+        )
+    }
+
+    /// Check under the path condition that the expression is well-defined,
+    /// and then assume it.
+    fn check_well_definedness(
+        &mut self,
+        expression: &Expression,
+        environment: &Environment,
+        states: &States,
+        exhale: bool,
+        evaluation: Evaluation,
+        path_condition: &[String],
+    ) -> TranslationResult<()> {
+        let mut well_definedness = WellDefinedness {
+            translator: self,
+            states,
+            exhale,
+            evaluation,
+            variables: vec![],
+            guards: vec![],
+            obligations: vec![],
+        };
+        well_definedness.visit(expression, environment)?;
+        for obligation in well_definedness.obligations {
+            let term = forall(
+                &obligation.variables,
+                &[],
+                implication(&obligation.guards, obligation.term),
+            )?;
+            self.check(term.clone(), path_condition, obligation.check);
+            self.assume(term, path_condition);
+        }
+        Ok(())
+    }
+
+    fn label_environment(&self, label: &str, states: &States) -> TranslationResult<&Environment> {
+        match self.label_environments.get(label) {
+            Some(environment) if states.labels.contains(label) => Ok(environment),
+            _ => unsupported(format!(
+                "the label {label}, which might not have been visited"
+            )),
+        }
+    }
+
+    /// Translate an expression evaluated in `environment`. `exhale` selects
+    /// the part of inhale-exhale expressions to use.
+    fn expression(
+        &self,
+        expression: &Expression,
+        environment: &Environment,
+        states: &States,
+        exhale: bool,
+    ) -> TranslationResult<String> {
+        let translate =
+            |expression: &Expression| self.expression(expression, environment, states, exhale);
+        let translate_all = |expressions: &[Expression]| {
+            expressions
+                .iter()
+                .map(&translate)
+                .collect::<TranslationResult<Vec<_>>>()
+        };
+        let term = match expression {
+            Expression::Local(local) => match environment.get(&local.variable.name) {
+                Some(term) => term.clone(),
+                None => return unsupported(format!("the undeclared variable {local}")),
+            },
+            Expression::LabelledOld(old) => {
+                let old_environment = match &old.label {
+                    None => states.old,
+                    Some(label) => self.label_environment(label, states)?,
+                };
+                self.expression(&old.base, old_environment, states, exhale)?
+            }
+            Expression::Constant(constant) => match (&constant.value, &constant.ty) {
+                (ConstantValue::Bool(value), Type::Bool) => value.to_string(),
+                (ConstantValue::Int(value), Type::Int) if *value < 0 => {
+                    format!("(- {})", value.unsigned_abs())
+                }
+                (ConstantValue::Int(value), Type::Int) => value.to_string(),
+                (ConstantValue::BigInt(value), Type::Int) => match value.strip_prefix('-') {
+                    Some(magnitude) => format!("(- {magnitude})"),
+                    None => value.clone(),
+                },
+                _ => {
+                    return unsupported(format!("the constant {constant} of type {}", constant.ty))
+                }
+            },
+            Expression::UnaryOp(unary_op) => {
+                let argument = translate(&unary_op.argument)?;
+                match unary_op.op_kind {
+                    UnaryOpKind::Not => format!("(not {argument})"),
+                    UnaryOpKind::Minus => format!("(- {argument})"),
+                }
+            }
+            Expression::BinaryOp(binary_op) => {
+                let left = translate(&binary_op.left)?;
+                let right = translate(&binary_op.right)?;
+                let operator = match binary_op.op_kind {
+                    BinaryOpKind::EqCmp => "=",
+                    BinaryOpKind::NeCmp => return Ok(format!("(not (= {left} {right}))")),
+                    BinaryOpKind::GtCmp => ">",
+                    BinaryOpKind::GeCmp => ">=",
+                    BinaryOpKind::LtCmp => "<",
+                    BinaryOpKind::LeCmp => "<=",
+                    BinaryOpKind::Add => "+",
+                    BinaryOpKind::Sub => "-",
+                    BinaryOpKind::Mul => "*",
+                    BinaryOpKind::Div => "div",
+                    BinaryOpKind::Mod => "mod",
+                    BinaryOpKind::And => "and",
+                    BinaryOpKind::Or => "or",
+                    BinaryOpKind::Implies => "=>",
+                };
+                format!("({operator} {left} {right})")
+            }
+            Expression::Conditional(conditional) => format!(
+                "(ite {} {} {})",
+                translate(&conditional.guard)?,
+                translate(&conditional.then_expr)?,
+                translate(&conditional.else_expr)?
+            ),
+            Expression::Quantifier(quantifier) => {
+                let mut body_environment = environment.clone();
+                for variable in &quantifier.variables {
+                    body_environment.insert(variable.name.clone(), symbol(&variable.name));
+                }
+                let translate_bound = |expression: &Expression| {
+                    self.expression(expression, &body_environment, states, exhale)
+                };
+                let body = translate_bound(&quantifier.body)?;
+                let patterns = quantifier
+                    .triggers
+                    .iter()
+                    .map(|trigger| {
+                        trigger
+                            .terms
+                            .iter()
+                            .map(&translate_bound)
+                            .collect::<TranslationResult<Vec<_>>>()
+                            .map(|terms| terms.join(" "))
+                    })
+                    .collect::<TranslationResult<Vec<_>>>()?;
+                match quantifier.kind {
+                    QuantifierKind::ForAll => forall(&quantifier.variables, &patterns, body)?,
+                    QuantifierKind::Exists => {
+                        let negated =
+                            forall(&quantifier.variables, &patterns, format!("(not {body})"))?;
+                        format!("(not {negated})")
+                    }
+                }
+            }
+            Expression::LetExpr(let_expr) => {
+                let def = translate(&let_expr.def)?;
+                let mut body_environment = environment.clone();
+                let variable = symbol(&let_expr.variable.name);
+                body_environment.insert(let_expr.variable.name.clone(), variable.clone());
+                let body = self.expression(&let_expr.body, &body_environment, states, exhale)?;
+                format!("(let (({variable} {def})) {body})")
+            }
+            Expression::FuncApp(app) => {
+                let arguments = translate_all(&app.arguments)?;
+                if self.limited_function_calls {
+                    application(&limited(&app.function_name), &arguments)
+                } else {
+                    application(&app.function_name, &arguments)
+                }
+            }
+            Expression::DomainFuncApp(app) => {
+                application(&app.function_name, &translate_all(&app.arguments)?)
+            }
+            Expression::InhaleExhale(inhale_exhale) => {
+                if exhale {
+                    translate(&inhale_exhale.exhale_expression)?
+                } else {
+                    translate(&inhale_exhale.inhale_expression)?
+                }
+            }
+            Expression::Field(_)
+            | Expression::MagicWand(_)
+            | Expression::PredicateAccessPredicate(_)
+            | Expression::FieldAccessPredicate(_)
+            | Expression::Unfolding(_)
+            | Expression::PermBinaryOp(_)
+            | Expression::ContainerOp(_) => {
+                return unsupported(format!("the expression `{expression}`"));
+            }
+        };
+        Ok(term)
+    }
+}
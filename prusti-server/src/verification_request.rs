@@ -6,21 +6,46 @@
 
 use prusti_common::{config, vir::program::Program};
 use rustc_hash::FxHasher;
-use std::hash::{Hash, Hasher};
+use std::{
+    hash::{Hash, Hasher},
+    time::Duration,
+};
 use viper::{self, VerificationBackend};
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Hash)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct VerificationRequest {
     pub program: Program,
     pub backend_config: ViperBackendConfig,
+    /// Further backends that verify the program concurrently with the one of
+    /// `backend_config`, each in a separate worker. The first conclusive
+    /// result is used and the other verifications are cancelled. Only a server
+    /// with workers runs portfolios, so other servers refuse them.
+    pub portfolio: Vec<ViperBackendConfig>,
+    /// The maximum time the server may spend verifying the request. When
+    /// exceeded, the verification is aborted. Only a server with workers can
+    /// abort a verification, so other servers refuse requests with a deadline.
+    pub timeout: Option<Duration>,
 }
 
 impl VerificationRequest {
+    /// The hash used to cache the result of the request. The deadline does not
+    /// influence the result, so it is not part of the hash.
     pub(crate) fn get_hash(&self) -> u64 {
         let mut hasher = FxHasher::default();
-        self.hash(&mut hasher);
+        self.program.hash(&mut hasher);
+        self.backend_config.hash(&mut hasher);
+        // Keep the hash of requests without a portfolio unchanged.
+        if !self.portfolio.is_empty() {
+            self.portfolio.hash(&mut hasher);
+        }
         hasher.finish()
     }
+
+    /// Whether the request can only be verified by a server with workers,
+    /// because it has a deadline or a portfolio.
+    pub fn requires_workers(&self) -> bool {
+        self.timeout.is_some() || !self.portfolio.is_empty()
+    }
 }
 
 /// The configuration for the viper backend, (i.e. verifier).
@@ -77,6 +102,7 @@ impl ViperBackendConfig {
             VerificationBackend::Carbon => {
                 verifier_args.extend(vec!["--disableAllocEncoding".to_string()]);
             }
+            VerificationBackend::SmtLib => {}
         }
         Self {
             backend,
@@ -0,0 +1,71 @@
+// © 2023, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::fmt;
+
+/// The version of the format of the requests and results exchanged between
+/// the client and the server. It must be incremented whenever this format
+/// changes in a way that is not captured by the other components of
+/// `ProtocolVersion`, e.g. when a field is added to `VerificationRequest`.
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// The HTTP header in which clients send the fingerprint of their
+/// `ProtocolVersion` along with each verification request.
+pub const PROTOCOL_VERSION_HEADER: &str = "prusti-protocol-version";
+
+/// Describes what a client and a server need to agree on to be able to
+/// exchange verification requests and results. It is returned by the
+/// `/version` endpoint of the server.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ProtocolVersion {
+    /// The version of Prusti. It is only reported to users, builds of
+    /// different versions are compatible if the other components match.
+    pub prusti_version: String,
+    pub protocol: u32,
+    /// The hash of the definitions of the VIR data structures.
+    pub vir_format: String,
+    /// The version of the format of the cached verification results.
+    pub cache_version: u64,
+}
+
+impl ProtocolVersion {
+    /// The version of this build of Prusti.
+    pub fn current() -> Self {
+        Self {
+            prusti_version: format!(
+                "{} ({})",
+                env!("CARGO_PKG_VERSION"),
+                option_env!("COMMIT_HASH").unwrap_or("<unknown>")
+            ),
+            protocol: PROTOCOL_VERSION,
+            vir_format: vir::FORMAT_HASH.to_string(),
+            cache_version: viper::RESULT_CACHE_VERSION,
+        }
+    }
+
+    /// A compact representation of the components that must match for two
+    /// builds to be compatible.
+    pub fn fingerprint(&self) -> String {
+        format!(
+            "{}-{}-{}",
+            self.protocol, self.vir_format, self.cache_version
+        )
+    }
+
+    pub fn is_compatible_with(&self, other: &ProtocolVersion) -> bool {
+        self.fingerprint() == other.fingerprint()
+    }
+}
+
+impl fmt::Display for ProtocolVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Prusti {} (protocol {}, VIR format {}, cache version {})",
+            self.prusti_version, self.protocol, self.vir_format, self.cache_version
+        )
+    }
+}
@@ -0,0 +1,400 @@
+// © 2023, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::{
+    server::remote_cache_client, PrustiClient, RemoteCacheClient, VerificationRequest, METRICS,
+};
+use futures::stream::{FuturesUnordered, StreamExt};
+use log::{error, info, warn};
+use prusti_common::{config, vir::program_normalization::NormalizationInfo};
+use std::{
+    env,
+    io::{self, BufRead, BufReader, Write},
+    iter, mem,
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    sync::{Arc, Mutex},
+    thread,
+    time::Instant,
+};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use viper::{PersistentCache, VerificationBackend, VerificationResult};
+
+/// A verification server process, with its own JVM, that verifies the
+/// requests dispatched to it by a `WorkerPool`.
+struct Worker {
+    id: usize,
+    process: Child,
+    client: PrustiClient,
+}
+
+impl Worker {
+    /// Start a new worker by running `executable` as a single-process server
+    /// on a port assigned by the OS.
+    fn spawn(id: usize, executable: &Path) -> io::Result<Self> {
+        let mut process = Command::new(executable)
+            .arg("--port")
+            .arg("0")
+            .arg("--workers")
+            .arg("0")
+            // The cache is shared by all workers, so it's managed by the pool.
+            .env("PRUSTI_ENABLE_CACHE", "false")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .spawn()?;
+
+        let mut stdout = BufReader::new(process.stdout.take().unwrap());
+        let mut port = None;
+        let mut line = String::new();
+        while port.is_none() && stdout.read_line(&mut line)? != 0 {
+            port = line
+                .trim()
+                .strip_prefix("port: ")
+                .and_then(|port| port.parse::<u16>().ok());
+            line.clear();
+        }
+        let Some(port) = port else {
+            let _ = process.kill();
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("worker {id} exited without reporting its port"),
+            ));
+        };
+
+        // Forward the rest of the output of the worker (e.g. when `print_hash`
+        // is enabled) to our own output.
+        thread::spawn(move || {
+            for line in stdout.lines().map_while(Result::ok) {
+                let mut out = io::stdout().lock();
+                let _ = writeln!(out, "{line}");
+            }
+        });
+
+        info!("Worker {id} listening on port {port}");
+        let client = match PrustiClient::new(format!("127.0.0.1:{port}")) {
+            Ok(client) => client,
+            Err(err) => {
+                let _ = process.kill();
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    format!("could not connect to worker {id}: {err}"),
+                ));
+            }
+        };
+        Ok(Self {
+            id,
+            process,
+            client,
+        })
+    }
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+/// The error returned when a worker could not produce a verification result.
+#[derive(Debug)]
+pub enum WorkerError {
+    /// The worker crashed or could not be reached.
+    Failed(String),
+    /// The worker did not verify the request within its deadline, and has
+    /// therefore been stopped.
+    DeadlineExceeded,
+}
+
+impl std::fmt::Display for WorkerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WorkerError::Failed(message) => write!(f, "{message}"),
+            WorkerError::DeadlineExceeded => write!(f, "the deadline of the request was exceeded"),
+        }
+    }
+}
+
+impl warp::reject::Reject for WorkerError {}
+
+/// A worker that is verifying a request. If the request is cancelled (e.g.
+/// because the client disconnected or the deadline was exceeded) or the
+/// worker needs to be restarted, dropping the guard kills the worker, which
+/// aborts its verification, and replaces it with a fresh worker.
+struct BusyWorker {
+    worker: Option<Worker>,
+    permit: Option<OwnedSemaphorePermit>,
+    executable: PathBuf,
+    idle_workers: Arc<Mutex<Vec<Worker>>>,
+}
+
+impl BusyWorker {
+    fn worker(&self) -> &Worker {
+        self.worker.as_ref().unwrap()
+    }
+
+    /// Make the worker available for the next request.
+    fn release(mut self) {
+        let worker = self.worker.take().unwrap();
+        self.idle_workers.lock().unwrap().push(worker);
+        drop(self.permit.take());
+    }
+}
+
+impl Drop for BusyWorker {
+    fn drop(&mut self) {
+        let Some(worker) = self.worker.take() else {
+            return;
+        };
+        let id = worker.id;
+        let permit = self.permit.take().unwrap();
+        let executable = self.executable.clone();
+        let idle_workers = self.idle_workers.clone();
+        // Starting a worker is blocking, and this might be running in an
+        // asynchronous context.
+        thread::spawn(move || {
+            info!("Restarting worker {id}");
+            drop(worker);
+            match Worker::spawn(id, &executable) {
+                Ok(worker) => {
+                    idle_workers.lock().unwrap().push(worker);
+                    drop(permit);
+                }
+                Err(err) => {
+                    error!("Failed to restart worker {id}: {err}");
+                    // The pool permanently loses a worker.
+                    permit.forget();
+                }
+            }
+        });
+    }
+}
+
+/// A pool of worker processes, each with its own JVM. Since Viper is not
+/// thread safe, this is how the server verifies several requests in parallel.
+pub struct WorkerPool {
+    executable: PathBuf,
+    idle_workers: Arc<Mutex<Vec<Worker>>>,
+    available_workers: Arc<Semaphore>,
+    /// The cache configured with `REMOTE_CACHE_ADDRESS`, which is looked up
+    /// after the local cache.
+    remote_cache: Option<Arc<RemoteCacheClient>>,
+}
+
+impl WorkerPool {
+    /// Start `size` workers, each running the current executable. This must
+    /// not be called from an asynchronous context.
+    pub fn new(size: usize) -> io::Result<Self> {
+        assert!(size > 0, "a worker pool needs at least one worker");
+        let executable = env::current_exe()?;
+        let workers = (0..size)
+            .map(|id| Worker::spawn(id, &executable))
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(Self {
+            executable,
+            idle_workers: Arc::new(Mutex::new(workers)),
+            available_workers: Arc::new(Semaphore::new(size)),
+            remote_cache: remote_cache_client(),
+        })
+    }
+
+    /// Wait until a worker is idle and reserve it.
+    async fn acquire(&self) -> BusyWorker {
+        let permit = self
+            .available_workers
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("the worker pool is never closed");
+        let worker = self
+            .idle_workers
+            .lock()
+            .unwrap()
+            .pop()
+            .expect("there should be an idle worker for each permit");
+        BusyWorker {
+            worker: Some(worker),
+            permit: Some(permit),
+            executable: self.executable.clone(),
+            idle_workers: self.idle_workers.clone(),
+        }
+    }
+
+    /// Verify the request on the first idle worker, using the shared `cache`.
+    /// If the request has a portfolio of backends, each backend verifies it on
+    /// a separate worker. If the future is dropped before completion, the
+    /// verification is aborted.
+    pub async fn verify(
+        &self,
+        mut request: VerificationRequest,
+        cache: &Arc<Mutex<PersistentCache>>,
+    ) -> Result<VerificationResult, WorkerError> {
+        // Normalize the request before reaching the cache.
+        let normalization_info = NormalizationInfo::normalize_program(&mut request.program);
+        let hash = request.get_hash();
+        let program_name = request.program.get_name().to_string();
+
+        // When only printing hashes the workers must receive every request.
+        let use_cache = config::enable_cache() && !config::print_hash();
+        if use_cache {
+            let mut cached_result = {
+                let mut cache = cache.lock().unwrap();
+                cache.get(hash).map(|result| (result, cache.backend(hash)))
+            };
+            if cached_result.is_none() {
+                if let Some(remote_cache) = self.remote_cache.clone() {
+                    // The client of the remote cache blocks.
+                    let remote_result = tokio::task::spawn_blocking(move || remote_cache.get(hash))
+                        .await
+                        .expect("the lookup in the remote cache panicked");
+                    if let Some(result) = remote_result {
+                        info!("Found result of request {hash} in the remote cache");
+                        cache.lock().unwrap().insert(hash, result.clone());
+                        cached_result = Some((result, None));
+                    }
+                }
+            }
+            METRICS.record_cache_lookup(cached_result.is_some());
+            if let Some((mut result, backend)) = cached_result {
+                match backend {
+                    Some(backend) => info!(
+                        "Using cached result {result:?} of {backend} for program {program_name}"
+                    ),
+                    None => info!("Using cached result {result:?} for program {program_name}"),
+                }
+                normalization_info.denormalize_result(&mut result);
+                return Ok(result);
+            }
+        }
+
+        // The deadline is enforced here, by stopping the workers. The workers
+        // are stopped as well when the client disconnects, because the server
+        // then drops this future.
+        let timeout = request.timeout.take();
+        let is_portfolio = !request.portfolio.is_empty();
+        let start = Instant::now();
+        let verification = self.verify_portfolio(request);
+        let verification_result = match timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, verification).await {
+                Ok(verification_result) => verification_result,
+                Err(_) => {
+                    warn!("Verification of program {program_name} exceeded its deadline");
+                    Err(WorkerError::DeadlineExceeded)
+                }
+            },
+            None => verification.await,
+        };
+        METRICS.record_verification(&program_name, start.elapsed());
+        let (winner, mut result) = verification_result?;
+        if is_portfolio {
+            info!("{winner} gave the result of the portfolio for program {program_name}");
+        }
+
+        // Don't cache Java exceptions, which might be due to misconfigured paths.
+        if use_cache && !matches!(result, VerificationResult::JavaException(_)) {
+            info!("Storing new cached result {result:?} for program {program_name}");
+            cache.lock().unwrap().insert_from_backend(
+                hash,
+                result.clone(),
+                is_portfolio.then_some(winner),
+            );
+            if let Some(remote_cache) = self.remote_cache.clone() {
+                // The upload does not delay the reply.
+                let result = result.clone();
+                tokio::task::spawn_blocking(move || remote_cache.put(hash, &result));
+            }
+        }
+
+        normalization_info.denormalize_result(&mut result);
+        Ok(result)
+    }
+
+    /// Concurrently verify the request with each backend of its portfolio,
+    /// returning the first conclusive result and the backend that produced
+    /// it. The remaining verifications are cancelled. If no result is
+    /// conclusive, the one of the first backend is returned.
+    async fn verify_portfolio(
+        &self,
+        mut request: VerificationRequest,
+    ) -> Result<(VerificationBackend, VerificationResult), WorkerError> {
+        let portfolio = mem::take(&mut request.portfolio);
+        let mut verifications: FuturesUnordered<_> = iter::once(request.backend_config.clone())
+            .chain(portfolio)
+            .enumerate()
+            .map(|(index, backend_config)| {
+                let request = VerificationRequest {
+                    backend_config,
+                    ..request.clone()
+                };
+                async move {
+                    let backend = request.backend_config.backend;
+                    (index, backend, self.verify_on_worker(request).await)
+                }
+            })
+            .collect();
+        let mut inconclusive = vec![];
+        while let Some((index, backend, verification_result)) = verifications.next().await {
+            match verification_result {
+                // Dropping the other verifications cancels them.
+                Ok(result) if result.is_conclusive() => return Ok((backend, result)),
+                verification_result => {
+                    info!(
+                        "{backend} did not give a conclusive result for program {}",
+                        request.program.get_name()
+                    );
+                    inconclusive.push((index, backend, verification_result));
+                }
+            }
+        }
+        let (_, backend, verification_result) = inconclusive
+            .into_iter()
+            .min_by_key(|(index, _, _)| *index)
+            .unwrap();
+        verification_result.map(|result| (backend, result))
+    }
+
+    /// Verify the request on the first idle worker.
+    async fn verify_on_worker(
+        &self,
+        request: VerificationRequest,
+    ) -> Result<VerificationResult, WorkerError> {
+        let program_name = request.program.get_name().to_string();
+        let queued = METRICS.track_queued();
+        let busy_worker = self.acquire().await;
+        drop(queued);
+        let _in_flight = METRICS.track_in_flight();
+        info!(
+            "Dispatching program {program_name} to worker {} ({})",
+            busy_worker.worker().id,
+            request.backend_config.backend
+        );
+        match busy_worker.worker().client.verify_once(request).await {
+            Ok(VerificationResult::JavaException(exception)) => {
+                METRICS.record_java_exception();
+                warn!(
+                    "Worker {} raised a Java exception while verifying program {program_name}",
+                    busy_worker.worker().id
+                );
+                // Dropping the worker restarts it.
+                drop(busy_worker);
+                Ok(VerificationResult::JavaException(exception))
+            }
+            Ok(result) => {
+                busy_worker.release();
+                Ok(result)
+            }
+            Err(err) => {
+                error!(
+                    "Worker {} failed while verifying program {program_name}: {err}",
+                    busy_worker.worker().id
+                );
+                Err(WorkerError::Failed(format!(
+                    "the worker verifying program {program_name} crashed: {err}"
+                )))
+            }
+        }
+    }
+}
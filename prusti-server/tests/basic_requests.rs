@@ -1,9 +1,10 @@
 use lazy_static::lazy_static;
 use prusti_common::vir::*;
 use prusti_server::{
-    spawn_server_thread, tokio::runtime::Builder, PrustiClient, VerificationRequest,
-    ViperBackendConfig,
+    spawn_server_thread, tokio::runtime::Builder, ClientError, ProtocolVersion, PrustiClient,
+    ServerStatus, VerificationRequest, ViperBackendConfig,
 };
+use std::time::Duration;
 use viper::VerificationResult;
 
 lazy_static! {
@@ -36,12 +37,85 @@ fn empty_program() {
     }
 }
 
+#[test]
+fn version_endpoint() {
+    let version: ProtocolVersion =
+        reqwest::blocking::get(format!("http://{}/version", *SERVER_ADDRESS))
+            .expect("Could not connect to server!")
+            .json()
+            .expect("Invalid version");
+
+    assert_eq!(version, ProtocolVersion::current());
+}
+
+#[test]
+fn metrics_endpoints() {
+    process_program(|_| ());
+
+    let status: ServerStatus = reqwest::blocking::get(format!("http://{}/status", *SERVER_ADDRESS))
+        .expect("Could not connect to server!")
+        .json()
+        .expect("Invalid status");
+    assert!(status.verifications + status.cache_hits > 0);
+
+    let metrics = reqwest::blocking::get(format!("http://{}/metrics", *SERVER_ADDRESS))
+        .expect("Could not connect to server!")
+        .text()
+        .expect("Invalid metrics");
+    assert!(metrics.contains("# TYPE prusti_verification_duration_seconds histogram"));
+    assert!(metrics.contains("prusti_requests_in_flight "));
+}
+
+#[test]
+fn cache_uploads_require_a_token() {
+    let response = reqwest::blocking::Client::new()
+        .put(format!("http://{}/cache/42", *SERVER_ADDRESS))
+        .body(bincode::serialize(&VerificationResult::Success).unwrap())
+        .send()
+        .expect("Could not connect to server!");
+    // The server of the tests has no `REMOTE_CACHE_TOKEN`.
+    assert_eq!(response.status(), reqwest::StatusCode::FORBIDDEN);
+
+    let response = reqwest::blocking::get(format!("http://{}/cache/42", *SERVER_ADDRESS))
+        .expect("Could not connect to server!");
+    assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+}
+
+#[test]
+fn deadlines_require_workers() {
+    let mut request = build_request(|_| ());
+    request.timeout = Some(Duration::from_secs(60));
+
+    match send_request(request) {
+        Err(ClientError::RequiresWorkers) => {}
+        other => panic!("request with a deadline not refused, instead found {other:?}"),
+    }
+}
+
+#[test]
+fn portfolios_require_workers() {
+    let mut request = build_request(|_| ());
+    request
+        .portfolio
+        .push(ViperBackendConfig::new(viper::VerificationBackend::Carbon));
+
+    match send_request(request) {
+        Err(ClientError::RequiresWorkers) => {}
+        other => panic!("request with a portfolio not refused, instead found {other:?}"),
+    }
+}
+
 fn process_program<F>(configure: F) -> VerificationResult
 where
     F: FnOnce(&mut Program),
 {
-    let client = PrustiClient::new(SERVER_ADDRESS.clone()).expect("Could not connect to server!");
+    send_request(build_request(configure)).expect("Verification request failed")
+}
 
+fn build_request<F>(configure: F) -> VerificationRequest
+where
+    F: FnOnce(&mut Program),
+{
     let mut program = Program {
         name: "dummy".to_string(),
         backend_types: vec![],
@@ -54,17 +128,22 @@ where
     };
     configure(&mut program);
 
-    let request = VerificationRequest {
+    VerificationRequest {
         program: prusti_common::vir::program::Program::Legacy(program),
         backend_config: ViperBackendConfig::new(
             prusti_common::config::viper_backend().parse().unwrap(),
         ),
-    };
+        portfolio: vec![],
+        timeout: None,
+    }
+}
+
+fn send_request(request: VerificationRequest) -> Result<VerificationResult, ClientError> {
+    let client = PrustiClient::new(SERVER_ADDRESS.clone()).expect("Could not connect to server!");
 
     Builder::new_current_thread()
         .enable_all()
         .build()
         .expect("failed to construct Tokio runtime")
         .block_on(client.verify(request))
-        .expect("Verification request failed")
 }
@@ -78,6 +78,141 @@ error: could not compile `foo` (bin \"foo\") due to previous error; 1 warning em
         .run();
 }
 
+#[cargo_test]
+fn verification_report() {
+    let p = project()
+        .file("src/main.rs", "fn main() { assert!(false); }")
+        .build();
+    let report_dir = p.root().join("report");
+    p.process(cargo_prusti_path())
+        .env("PRUSTI_REPORT_DIR", &report_dir)
+        .with_status(101)
+        .run();
+
+    let report = fs::read_to_string(report_dir.join("foo-main.json")).unwrap();
+    assert!(report.contains(r#""name": "foo::main""#));
+    assert!(report.contains(r#""status": "failed""#));
+    assert!(report.contains(r#""kind": "Panic""#));
+    let sarif = fs::read_to_string(report_dir.join("foo-main.sarif")).unwrap();
+    assert!(sarif.contains(r#""version": "2.1.0""#));
+    assert!(sarif.contains(r#""ruleId": "Panic""#));
+}
+
+#[cargo_test]
+fn junit_report() {
+    let p = project()
+        .file("src/main.rs", "fn main() { assert!(false); }")
+        .build();
+    p.process(cargo_prusti_path())
+        .with_status(101)
+        .with_stdout_contains("foo-main [..]")
+        .run();
+
+    let junit = fs::read_to_string(p.root().join("target/verify/report/junit.xml")).unwrap();
+    assert!(junit.contains(r#"<testsuite name="foo-main" tests="1" failures="1""#));
+    assert!(junit.contains(r#"<testcase classname="foo" name="foo::main""#));
+    assert!(junit.contains(r#"<failure message="the asserted expression might not hold""#));
+}
+
+#[cargo_test]
+fn verification_profile() {
+    let p = project()
+        .file(
+            "src/main.rs",
+            "fn inc(x: u32) -> u32 { x.wrapping_add(1) }\nfn main() { inc(0); }",
+        )
+        .build();
+    p.process(cargo_prusti_path())
+        .env("PRUSTI_PROFILE_VERIFICATION", "true")
+        .with_stderr_contains("Slowest verified items [..]")
+        .with_stderr_contains("foo::inc [..]")
+        .run();
+
+    let profile =
+        fs::read_to_string(p.root().join("target/verify/log/profile/foo-main.rs.txt")).unwrap();
+    assert!(profile.starts_with("item "));
+    assert!(profile.contains("foo::main "));
+}
+
+#[cargo_test]
+fn chrome_trace() {
+    let p = project()
+        .file("src/main.rs", "fn main() { assert!(true); }")
+        .build();
+    p.process(cargo_prusti_path())
+        .env("PRUSTI_CHROME_TRACE", "true")
+        .run();
+
+    let trace =
+        fs::read_to_string(p.root().join("target/verify/log/chrome_trace/foo-bin.json")).unwrap();
+    assert!(trace.contains(r#""name":"spec collection""#));
+    assert!(trace.contains(r#""name":"encode procedure""#));
+    assert!(trace.contains(r#""name":"verification request""#));
+}
+
+#[cargo_test]
+fn verify_selected_items() {
+    let p = project()
+        .file(
+            "src/main.rs",
+            "fn ok() { assert!(true); }\nfn fails() { assert!(false); }\nfn main() { ok(); }",
+        )
+        .build();
+    p.process(cargo_prusti_path())
+        .arg("--only")
+        .arg("crate::ok")
+        .run();
+    // Changing the selection verifies the crate again.
+    p.process(cargo_prusti_path())
+        .arg("--skip=crate::fails")
+        .run();
+    p.process(cargo_prusti_path())
+        .arg("--only")
+        .arg("foo::f*")
+        .with_status(101)
+        .with_stderr_contains("[..]the asserted expression might not hold[..]")
+        .run();
+}
+
+#[cargo_test]
+fn incremental_verification() {
+    let p = project()
+        .file(
+            "src/main.rs",
+            "fn ok() { assert!(true); }\nfn other() { assert!(1 + 1 == 2); }\nfn main() { ok(); }",
+        )
+        .build();
+    p.process(cargo_prusti_path())
+        .env("PRUSTI_QUIET", "false")
+        .with_stderr_does_not_contain("[..]unchanged items[..]")
+        .run();
+    // Only the changed procedure is verified again.
+    p.change_file(
+        "src/main.rs",
+        "fn ok() { assert!(true); }\nfn other() { assert!(1 + 1 == 3); }\nfn main() { ok(); }",
+    );
+    p.process(cargo_prusti_path())
+        .env("PRUSTI_QUIET", "false")
+        .with_status(101)
+        .with_stderr_contains("Skipping the verification of 2 unchanged items")
+        .with_stderr_contains("[..]the asserted expression might not hold[..]")
+        .run();
+    // The procedures that failed and the new ones are verified.
+    p.change_file(
+        "src/main.rs",
+        "fn ok() { assert!(true); }\nfn other() { assert!(1 + 1 == 2); }\nfn main() { ok(); }\n\
+        #[allow(dead_code)] fn unused() {}",
+    );
+    p.process(cargo_prusti_path())
+        .env("PRUSTI_QUIET", "false")
+        .with_stderr_contains("Skipping the verification of 2 unchanged items")
+        .run();
+    assert!(p
+        .root()
+        .join("target/verify/fingerprints/foo-main.json")
+        .exists());
+}
+
 /// Test `cargo-prusti` on one of the crates in `test/cargo_verify`.
 ///
 /// Special files and folders in the root of the test crate:
@@ -0,0 +1,15 @@
+use prusti_contracts::*;
+
+#[cfg_attr(prusti, prusti::config(viper_backend = "Carbon"))] //~ ERROR: the configuration flag `viper_backend` cannot be set for a single item
+fn unsupported_flag() {}
+
+#[cfg_attr(prusti, prusti::config(assert_timeout = false))] //~ ERROR: invalid value `false` for the configuration flag `assert_timeout`
+fn invalid_value() {}
+
+#[cfg_attr(prusti, prusti::config(check_panics))] //~ ERROR: expected arguments of the form `flag = value`
+fn missing_value() {}
+
+#[cfg_attr(prusti, prusti::config(check_panics = false, assert_timeout = 60000))]
+fn valid() {}
+
+fn main() {}
@@ -0,0 +1,46 @@
+// compile-flags: -Pbmc=true -Pbmc_unroll_depth=3
+
+use prusti_contracts::*;
+
+#[requires(n < 10)]
+fn sum(n: u32) -> u32 {
+    let mut i = 0;
+    let mut sum = 0;
+    while i < n {
+        sum += i;
+        i += 1;
+    }
+    assert!(sum != 3); //~ ERROR [Prusti: bounded verification error] the asserted expression might not hold
+    sum
+}
+
+fn index(values: &[u32]) -> u32 {
+    let mut i = 0;
+    let mut last = 0;
+    while i <= values.len() {
+        last = values[i]; //~ ERROR [Prusti: bounded verification error] the array or slice index may be out of bounds
+        i += 1;
+    }
+    last
+}
+
+#[requires(*x < 100)]
+#[ensures(*x == old(*x) + 1)]
+fn increment(x: &mut u32) {
+    *x += 1;
+}
+
+#[requires(n <= 3)]
+fn count(n: u32) -> u32 {
+    let mut i = 0;
+    let mut count = 0;
+    while i < n {
+        let counter = &mut count;
+        increment(counter);
+        i += 1;
+    }
+    assert!(count < 3); //~ ERROR [Prusti: bounded verification error] the asserted expression might not hold
+    count
+}
+
+fn main() {}
@@ -0,0 +1,48 @@
+use prusti_contracts::*;
+
+struct Counter {
+    value: u32,
+}
+
+#[panics_if(d == 0)]
+fn divide(n: u32, d: u32) -> u32 {
+    n / d
+}
+
+#[panics_if(d == 0)]
+fn panics_too_often(n: u32, d: u32) -> u32 {
+    if n == 0 {
+        panic!(); //~ ERROR panic!(..) statement might be reachable
+    }
+    n / d
+}
+
+#[panics_if(d == 0)] //~ ERROR the function might return although its panic condition holds
+fn returns_anyway(n: u32, d: u32) -> u32 {
+    if d == 0 {
+        return 0;
+    }
+    n / d
+}
+
+#[panics_if(c.value == u32::MAX)]
+#[ensures_on_panic(c.value == old(c.value))] //~ ERROR postcondition on panic might not hold
+fn reset_before_panic(c: &mut Counter) {
+    let value = c.value;
+    c.value = 0;
+    if value == u32::MAX {
+        panic!();
+    }
+}
+
+fn call_with_panic(n: u32, d: u32) -> u32 {
+    divide(n, d) //~ ERROR the panic condition of the called function might hold
+}
+
+#[panics_if(d == 0)]
+#[ensures_on_panic(true)]
+fn call_in_function_with_post_on_panic(n: u32, d: u32) -> u32 {
+    divide(n, d) //~ ERROR panics of calls are not supported
+}
+
+fn main() {}
@@ -0,0 +1,42 @@
+// compile-flags: -Psplit_obligations=true
+
+use prusti_contracts::*;
+
+struct Counter {
+    value: u32,
+}
+
+#[requires(counter.value < 100)]
+#[ensures(counter.value == old(counter.value) + 1)]
+#[ensures(counter.value > 1)] //~ ERROR postcondition might not hold
+fn increment(counter: &mut Counter) {
+    counter.value += 1;
+}
+
+#[ensures(result <= a)]
+#[ensures(result == a - 1)] //~ ERROR postcondition might not hold
+fn decrement(a: u32) -> u32 {
+    if a == 0 {
+        0
+    } else {
+        a - 1
+    }
+}
+
+fn count(n: u32) -> u32 {
+    let mut i = 0;
+    while i < n {
+        body_invariant!(i < n);
+        body_invariant!(i < 10); //~ ERROR loop invariant might not hold after a loop iteration
+        i += 1;
+    }
+    i
+}
+
+fn compare(a: u32, b: u32) {
+    assert!(a <= a);
+    assert!(a == b); //~ ERROR the asserted expression might not hold
+    assert!(a > 0); //~ ERROR the asserted expression might not hold
+}
+
+fn main() {}
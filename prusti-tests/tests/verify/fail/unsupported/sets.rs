@@ -0,0 +1,11 @@
+use prusti_contracts::*;
+
+fn uses_set(_s: Set<u32>) {} //~ ERROR sets and multisets are supported only with the unsafe core proof
+
+fn uses_multiset(_s: Multiset<u32>) {} //~ ERROR sets and multisets are supported only with the unsafe core proof
+
+fn uses_set_reference(_s: &Set<u32>) {} //~ ERROR sets and multisets are supported only with the unsafe core proof
+
+fn uses_optional_set(_s: Option<Set<u32>>) {} //~ ERROR sets and multisets are supported only with the unsafe core proof
+
+fn main() {}
@@ -0,0 +1,55 @@
+// compile-flags: -Pbmc=true -Pbmc_unroll_depth=3
+
+use prusti_contracts::*;
+
+fn count_to_two() -> u32 {
+    let mut i = 0;
+    while i < 2 {
+        i += 1;
+    }
+    assert!(i == 2);
+    i
+}
+
+#[requires(n <= 3)]
+#[ensures(result == 2 * n)]
+fn double(n: u32) -> u32 {
+    let mut i = 0;
+    let mut result = 0;
+    while i < n {
+        let mut j = 0;
+        while j < 2 {
+            result += 1;
+            j += 1;
+        }
+        i += 1;
+    }
+    result
+}
+
+#[requires(*x < 100)]
+#[ensures(*x == old(*x) + 1)]
+fn increment(x: &mut u32) {
+    *x += 1;
+}
+
+#[requires(n <= 3)]
+#[ensures(result == n)]
+fn count(n: u32) -> u32 {
+    let mut i = 0;
+    let mut count = 0;
+    while i < n {
+        // Each iteration creates a loan, and the call has its own `old` label.
+        let counter = &mut count;
+        increment(counter);
+        assert!(*counter == i + 1);
+        i += 1;
+    }
+    count
+}
+
+fn main() {
+    count_to_two();
+    assert!(count(2) == 2);
+    assert!(double(3) == 6);
+}
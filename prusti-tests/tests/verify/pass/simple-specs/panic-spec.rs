@@ -0,0 +1,45 @@
+use prusti_contracts::*;
+
+struct Counter {
+    value: u32,
+}
+
+#[panics_if(d == 0)]
+#[ensures(result == n / d)]
+fn divide(n: u32, d: u32) -> u32 {
+    n / d
+}
+
+#[panics_if(d == 0)]
+fn checked_divide(n: u32, d: u32) -> u32 {
+    if d == 0 {
+        panic!("division by zero");
+    }
+    n / d
+}
+
+#[panics_if(c.value == u32::MAX)]
+#[ensures_on_panic(c.value == old(c.value))]
+#[ensures(c.value == old(c.value) + 1)]
+fn increment(c: &mut Counter) {
+    c.value += 1;
+}
+
+#[requires(d > 0)]
+#[ensures(result == n / d)]
+fn call_without_panic(n: u32, d: u32) -> u32 {
+    divide(n, d)
+}
+
+#[panics_if(d == 0)]
+fn propagate_panic(n: u32, d: u32) -> u32 {
+    divide(n, d)
+}
+
+fn use_counter() {
+    let mut c = Counter { value: 0 };
+    increment(&mut c);
+    assert!(c.value == 1);
+}
+
+fn main() {}
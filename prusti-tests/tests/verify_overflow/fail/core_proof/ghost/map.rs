@@ -43,6 +43,13 @@ fn map_lookup(m: Map, k: u32, v1: u32, v2: u32) {
     prusti_assert!(m[k] == m[k]) //~ ERROR:
 }
 
+fn map_domain(m: Map, k: u32, v: u32) {
+    prusti_assert!(Map::empty().domain() == Set::empty());
+    prusti_assert!(m.insert(k, v).domain().contains(k));
+    prusti_assert!(m.domain().contains(k) == m.contains(k));
+    prusti_assert!(m.domain().contains(k)); //~ ERROR: the asserted expression might not hold
+}
+
 fn map_construction() {
     let map1 = Map::empty();
 }
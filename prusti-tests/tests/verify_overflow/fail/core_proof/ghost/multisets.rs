@@ -0,0 +1,38 @@
+// compile-flags: -Punsafe_core_proof=true
+
+#![allow(unused)]
+
+use prusti_contracts::*;
+
+type Multiset = prusti_contracts::Multiset<u32>;
+
+fn empty_multiset_zero_len() {
+    prusti_assert!(Multiset::empty().len() == Int::new(0));
+}
+
+fn count(x: u32) {
+    prusti_assert!(Multiset::single(x).count(x) == Int::new(1));
+    prusti_assert!(multiset![x, x].count(x) == Int::new(2));
+    prusti_assert!(multiset![x, x].len() == Int::new(2));
+    prusti_assert!(multiset![x, x].count(x) == Int::new(1)); //~ ERROR: asserted expression might not hold
+}
+
+fn contains(x: u32) {
+    prusti_assert!(Multiset::single(x).contains(x));
+    prusti_assert!(!Multiset::empty().contains(x));
+    prusti_assert!(multiset![x, x].difference(Multiset::single(x)).contains(x));
+    prusti_assert!(Multiset::single(x).difference(Multiset::single(x)).contains(x)); //~ ERROR: asserted expression might not hold
+}
+
+fn operations(a: Multiset, b: Multiset, x: u32) {
+    prusti_assert!(a.union(b).count(x) == a.count(x) + b.count(x));
+    prusti_assert!(a.union(b).len() == a.len() + b.len());
+    prusti_assert!(a.intersection(b).len() <= a.len());
+}
+
+fn permutation() {
+    prusti_assert!(multiset![1, 2, 2] == multiset![2, 1, 2]);
+    prusti_assert!(multiset![1, 2] == multiset![1, 2, 2]); //~ ERROR: asserted expression might not hold
+}
+
+fn main() {}
@@ -0,0 +1,48 @@
+// compile-flags: -Punsafe_core_proof=true
+
+#![allow(unused)]
+
+use prusti_contracts::*;
+
+type Seq = prusti_contracts::Seq<u32>;
+
+fn take_drop() {
+    prusti_assert!(seq![1, 2, 3].take(2) == seq![1, 2]);
+    prusti_assert!(seq![1, 2, 3].drop(2) == seq![3]);
+    prusti_assert!(seq![1, 2, 3].drop(1) == seq![3]); //~ ERROR: asserted expression might not hold
+}
+
+fn slice(s: Seq) {
+    prusti_assert!(seq![1, 2, 3, 4].slice(1, 3) == seq![2, 3]);
+    prusti_assert!(s.slice(0, 0) == Seq::empty());
+    prusti_assert!(s.slice(0, 1).len() == Int::new(1)); //~ ERROR: asserted expression might not hold
+}
+
+#[requires(Int::new_usize(i) < s.len())]
+fn update(s: Seq, i: usize, v: u32) {
+    prusti_assert!(seq![1, 2, 3].update(1, 5) == seq![1, 5, 3]);
+    prusti_assert!(s.update(i, v)[i] == v);
+    prusti_assert!(s.update(i, v).len() == s.len());
+    prusti_assert!(s.update(i, v) == s); //~ ERROR: asserted expression might not hold
+}
+
+fn contains(s: Seq, x: u32) {
+    prusti_assert!(seq![1, 2, 3].contains(2));
+    prusti_assert!(!Seq::empty().contains(x));
+    prusti_assert!(s.concat(Seq::single(x)).contains(x));
+    prusti_assert!(s.contains(x)); //~ ERROR: asserted expression might not hold
+}
+
+#[requires(a.to_seq().contains(1))]
+fn array_to_seq(a: [u32; 3]) {
+    prusti_assert!(a.to_seq() == a.to_seq());
+    prusti_assert!(a.to_seq().contains(1));
+    prusti_assert!(a.to_seq().contains(2)); //~ ERROR: asserted expression might not hold
+}
+
+#[requires(s.len() > 0)]
+#[ensures(s.to_seq().len() == Int::new_usize(s.len()))]
+#[ensures(s.to_seq()[0] == s[0])]
+fn slice_to_seq(s: &[u32]) {}
+
+fn main() {}
@@ -0,0 +1,54 @@
+// compile-flags: -Punsafe_core_proof=true
+
+#![allow(unused)]
+
+use prusti_contracts::*;
+
+type Set = prusti_contracts::Set<u32>;
+
+fn empty_set_zero_len() {
+    prusti_assert!(Set::empty().len() == Int::new(0));
+}
+
+fn empty_set_not_one_len() {
+    prusti_assert!(Set::empty().len() == Int::new(1)); //~ ERROR: asserted expression might not hold
+}
+
+fn single_len() {
+    prusti_assert!(Set::single(1).len() == Int::new(1));
+    prusti_assert!(Set::single(1).union(Set::single(1)).len() == Int::new(1));
+}
+
+fn contains(x: u32) {
+    prusti_assert!(Set::single(x).contains(x));
+    prusti_assert!(!Set::empty().contains(x));
+    prusti_assert!(Set::empty().contains(x)); //~ ERROR: asserted expression might not hold
+}
+
+fn operations(a: Set, b: Set, x: u32) {
+    prusti_assert!(a.union(b).contains(x) == (a.contains(x) || b.contains(x)));
+    prusti_assert!(a.intersection(b).contains(x) == (a.contains(x) && b.contains(x)));
+    prusti_assert!(a.difference(b).contains(x) == (a.contains(x) && !b.contains(x)));
+    prusti_assert!(a.union(b) == b.union(a));
+}
+
+fn set_eq() {
+    prusti_assert!(set![1, 2] == set![2, 1]);
+    prusti_assert!(set![1, 2] == set![1, 2, 1]);
+    prusti_assert!(set![1] == set![2]); //~ ERROR: asserted expression might not hold
+}
+
+#[requires(forall(|x: u32| s.contains(x) ==> x > 10))]
+fn quantifier(s: Set) {
+    prusti_assert!(!s.contains(5));
+    prusti_assert!(!s.contains(15)); //~ ERROR: asserted expression might not hold
+}
+
+fn in_procedure(x: u32) {
+    let s = Set::single(x);
+    let s = s.union(Set::single(3));
+    let contained = s.contains(x);
+    prusti_assert!(contained);
+}
+
+fn main() {}
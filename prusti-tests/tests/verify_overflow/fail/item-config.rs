@@ -0,0 +1,34 @@
+use prusti_contracts::*;
+
+fn add(a: u64, b: u64) -> u64 {
+    a + b //~ ERROR: attempt to add with overflow
+}
+
+#[cfg_attr(prusti, prusti::config(check_overflows = false))]
+fn add_unchecked(a: u64, b: u64) -> u64 {
+    a + b
+}
+
+#[cfg_attr(prusti, prusti::config(check_overflows = false))]
+mod unchecked {
+    pub fn add(a: u64, b: u64) -> u64 {
+        a + b
+    }
+
+    pub struct Counter {
+        pub value: u64,
+    }
+
+    impl Counter {
+        pub fn increment(&mut self) {
+            self.value += 1;
+        }
+    }
+
+    #[cfg_attr(prusti, prusti::config(check_overflows = true))]
+    pub fn add_checked(a: u64, b: u64) -> u64 {
+        a + b //~ ERROR: attempt to add with overflow
+    }
+}
+
+fn main() {}
@@ -13,7 +13,7 @@ use ::config::{Config, Environment, File};
 use log::warn;
 use rustc_hash::FxHashSet;
 use serde::Deserialize;
-use std::{env, path::PathBuf, sync::RwLock};
+use std::{cell::RefCell, collections::BTreeMap, env, path::PathBuf, sync::RwLock};
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct Optimizations {
@@ -80,14 +80,24 @@ lazy_static::lazy_static! {
         settings.set_default("check_foldunfold_state", false).unwrap();
         settings.set_default("check_overflows", true).unwrap();
         settings.set_default("check_panics", true).unwrap();
+        settings.set_default("bmc", false).unwrap();
+        settings.set_default("bmc_unroll_depth", 3).unwrap();
         settings.set_default("encode_unsigned_num_constraint", true).unwrap();
         settings.set_default("encode_bitvectors", false).unwrap();
         settings.set_default("simplify_encoding", true).unwrap();
+        settings.set_default("split_obligations", false).unwrap();
         settings.set_default("log", "").unwrap();
         settings.set_default("log_style", "auto").unwrap();
         settings.set_default("log_dir", "log").unwrap();
         settings.set_default("log_tracing", false).unwrap();
+        settings.set_default("chrome_trace", false).unwrap();
         settings.set_default("cache_path", "").unwrap();
+        settings.set_default::<Option<u64>>("cache_max_entries", None).unwrap();
+        settings.set_default("fingerprint_dir", "").unwrap();
+        settings.set_default::<Option<u64>>("request_timeout", None).unwrap();
+        settings.set_default::<Option<String>>("report_dir", None).unwrap();
+        settings.set_default("server_max_retries", 3).unwrap();
+        settings.set_default("server_refuse_incompatible_clients", false).unwrap();
         settings.set_default("dump_debug_info", false).unwrap();
         settings.set_default("dump_debug_info_during_fold", false).unwrap();
         settings.set_default("dump_nll_facts", false).unwrap();
@@ -110,6 +120,8 @@ lazy_static::lazy_static! {
         settings.set_default("no_verify", false).unwrap();
         settings.set_default("no_verify_deps", false).unwrap();
         settings.set_default("opt_in_verification", false).unwrap();
+        settings.set_default::<Vec<String>>("verify_only_items", vec![]).unwrap();
+        settings.set_default::<Vec<String>>("verify_skip_items", vec![]).unwrap();
         settings.set_default("full_compilation", false).unwrap();
         settings.set_default("json_communication", false).unwrap();
         settings.set_default("optimizations", "all").unwrap();
@@ -161,6 +173,8 @@ lazy_static::lazy_static! {
         settings.set_default("preserve_smt_trace_files", false).unwrap();
         settings.set_default("write_smt_statistics", false).unwrap();
         settings.set_default("log_smt_wrapper_interaction", false).unwrap();
+        settings.set_default("profile_verification", false).unwrap();
+        settings.set_default("profile_top_items", 10).unwrap();
 
         // Flags for debugging Prusti that can change verification results.
         settings.set_default("disable_name_mangling", false).unwrap();
@@ -174,6 +188,8 @@ lazy_static::lazy_static! {
         allowed_keys.insert("server_max_stored_verifiers".to_string());
         allowed_keys.insert("server_max_concurrency".to_string());
         allowed_keys.insert("server_address".to_string());
+        allowed_keys.insert("remote_cache_address".to_string());
+        allowed_keys.insert("remote_cache_token".to_string());
         allowed_keys.insert("config".to_string());
         allowed_keys.insert("log".to_string());
         allowed_keys.insert("log_style".to_string());
@@ -211,6 +227,8 @@ lazy_static::lazy_static! {
                 .with_list_parse_key("extra_jvm_args")
                 .with_list_parse_key("extra_verifier_args")
                 .with_list_parse_key("verify_only_basic_block_path")
+                .with_list_parse_key("verify_only_items")
+                .with_list_parse_key("verify_skip_items")
                 .list_separator(" ")
         ).unwrap();
         check_keys(&settings, &allowed_keys, "the `PRUSTI_*` environment variables");
@@ -274,6 +292,9 @@ fn read_setting<T>(name: &'static str) -> T
 where
     T: Deserialize<'static>,
 {
+    if let Some(value) = read_item_setting(name) {
+        return value;
+    }
     SETTINGS
         .read()
         .unwrap()
@@ -289,6 +310,136 @@ fn write_setting<T: Into<config::Value>>(key: &'static str, value: T) {
         .unwrap_or_else(|e| panic!("Failed to write setting {key} due to {e}"));
 }
 
+/// A check that a string is a valid value of a flag.
+type ValueCheck = fn(&str) -> bool;
+
+/// The flags that can be overridden for a single item with the
+/// `#[prusti::config(...)]` attribute, each with a check of its values. They
+/// only influence the encoding and the verification of the item itself.
+const PER_ITEM_FLAGS: &[(&str, ValueCheck)] = &[
+    ("assert_timeout", is_u64),
+    ("check_overflows", is_bool),
+    ("check_panics", is_bool),
+    ("check_timeout", is_u32),
+    ("smt_qi_eager_threshold", is_u64),
+    ("split_obligations", is_bool),
+    ("use_more_complete_exhale", is_bool),
+];
+
+fn is_bool(value: &str) -> bool {
+    value.parse::<bool>().is_ok()
+}
+
+fn is_u32(value: &str) -> bool {
+    value.parse::<u32>().is_ok()
+}
+
+fn is_u64(value: &str) -> bool {
+    value.parse::<u64>().is_ok()
+}
+
+/// The configuration flags overridden for a single item (e.g. a function, an
+/// impl or a module) with the `#[prusti::config(...)]` attribute.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ItemConfig {
+    values: BTreeMap<String, String>,
+}
+
+impl ItemConfig {
+    /// Override `flag` with `value`. Fails if the flag cannot be overridden
+    /// for a single item or if the value is not valid for the flag.
+    pub fn set(&mut self, flag: &str, value: String) -> Result<(), String> {
+        let flag = flag.to_lowercase();
+        let Some((_, is_valid)) = PER_ITEM_FLAGS.iter().find(|(name, _)| *name == flag) else {
+            let flags: Vec<_> = PER_ITEM_FLAGS.iter().map(|(name, _)| *name).collect();
+            return Err(format!(
+                "the configuration flag `{flag}` cannot be set for a single item \
+                (the supported flags are {})",
+                flags.join(", ")
+            ));
+        };
+        if !is_valid(&value) {
+            return Err(format!(
+                "invalid value `{value}` for the configuration flag `{flag}`"
+            ));
+        }
+        self.values.insert(flag, value);
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Add the overrides of `outer` (e.g. of the module of the item) for the
+    /// flags that are not overridden by `self`.
+    pub fn inherit(&mut self, outer: &ItemConfig) {
+        for (flag, value) in &outer.values {
+            self.values
+                .entry(flag.clone())
+                .or_insert_with(|| value.clone());
+        }
+    }
+}
+
+impl std::fmt::Display for ItemConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let values: Vec<_> = self
+            .values
+            .iter()
+            .map(|(flag, value)| format!("{flag}={value}"))
+            .collect();
+        write!(f, "{}", values.join(", "))
+    }
+}
+
+thread_local! {
+    /// The overrides of the item that is being encoded or verified on the
+    /// current thread, if any.
+    static ITEM_SETTINGS: RefCell<Option<(ItemConfig, Config)>> = RefCell::new(None);
+}
+
+/// Run `f` with the flags of `item_config`, instead of those of any enclosing
+/// call, overriding the configuration on the current thread. An empty
+/// `item_config` runs `f` with the global configuration.
+pub fn with_item_config<R>(item_config: &ItemConfig, f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<(ItemConfig, Config)>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            ITEM_SETTINGS.with(|item_settings| *item_settings.borrow_mut() = previous);
+        }
+    }
+
+    let item_settings = (!item_config.is_empty()).then(|| {
+        let mut settings = Config::default();
+        for (flag, value) in &item_config.values {
+            settings
+                .set(flag, value.as_str())
+                .unwrap_or_else(|e| panic!("Failed to override setting {flag} due to {e}"));
+        }
+        (item_config.clone(), settings)
+    });
+    let previous = ITEM_SETTINGS.with(|current| current.replace(item_settings));
+    let _restore = Restore(previous);
+    f()
+}
+
+fn read_item_setting<T>(name: &'static str) -> Option<T>
+where
+    T: Deserialize<'static>,
+{
+    ITEM_SETTINGS.with(|item_settings| {
+        let item_settings = item_settings.borrow();
+        let (item_config, settings) = item_settings.as_ref()?;
+        item_config.values.contains_key(name).then(|| {
+            settings
+                .get(name)
+                .unwrap_or_else(|e| panic!("Failed to read setting {name} due to {e}"))
+        })
+    })
+}
+
 // The following methods are all convenience wrappers for the actual call to
 // `read_setting` (+ optionally some light sanitisation or processing). Please
 // keep the documentation on each method in sync with `flags.md` in the dev
@@ -362,6 +513,20 @@ pub fn java_home() -> String {
         .expect("Please set the java_home configuration flag")
 }
 
+/// When enabled, loops are unrolled `bmc_unroll_depth` times instead of
+/// being verified with their invariants, which are ignored. The executions
+/// that iterate a loop more often are assumed away, so a successful
+/// verification is not a proof.
+pub fn bmc() -> bool {
+    read_setting("bmc")
+}
+
+/// The number of iterations of each loop that are verified when `bmc` is
+/// enabled.
+pub fn bmc_unroll_depth() -> usize {
+    read_setting("bmc_unroll_depth")
+}
+
 /// When enabled, Prusti will check for an absence of `panic!`s.
 pub fn check_panics() -> bool {
     read_setting("check_panics")
@@ -373,6 +538,15 @@ pub fn simplify_encoding() -> bool {
     read_setting("simplify_encoding")
 }
 
+/// When enabled, the program encoding a method is split into one program per
+/// proof obligation (e.g. a postcondition, the preservation of a loop
+/// invariant or the absence of a panic), in which the other obligations are
+/// assumed. The programs are verified and cached independently, so that a
+/// timeout only affects the obligation that caused it.
+pub fn split_obligations() -> bool {
+    read_setting("split_obligations")
+}
+
 /// When enabled, debug files will be created.
 pub fn dump_debug_info() -> bool {
     read_setting("dump_debug_info")
@@ -446,6 +620,14 @@ pub fn log_tracing() -> bool {
     read_setting("log_tracing")
 }
 
+/// When enabled, Prusti writes a trace of its phases and of the work done for
+/// each item in the Chrome trace event format, which can be opened in a trace
+/// viewer such as Perfetto. The trace of each crate is written to
+/// `<log_dir>/chrome_trace/<crate>-<crate type>.json`.
+pub fn chrome_trace() -> bool {
+    read_setting("chrome_trace")
+}
+
 /// Path to a cache file, where verification cache will be loaded from and
 /// saved to. The default empty string disables saving any cache to disk.
 /// A path to a file which does not yet exist will result in using an empty
@@ -454,6 +636,21 @@ pub fn cache_path() -> PathBuf {
     PathBuf::from(read_setting::<String>("cache_path"))
 }
 
+/// Maximum number of verification results to keep in the cache. When the
+/// cache grows beyond this limit, the least recently used results are
+/// evicted. If not set, the cache is unbounded.
+pub fn cache_max_entries() -> Option<usize> {
+    read_setting::<Option<u64>>("cache_max_entries").map(|max_entries| max_entries as usize)
+}
+
+/// Directory in which the dependency fingerprint of each verified procedure
+/// is stored. A procedure whose fingerprint did not change since it was last
+/// verified is neither encoded nor verified again. The default empty string
+/// disables this incremental verification.
+pub fn fingerprint_dir() -> String {
+    read_setting("fingerprint_dir")
+}
+
 /// When enabled, binary operations and numeric casts will be checked for
 /// overflows.
 pub fn check_overflows() -> bool {
@@ -655,6 +852,55 @@ pub fn server_address() -> Option<String> {
     read_optional_setting("server_address")
 }
 
+/// When set to the address and port of a `prusti-server` (e.g.
+/// `"10.0.0.1:2468"`), Prusti will look up verification results in the cache
+/// of that server before verifying a program, and will upload new results to
+/// it. This allows sharing the cache between machines. When Prusti uses a
+/// server (see `SERVER_ADDRESS`), this flag is read by the server instead.
+pub fn remote_cache_address() -> Option<String> {
+    read_optional_setting("remote_cache_address")
+}
+
+/// The secret shared by a `prusti-server` and the clients that upload results
+/// to its cache. A server only accepts uploads (`PUT /cache/<hash>`) with this
+/// token, and does not accept any if the token is not set. The clients send it
+/// with the results they upload to the cache at `REMOTE_CACHE_ADDRESS`.
+pub fn remote_cache_token() -> Option<String> {
+    read_optional_setting("remote_cache_token")
+}
+
+/// When set to a directory, Prusti writes there a report of the verification
+/// of each crate, in JSON (`<crate>-<source file>.json`) and in SARIF 2.1
+/// (`<crate>-<source file>.sarif`). For each verified item, the report gives
+/// its path, span, status, timing and the errors reported for it. Reports
+/// are never removed, so the directory keeps the reports of crates that were
+/// renamed or removed.
+pub fn report_dir() -> Option<String> {
+    read_setting("report_dir")
+}
+
+/// Maximum time (in seconds) the server may spend verifying a single program.
+/// When exceeded, the server aborts the verification and an error is reported
+/// for the affected function. If not set, there is no deadline.
+pub fn request_timeout() -> Option<u64> {
+    read_setting("request_timeout")
+}
+
+/// How many times a verification request is retried, with an exponential
+/// backoff, when the server cannot be reached or fails. Requests that exceeded
+/// their deadline are not retried.
+pub fn server_max_retries() -> u32 {
+    read_setting("server_max_retries")
+}
+
+/// When enabled, the server refuses the verification requests of clients
+/// running a version of Prusti with an incompatible protocol version, VIR
+/// format or cache version. Otherwise, clients are expected to check the
+/// `/version` endpoint of the server themselves, which Prusti always does.
+pub fn server_refuse_incompatible_clients() -> bool {
+    read_setting("server_refuse_incompatible_clients")
+}
+
 /// When enabled, communication with the server will be encoded as JSON
 /// instead of the default bincode.
 pub fn json_communication() -> bool {
@@ -864,6 +1110,21 @@ pub fn log_smt_wrapper_interaction() -> bool {
     read_smt_wrapper_dependent_bool("log_smt_wrapper_interaction")
 }
 
+/// When enabled, Prusti records for each verified item the time spent encoding
+/// it (including the fold-unfold algorithm), the size of its Viper programs,
+/// the time spent verifying them and whether their result was cached. The
+/// `profile_top_items` slowest items are printed, and the full table is
+/// written to `<log_dir>/profile/<crate>-<source file>.txt`.
+pub fn profile_verification() -> bool {
+    read_setting("profile_verification")
+}
+
+/// How many of the slowest items are printed when `profile_verification` is
+/// enabled.
+pub fn profile_top_items() -> usize {
+    read_setting("profile_top_items")
+}
+
 /// When enabled, the new core proof is used, suitable for unsafe code
 ///
 /// **Note:** This option is currently very incomplete.
@@ -904,7 +1165,8 @@ pub fn verify_specifications_with_core_proof() -> bool {
     read_setting("verify_specifications_with_core_proof")
 }
 
-/// Verification backend to use for functional specification only.
+/// Verification backend to use for functional specification only. These
+/// programs are predicate-free, so they can also be verified by `SmtLib`.
 pub fn verify_specifications_backend() -> String {
     read_setting::<String>("verify_specifications_backend")
         .to_lowercase()
@@ -996,6 +1258,19 @@ pub fn opt_in_verification() -> bool {
     read_setting("opt_in_verification")
 }
 
+/// When non-empty, only the procedures whose name (e.g.
+/// `my_crate::module::func`) matches one of these glob patterns are verified.
+/// The specifications of the other procedures are still used at call sites.
+pub fn verify_only_items() -> Vec<String> {
+    read_setting("verify_only_items")
+}
+
+/// The procedures whose name matches one of these glob patterns are not
+/// verified, even if they match `verify_only_items`.
+pub fn verify_skip_items() -> Vec<String> {
+    read_setting("verify_skip_items")
+}
+
 /// When enabled, compilation will continue and a binary will be generated
 /// after Prusti terminates.
 pub fn full_compilation() -> bool {
@@ -13,6 +13,7 @@ use std::{
 };
 
 pub mod job;
+pub mod verification_summary;
 
 /// Determines which crates in `./prusti-contracts` have their specs re-exported
 /// for `prusti-rustc`.
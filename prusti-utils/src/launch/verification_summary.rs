@@ -0,0 +1,398 @@
+// © 2023, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Merges the per-crate verification reports written by `prusti-rustc` (see
+//! the `report_dir` configuration flag) into a JUnit XML report and a console
+//! summary.
+
+use serde::Deserialize;
+use std::{fmt::Write, fs, io, path::Path};
+
+#[derive(Debug, Deserialize)]
+pub struct CrateReport {
+    #[serde(rename = "crate")]
+    pub crate_name: String,
+    pub items: Vec<ItemReport>,
+    /// Errors that could not be attributed to an item.
+    #[serde(default)]
+    pub errors: Vec<ReportedError>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ItemReport {
+    pub name: String,
+    pub def_path: String,
+    pub span: Option<SourceSpan>,
+    /// One of `verified`, `failed`, `unsupported`, `trusted` and `skipped`.
+    pub status: String,
+    pub verification_seconds: f64,
+    pub errors: Vec<ReportedError>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReportedError {
+    pub kind: String,
+    pub message: String,
+    pub span: Option<SourceSpan>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SourceSpan {
+    pub file: String,
+    pub start_line: usize,
+    pub start_column: usize,
+}
+
+impl std::fmt::Display for SourceSpan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.start_line, self.start_column)
+    }
+}
+
+/// The report of one crate, named after its report file (e.g. `foo-lib`),
+/// since a package can have a library and a binary crate with the same name.
+pub struct NamedCrateReport {
+    pub name: String,
+    pub report: CrateReport,
+}
+
+impl NamedCrateReport {
+    fn count(&self, status: &str) -> usize {
+        self.report
+            .items
+            .iter()
+            .filter(|item| item.status == status)
+            .count()
+    }
+
+    fn verification_seconds(&self) -> f64 {
+        self.report
+            .items
+            .iter()
+            .map(|item| item.verification_seconds)
+            .sum()
+    }
+}
+
+/// Load the JSON reports in `dir`, sorted by name. Files that cannot be parsed
+/// are skipped with a warning.
+pub fn load_reports(dir: &Path) -> io::Result<Vec<NamedCrateReport>> {
+    let mut reports = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path
+            .extension()
+            .map_or(true, |extension| extension != "json")
+        {
+            continue;
+        }
+        let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+        match serde_json::from_str(&fs::read_to_string(&path)?) {
+            Ok(report) => reports.push(NamedCrateReport { name, report }),
+            Err(error) => eprintln!(
+                "warning: ignoring invalid verification report {}: {error}",
+                path.display()
+            ),
+        }
+    }
+    reports.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(reports)
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// The messages of the errors, each prefixed with its location.
+fn describe_errors(errors: &[ReportedError]) -> String {
+    let mut description = String::new();
+    for error in errors {
+        if let Some(span) = &error.span {
+            write!(description, "{span}: ").unwrap();
+        }
+        writeln!(description, "[{}] {}", error.kind, error.message).unwrap();
+    }
+    description
+}
+
+/// Write a JUnit `<failure>` or `<error>` element for the errors.
+fn write_errors(xml: &mut String, element: &str, errors: &[ReportedError]) {
+    let (message, kind) = errors.first().map_or(("", ""), |error| {
+        (error.message.as_str(), error.kind.as_str())
+    });
+    writeln!(
+        xml,
+        "      <{element} message=\"{}\" type=\"{}\">{}</{element}>",
+        escape_xml(message),
+        escape_xml(kind),
+        escape_xml(&describe_errors(errors))
+    )
+    .unwrap();
+}
+
+/// A JUnit XML report with one test suite per crate and one test case per
+/// procedure. Failed procedures are reported as failures, unsupported ones as
+/// errors, and trusted or skipped ones as skipped.
+pub fn junit_xml(reports: &[NamedCrateReport]) -> String {
+    let mut xml = String::new();
+    let count = |status: &str| -> usize { reports.iter().map(|r| r.count(status)).sum() };
+    let tests: usize = reports.iter().map(|r| r.report.items.len()).sum();
+    let time: f64 = reports
+        .iter()
+        .map(NamedCrateReport::verification_seconds)
+        .sum();
+    writeln!(xml, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
+    writeln!(
+        xml,
+        r#"<testsuites name="prusti" tests="{tests}" failures="{}" errors="{}" skipped="{}" time="{time:.3}">"#,
+        count("failed"),
+        count("unsupported"),
+        count("trusted") + count("skipped"),
+    )
+    .unwrap();
+    for crate_report in reports {
+        writeln!(
+            xml,
+            r#"  <testsuite name="{}" tests="{}" failures="{}" errors="{}" skipped="{}" time="{:.3}">"#,
+            escape_xml(&crate_report.name),
+            crate_report.report.items.len(),
+            crate_report.count("failed"),
+            crate_report.count("unsupported"),
+            crate_report.count("trusted") + crate_report.count("skipped"),
+            crate_report.verification_seconds(),
+        )
+        .unwrap();
+        for item in &crate_report.report.items {
+            write!(
+                xml,
+                r#"    <testcase classname="{}" name="{}" time="{:.3}""#,
+                escape_xml(&crate_report.report.crate_name),
+                escape_xml(&item.name),
+                item.verification_seconds,
+            )
+            .unwrap();
+            if let Some(span) = &item.span {
+                write!(
+                    xml,
+                    r#" file="{}" line="{}""#,
+                    escape_xml(&span.file),
+                    span.start_line
+                )
+                .unwrap();
+            }
+            writeln!(xml, ">").unwrap();
+            match item.status.as_str() {
+                "failed" => write_errors(&mut xml, "failure", &item.errors),
+                "unsupported" => write_errors(&mut xml, "error", &item.errors),
+                "trusted" | "skipped" => {
+                    writeln!(xml, r#"      <skipped message="{}"/>"#, item.status).unwrap()
+                }
+                _ => {}
+            }
+            writeln!(xml, "    </testcase>").unwrap();
+        }
+        if !crate_report.report.errors.is_empty() {
+            writeln!(
+                xml,
+                r#"    <system-err>{}</system-err>"#,
+                escape_xml(&describe_errors(&crate_report.report.errors))
+            )
+            .unwrap();
+        }
+        writeln!(xml, "  </testsuite>").unwrap();
+    }
+    writeln!(xml, "</testsuites>").unwrap();
+    xml
+}
+
+/// A table with the number of procedures of each status per crate.
+pub fn summary_table(reports: &[NamedCrateReport]) -> String {
+    let header = [
+        "crate",
+        "verified",
+        "failed",
+        "unsupported",
+        "trusted",
+        "skipped",
+        "time (s)",
+    ];
+    let rows: Vec<[String; 7]> = reports
+        .iter()
+        .map(|crate_report| {
+            [
+                crate_report.name.clone(),
+                crate_report.count("verified").to_string(),
+                crate_report.count("failed").to_string(),
+                crate_report.count("unsupported").to_string(),
+                crate_report.count("trusted").to_string(),
+                crate_report.count("skipped").to_string(),
+                format!("{:.1}", crate_report.verification_seconds()),
+            ]
+        })
+        .collect();
+    let mut widths = header.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    let mut table = String::new();
+    let mut write_row = |cells: &[&str]| {
+        for (column, (cell, width)) in cells.iter().zip(widths).enumerate() {
+            if column == 0 {
+                write!(table, "{cell:<width$}").unwrap();
+            } else {
+                write!(table, "  {cell:>width$}").unwrap();
+            }
+        }
+        writeln!(table).unwrap();
+    };
+    write_row(&header);
+    for row in &rows {
+        let cells: Vec<_> = row.iter().map(String::as_str).collect();
+        write_row(&cells);
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(kind: &str, message: &str) -> ReportedError {
+        ReportedError {
+            kind: kind.to_string(),
+            message: message.to_string(),
+            span: Some(SourceSpan {
+                file: "src/lib.rs".to_string(),
+                start_line: 3,
+                start_column: 5,
+            }),
+        }
+    }
+
+    fn item(name: &str, status: &str, errors: Vec<ReportedError>) -> ItemReport {
+        ItemReport {
+            name: name.to_string(),
+            def_path: format!("foo::{name}"),
+            span: None,
+            status: status.to_string(),
+            verification_seconds: 0.5,
+            errors,
+        }
+    }
+
+    fn report(items: Vec<ItemReport>, errors: Vec<ReportedError>) -> Vec<NamedCrateReport> {
+        vec![NamedCrateReport {
+            name: "foo-lib".to_string(),
+            report: CrateReport {
+                crate_name: "foo".to_string(),
+                items,
+                errors,
+            },
+        }]
+    }
+
+    #[test]
+    fn escape_special_characters() {
+        assert_eq!(
+            escape_xml(r#"a < b && c > "d" || 'e'"#),
+            "a &lt; b &amp;&amp; c &gt; &quot;d&quot; || &apos;e&apos;"
+        );
+        assert_eq!(escape_xml("plain text"), "plain text");
+    }
+
+    #[test]
+    fn escape_error_messages() {
+        let reports = report(
+            vec![item(
+                "check",
+                "failed",
+                vec![error("Panic", r#"assertion <x & "y"> 'z' failed"#)],
+            )],
+            vec![],
+        );
+        let xml = junit_xml(&reports);
+        assert!(xml
+            .contains(r#"message="assertion &lt;x &amp; &quot;y&quot;&gt; &apos;z&apos; failed""#));
+        assert!(!xml.contains("<x"));
+    }
+
+    #[test]
+    fn failed_item_is_a_failure() {
+        let reports = report(
+            vec![
+                item("verified", "verified", vec![]),
+                item("failed", "failed", vec![error("Panic", "might panic")]),
+            ],
+            vec![],
+        );
+        let xml = junit_xml(&reports);
+        assert!(xml.contains(r#"tests="2" failures="1" errors="0" skipped="0""#));
+        assert!(xml.contains(
+            "<failure message=\"might panic\" type=\"Panic\">\
+             src/lib.rs:3:5: [Panic] might panic\n</failure>"
+        ));
+        assert!(!xml.contains("<error"));
+    }
+
+    #[test]
+    fn unsupported_item_is_an_error() {
+        let reports = report(
+            vec![item(
+                "unsupported",
+                "unsupported",
+                vec![error("Unsupported", "unsupported feature")],
+            )],
+            vec![],
+        );
+        let xml = junit_xml(&reports);
+        assert!(xml.contains(r#"tests="1" failures="0" errors="1" skipped="0""#));
+        assert!(xml.contains(r#"<error message="unsupported feature" type="Unsupported">"#));
+        assert!(!xml.contains("<failure"));
+    }
+
+    #[test]
+    fn unattributed_errors_are_system_errors() {
+        let reports = report(
+            vec![item("verified", "verified", vec![])],
+            vec![error("Internal", "no item")],
+        );
+        let xml = junit_xml(&reports);
+        assert!(xml.contains("<system-err>src/lib.rs:3:5: [Internal] no item\n</system-err>"));
+        assert!(!xml.contains("<failure"));
+        assert!(!xml.contains("<error"));
+    }
+
+    #[test]
+    fn summary_counts_statuses() {
+        let reports = report(
+            vec![
+                item("a", "verified", vec![]),
+                item("b", "verified", vec![]),
+                item("c", "failed", vec![error("Panic", "might panic")]),
+                item("d", "trusted", vec![]),
+            ],
+            vec![],
+        );
+        let table = summary_table(&reports);
+        let lines: Vec<_> = table.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let cells: Vec<_> = lines[1].split_whitespace().collect();
+        assert_eq!(cells, ["foo-lib", "2", "1", "0", "1", "0", "2.0"]);
+    }
+}
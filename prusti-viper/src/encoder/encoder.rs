@@ -39,6 +39,7 @@ use crate::{
         stub_procedure_encoder::StubProcedureEncoder,
     },
     error_unsupported,
+    verification_profile::EncodingProfile,
 };
 use ::log::{debug, info};
 use prusti_common::{
@@ -61,6 +62,7 @@ use std::{
     io::Write,
     ops::AddAssign,
     rc::Rc,
+    time::{Duration, Instant},
 };
 use vir_crate::{
     common::{check_mode::CheckMode, identifier::WithIdentifier},
@@ -97,6 +99,13 @@ pub struct Encoder<'v, 'tcx: 'v> {
     vir_program_before_foldunfold_writer: Option<RefCell<Box<dyn Write>>>,
     vir_program_before_viper_writer: Option<RefCell<Box<dyn Write>>>,
     encoding_errors_counter: RefCell<usize>,
+    /// The encoding errors of each procedure of the encoding queue, used to
+    /// report the status of the procedures.
+    procedure_encoding_errors: RefCell<FxHashMap<ProcedureDefId, Vec<SpannedEncodingError>>>,
+    /// The time spent encoding each procedure of the encoding queue.
+    procedure_encoding_profiles: RefCell<FxHashMap<ProcedureDefId, EncodingProfile>>,
+    /// The total time spent adding fold-unfold statements.
+    fold_unfold_time: Cell<Duration>,
     name_interner: RefCell<NameInterner>,
     /// Maps locals to the local of their discriminant.
     pub(super) discriminants_state: DiscriminantsState,
@@ -174,6 +183,9 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
             snapshot_encoder_state: Default::default(),
             mirror_encoder: RefCell::new(MirrorEncoder::new()),
             encoding_errors_counter: RefCell::new(0),
+            procedure_encoding_errors: RefCell::new(FxHashMap::default()),
+            procedure_encoding_profiles: RefCell::new(FxHashMap::default()),
+            fold_unfold_time: Cell::new(Duration::ZERO),
             name_interner: RefCell::new(NameInterner::new()),
             is_encoding_trigger: Cell::new(false),
             specifications_state: SpecificationsState::new(def_spec),
@@ -273,10 +285,52 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
         prusti_error.emit(&self.env.diagnostic);
     }
 
+    /// Register an encoding error that prevented `proc_def_id` from being
+    /// verified.
+    fn register_procedure_encoding_error(
+        &self,
+        proc_def_id: ProcedureDefId,
+        encoding_error: SpannedEncodingError,
+    ) {
+        self.procedure_encoding_errors
+            .borrow_mut()
+            .entry(proc_def_id)
+            .or_default()
+            .push(encoding_error.clone());
+        self.register_encoding_error(encoding_error);
+    }
+
     pub fn count_encoding_errors(&self) -> usize {
         *self.encoding_errors_counter.borrow()
     }
 
+    /// Whether the procedure is trusted, in which case it is not verified.
+    pub fn is_trusted_procedure(&self, def_id: ProcedureDefId) -> bool {
+        self.is_trusted(def_id, None)
+    }
+
+    /// The configuration overrides of the procedure, set with the
+    /// `#[prusti::config(...)]` attribute.
+    pub fn get_procedure_config(&self, def_id: ProcedureDefId) -> config::ItemConfig {
+        self.get_item_config(def_id)
+    }
+
+    pub fn take_procedure_encoding_errors(
+        &self,
+    ) -> FxHashMap<ProcedureDefId, Vec<SpannedEncodingError>> {
+        self.procedure_encoding_errors.take()
+    }
+
+    pub fn take_procedure_encoding_profiles(&self) -> FxHashMap<ProcedureDefId, EncodingProfile> {
+        self.procedure_encoding_profiles.take()
+    }
+
+    /// Record time spent adding fold-unfold statements to a method.
+    pub(super) fn add_fold_unfold_time(&self, duration: Duration) {
+        self.fold_unfold_time
+            .set(self.fold_unfold_time.get() + duration);
+    }
+
     pub(super) fn get_mirror_domain(&self) -> Option<vir::Domain> {
         self.mirror_encoder.borrow().get_domain().cloned()
     }
@@ -800,105 +854,20 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
                     def_id: proc_def_id,
                     substs,
                 } => {
-                    let proc_name = self.env.name.get_unique_item_name(proc_def_id);
-                    let proc_def_path = self.env.name.get_item_def_path(proc_def_id);
-                    info!("Encoding: {} ({})", proc_name, proc_def_path);
                     assert!(substs.is_empty());
-
-                    if config::unsafe_core_proof() {
-                        if self.env.query.is_unsafe_function(proc_def_id) {
-                            if let Err(error) =
-                                self.encode_lifetimes_core_proof(proc_def_id, CheckMode::Both)
-                            {
-                                self.register_encoding_error(error);
-                                debug!(
-                                    "Error encoding function: {:?} {}",
-                                    proc_def_id,
-                                    CheckMode::Both
-                                );
-                            }
-                        } else {
-                            if config::verify_core_proof() {
-                                if let Err(error) = self
-                                    .encode_lifetimes_core_proof(proc_def_id, CheckMode::CoreProof)
-                                {
-                                    self.register_encoding_error(error);
-                                    debug!(
-                                        "Error encoding function: {:?} {}",
-                                        proc_def_id,
-                                        CheckMode::CoreProof
-                                    );
-                                }
-                            }
-                            if config::verify_specifications() {
-                                let check_mode = if config::verify_specifications_with_core_proof()
-                                {
-                                    CheckMode::Both
-                                } else {
-                                    CheckMode::Specifications
-                                };
-                                if let Err(error) =
-                                    self.encode_lifetimes_core_proof(proc_def_id, check_mode)
-                                {
-                                    self.register_encoding_error(error);
-                                    debug!(
-                                        "Error encoding function: {:?} {}",
-                                        proc_def_id, check_mode
-                                    );
-                                }
-                            }
-                        }
-                        continue;
-                    }
-
-                    let proc_kind = self.get_proc_kind(proc_def_id, None);
-
-                    if matches!(proc_kind, ProcedureSpecificationKind::Pure) {
-                        // Check that the pure Rust function satisfies the basic
-                        // requirements by trying to encode it as a Viper function,
-                        // which will automatically run the validity checks.
-
-                        // TODO: Make sure that this encoded function does not end up in
-                        // the Viper file because that would be unsound.
-                        let identity_substs = self.env.query.identity_substs(proc_def_id);
-                        if let Err(error) =
-                            self.encode_pure_function_def(proc_def_id, proc_def_id, identity_substs)
-                        {
-                            self.register_encoding_error(error);
-                            debug!("Error encoding function: {:?}", proc_def_id);
-                            // Skip encoding the function as a method.
-                            continue;
-                        }
-                    }
-
-                    match proc_kind {
-                        _ if self.is_trusted(proc_def_id, None) => {
-                            debug!(
-                                "Trusted procedure will not be encoded or verified: {:?}",
-                                proc_def_id
-                            );
-                        }
-                        ProcedureSpecificationKind::Predicate(_) => {
-                            debug!(
-                                "Predicates will not be encoded or verified: {:?}",
-                                proc_def_id
-                            );
-                        }
-                        ProcedureSpecificationKind::Pure | ProcedureSpecificationKind::Impure => {
-                            if let Err(error) = self.encode_procedure(proc_def_id) {
-                                self.register_encoding_error(error);
-                                debug!("Error encoding function: {:?}", proc_def_id);
-                            } else {
-                                match self.finalize_viper_program(proc_name, proc_def_id) {
-                                    Ok(program) => self.programs.push(program),
-                                    Err(error) => {
-                                        self.register_encoding_error(error);
-                                        debug!("Error finalizing program: {:?}", proc_def_id);
-                                    }
-                                }
-                            }
-                        }
-                    }
+                    let start = Instant::now();
+                    let fold_unfold_time = self.fold_unfold_time.get();
+                    let item_config = self.get_item_config(proc_def_id);
+                    config::with_item_config(&item_config, || {
+                        self.encode_queued_procedure(proc_def_id)
+                    });
+                    self.procedure_encoding_profiles.borrow_mut().insert(
+                        proc_def_id,
+                        EncodingProfile {
+                            encoding_time: start.elapsed(),
+                            fold_unfold_time: self.fold_unfold_time.get() - fold_unfold_time,
+                        },
+                    );
                 }
                 EncodingTask::Type { ty } => {
                     if config::unsafe_core_proof()
@@ -917,6 +886,106 @@ impl<'v, 'tcx> Encoder<'v, 'tcx> {
         }
     }
 
+    /// Encode a procedure of the encoding queue, registering its encoding
+    /// errors.
+    fn encode_queued_procedure(&mut self, proc_def_id: ProcedureDefId) {
+        let proc_name = self.env.name.get_unique_item_name(proc_def_id);
+        let proc_def_path = self.env.name.get_item_def_path(proc_def_id);
+        info!("Encoding: {} ({})", proc_name, proc_def_path);
+        let _span = tracing::info_span!(
+            target: tracing::PHASE_TARGET,
+            "encode procedure",
+            item = %proc_name
+        )
+        .entered();
+        if config::unsafe_core_proof() {
+            if self.env.query.is_unsafe_function(proc_def_id) {
+                if let Err(error) = self.encode_lifetimes_core_proof(proc_def_id, CheckMode::Both) {
+                    self.register_procedure_encoding_error(proc_def_id, error);
+                    debug!(
+                        "Error encoding function: {:?} {}",
+                        proc_def_id,
+                        CheckMode::Both
+                    );
+                }
+            } else {
+                if config::verify_core_proof() {
+                    if let Err(error) =
+                        self.encode_lifetimes_core_proof(proc_def_id, CheckMode::CoreProof)
+                    {
+                        self.register_procedure_encoding_error(proc_def_id, error);
+                        debug!(
+                            "Error encoding function: {:?} {}",
+                            proc_def_id,
+                            CheckMode::CoreProof
+                        );
+                    }
+                }
+                if config::verify_specifications() {
+                    let check_mode = if config::verify_specifications_with_core_proof() {
+                        CheckMode::Both
+                    } else {
+                        CheckMode::Specifications
+                    };
+                    if let Err(error) = self.encode_lifetimes_core_proof(proc_def_id, check_mode) {
+                        self.register_procedure_encoding_error(proc_def_id, error);
+                        debug!("Error encoding function: {:?} {}", proc_def_id, check_mode);
+                    }
+                }
+            }
+            return;
+        }
+
+        let proc_kind = self.get_proc_kind(proc_def_id, None);
+
+        if matches!(proc_kind, ProcedureSpecificationKind::Pure) {
+            // Check that the pure Rust function satisfies the basic
+            // requirements by trying to encode it as a Viper function,
+            // which will automatically run the validity checks.
+
+            // TODO: Make sure that this encoded function does not end up in
+            // the Viper file because that would be unsound.
+            let identity_substs = self.env.query.identity_substs(proc_def_id);
+            if let Err(error) =
+                self.encode_pure_function_def(proc_def_id, proc_def_id, identity_substs)
+            {
+                self.register_procedure_encoding_error(proc_def_id, error);
+                debug!("Error encoding function: {:?}", proc_def_id);
+                // Skip encoding the function as a method.
+                return;
+            }
+        }
+
+        match proc_kind {
+            _ if self.is_trusted(proc_def_id, None) => {
+                debug!(
+                    "Trusted procedure will not be encoded or verified: {:?}",
+                    proc_def_id
+                );
+            }
+            ProcedureSpecificationKind::Predicate(_) => {
+                debug!(
+                    "Predicates will not be encoded or verified: {:?}",
+                    proc_def_id
+                );
+            }
+            ProcedureSpecificationKind::Pure | ProcedureSpecificationKind::Impure => {
+                if let Err(error) = self.encode_procedure(proc_def_id) {
+                    self.register_procedure_encoding_error(proc_def_id, error);
+                    debug!("Error encoding function: {:?}", proc_def_id);
+                } else {
+                    match self.finalize_viper_program(proc_name, proc_def_id) {
+                        Ok(program) => self.programs.push(program),
+                        Err(error) => {
+                            self.register_procedure_encoding_error(proc_def_id, error);
+                            debug!("Error finalizing program: {:?}", proc_def_id);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
     pub fn intern_viper_identifier<S: AsRef<str> + Debug>(
         &self,
         full_name: S,
@@ -9,7 +9,10 @@ use std::fmt::Debug;
 use super::PositionManager;
 use log::debug;
 use prusti_interface::{data::ProcedureDefId, PrustiError};
-use prusti_rustc_interface::{errors::MultiSpan, span::source_map::SourceMap};
+use prusti_rustc_interface::{
+    errors::MultiSpan,
+    span::{source_map::SourceMap, Span},
+};
 use rustc_hash::FxHashMap;
 use viper::VerificationError;
 use vir_crate::polymorphic::Position;
@@ -36,6 +39,8 @@ pub enum PanicCause {
     Unreachable,
     /// Caused by an unimplemented!()
     Unimplemented,
+    /// Caused by the panic condition of a called function
+    PanicCondition,
 }
 
 /// The kind of the method whose proof failed.
@@ -70,6 +75,17 @@ pub enum ErrorCtxt {
     AssertMethodPostconditionTypeInvariants,
     /// A Viper `exhale expr` that encodes the end of a Rust procedure with postcondition `expr`
     ExhaleMethodPostcondition,
+    /// A panic of a Rust procedure with a panic condition, which must hold when it panics.
+    /// Arguments: the context of the panic and the span of the panic condition
+    PanicOutsidePanicCondition(Box<ErrorCtxt>, Span),
+    /// A Viper `assert expr` that encodes a postcondition `expr` that must hold when a Rust
+    /// procedure panics
+    AssertPostconditionOnPanic,
+    /// A Viper `assert !cond` that encodes the end of a Rust procedure with panic condition `cond`
+    AssertPanicConditionOnReturn,
+    /// A call that might panic in a Rust procedure with postconditions on panic, which cannot
+    /// be checked after the call panicked
+    UnsupportedCallPanic,
     /// A generic loop invariant error.
     LoopInvariant,
     /// A Viper `exhale expr` that exhales the permissions of a loop invariant `expr`
@@ -188,6 +204,94 @@ pub enum ErrorCtxt {
     UnreachableFoldingState,
 }
 
+impl ErrorCtxt {
+    /// The name of the kind of the error context, e.g. `Panic` for
+    /// `ErrorCtxt::Panic(PanicCause::Assert)`. It is reported in the
+    /// machine-readable verification reports, so it must not change when a
+    /// variant is renamed.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ErrorCtxt::Panic(..) => "Panic",
+            ErrorCtxt::ExhaleMethodPrecondition => "ExhaleMethodPrecondition",
+            ErrorCtxt::UnexpectedAssumeMethodPrecondition => "UnexpectedAssumeMethodPrecondition",
+            ErrorCtxt::UnexpectedAssumeMethodPostcondition => "UnexpectedAssumeMethodPostcondition",
+            ErrorCtxt::AssertMethodPostcondition => "AssertMethodPostcondition",
+            ErrorCtxt::AssertMethodPostconditionTypeInvariants => {
+                "AssertMethodPostconditionTypeInvariants"
+            }
+            ErrorCtxt::ExhaleMethodPostcondition => "ExhaleMethodPostcondition",
+            ErrorCtxt::PanicOutsidePanicCondition(..) => "PanicOutsidePanicCondition",
+            ErrorCtxt::AssertPostconditionOnPanic => "AssertPostconditionOnPanic",
+            ErrorCtxt::AssertPanicConditionOnReturn => "AssertPanicConditionOnReturn",
+            ErrorCtxt::UnsupportedCallPanic => "UnsupportedCallPanic",
+            ErrorCtxt::LoopInvariant => "LoopInvariant",
+            ErrorCtxt::ExhaleLoopInvariantOnEntry => "ExhaleLoopInvariantOnEntry",
+            ErrorCtxt::ExhaleLoopInvariantAfterIteration => "ExhaleLoopInvariantAfterIteration",
+            ErrorCtxt::AssertLoopInvariantOnEntry => "AssertLoopInvariantOnEntry",
+            ErrorCtxt::AssertLoopInvariantAfterIteration => "AssertLoopInvariantAfterIteration",
+            ErrorCtxt::UnexpectedAssumeLoopInvariantOnEntry => {
+                "UnexpectedAssumeLoopInvariantOnEntry"
+            }
+            ErrorCtxt::LoopVariant => "LoopVariant",
+            ErrorCtxt::LoopVariantOnEntry => "LoopVariantOnEntry",
+            ErrorCtxt::LoopVariantAfterIteration => "LoopVariantAfterIteration",
+            ErrorCtxt::LoopVariantNonDecreased => "LoopVariantNonDecreased",
+            ErrorCtxt::UnexpectedReachableLoop => "UnexpectedReachableLoop",
+            ErrorCtxt::UnexpectedReachableCall => "UnexpectedReachableCall",
+            ErrorCtxt::CallTerminationMeasureLower => "CallTerminationMeasureLower",
+            ErrorCtxt::CallTerminationMeasureNonNegative => "CallTerminationMeasureNonNegative",
+            ErrorCtxt::UnexpectedAssignMethodTerminationMeasure => {
+                "UnexpectedAssignMethodTerminationMeasure"
+            }
+            ErrorCtxt::AssertTerminator(..) => "AssertTerminator",
+            ErrorCtxt::BoundsCheckAssert => "BoundsCheckAssert",
+            ErrorCtxt::SliceRangeBoundsCheckAssert(..) => "SliceRangeBoundsCheckAssert",
+            ErrorCtxt::AbortTerminator => "AbortTerminator",
+            ErrorCtxt::UnreachableTerminator => "UnreachableTerminator",
+            ErrorCtxt::Unexpected => "Unexpected",
+            ErrorCtxt::UnexpectedBuiltinMethod(..) => "UnexpectedBuiltinMethod",
+            ErrorCtxt::UnexpectedStorageLive => "UnexpectedStorageLive",
+            ErrorCtxt::UnexpectedStorageDead => "UnexpectedStorageDead",
+            ErrorCtxt::MovePlace => "MovePlace",
+            ErrorCtxt::CopyPlace => "CopyPlace",
+            ErrorCtxt::WritePlace => "WritePlace",
+            ErrorCtxt::Assign => "Assign",
+            ErrorCtxt::PureFunctionDefinition => "PureFunctionDefinition",
+            ErrorCtxt::PureFunctionCall => "PureFunctionCall",
+            ErrorCtxt::PureFunctionPostconditionValueRangeOfResult => {
+                "PureFunctionPostconditionValueRangeOfResult"
+            }
+            ErrorCtxt::PureFunctionAssertTerminator(..) => "PureFunctionAssertTerminator",
+            ErrorCtxt::PackageMagicWandForPostcondition => "PackageMagicWandForPostcondition",
+            ErrorCtxt::ApplyMagicWandOnExpiry => "ApplyMagicWandOnExpiry",
+            ErrorCtxt::DivergingCallInPureFunction => "DivergingCallInPureFunction",
+            ErrorCtxt::PanicInPureFunction(..) => "PanicInPureFunction",
+            ErrorCtxt::AssertMethodPreconditionWeakening => "AssertMethodPreconditionWeakening",
+            ErrorCtxt::AssertMethodPostconditionStrengthening => {
+                "AssertMethodPostconditionStrengthening"
+            }
+            ErrorCtxt::TypeCast => "TypeCast",
+            ErrorCtxt::Unsupported(..) => "Unsupported",
+            ErrorCtxt::Unfold => "Unfold",
+            ErrorCtxt::UnfoldUnionVariant => "UnfoldUnionVariant",
+            ErrorCtxt::ProcedureCall => "ProcedureCall",
+            ErrorCtxt::DropCall => "DropCall",
+            ErrorCtxt::LifetimeEncoding => "LifetimeEncoding",
+            ErrorCtxt::LifetimeTake => "LifetimeTake",
+            ErrorCtxt::LifetimeReturn => "LifetimeReturn",
+            ErrorCtxt::LifetimeInhale => "LifetimeInhale",
+            ErrorCtxt::LifetimeExhale => "LifetimeExhale",
+            ErrorCtxt::OpenMutRef => "OpenMutRef",
+            ErrorCtxt::OpenFracRef => "OpenFracRef",
+            ErrorCtxt::CloseMutRef => "CloseMutRef",
+            ErrorCtxt::CloseFracRef => "CloseFracRef",
+            ErrorCtxt::SetEnumVariant => "SetEnumVariant",
+            ErrorCtxt::Assumption => "Assumption",
+            ErrorCtxt::UnreachableFoldingState => "UnreachableFoldingState",
+        }
+    }
+}
+
 /// The error manager
 #[derive(Clone)]
 pub struct ErrorManager<'tcx> {
@@ -282,6 +386,15 @@ impl<'tcx> ErrorManager<'tcx> {
             .and_then(|id| self.position_manager.def_id.get(&id).copied())
     }
 
+    /// The context of the Viper node whose verification failed, if known.
+    pub fn get_error_context(&self, ver_error: &VerificationError) -> Option<&ErrorCtxt> {
+        ver_error
+            .offending_pos_id
+            .as_ref()
+            .and_then(|id| id.parse().ok())
+            .and_then(|id: u64| self.error_contexts.get(&id))
+    }
+
     #[tracing::instrument(level = "debug", skip(self))]
     pub fn translate_verification_error(&self, ver_error: &VerificationError) -> PrustiError {
         let opt_pos_id: Option<u64> = match ver_error.offending_pos_id {
@@ -393,6 +506,46 @@ impl<'tcx> ErrorManager<'tcx> {
                     .set_failing_assertion(opt_cause_span)
             }
 
+            ("assert.failed:assertion.false", ErrorCtxt::Panic(PanicCause::PanicCondition)) => {
+                PrustiError::verification(
+                    "the panic condition of the called function might hold",
+                    error_span
+                ).set_failing_assertion(opt_cause_span)
+            }
+
+            (_, ErrorCtxt::PanicOutsidePanicCondition(panic_ctxt, panic_condition_span)) => {
+                self.translate_verification_error_with_context(
+                    ver_error,
+                    error_span,
+                    opt_cause_span,
+                    panic_ctxt,
+                ).add_note(
+                    "the function might panic although its panic condition does not hold",
+                    Some(*panic_condition_span),
+                )
+            }
+
+            ("assert.failed:assertion.false", ErrorCtxt::AssertPostconditionOnPanic) => {
+                PrustiError::verification("postcondition on panic might not hold.", error_span)
+                    .push_primary_span(opt_cause_span)
+            }
+
+            ("assert.failed:assertion.false", ErrorCtxt::AssertPanicConditionOnReturn) => {
+                PrustiError::verification(
+                    "the function might return although its panic condition holds.",
+                    error_span
+                ).push_primary_span(opt_cause_span)
+            }
+
+            ("assert.failed:assertion.false", ErrorCtxt::UnsupportedCallPanic) => {
+                PrustiError::verification(
+                    "the panic condition of the called function might hold, but panics of calls \
+                    are not supported in functions with postconditions on panic",
+                    error_span
+                ).set_failing_assertion(opt_cause_span)
+                    .set_help("Prove that the panic condition of the called function does not hold.")
+            }
+
             ("assert.failed:assertion.false", ErrorCtxt::AssertTerminator(ref message)) => {
                 PrustiError::verification(format!("assertion might fail with \"{message}\""), error_span)
                     .set_failing_assertion(opt_cause_span)
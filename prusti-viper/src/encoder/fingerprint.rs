@@ -0,0 +1,391 @@
+// © 2023, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! The dependency fingerprint of a procedure, which changes whenever anything
+//! that the verification of the procedure depends on might have changed.
+
+use super::{mir::specifications::SpecificationsInterface, Encoder};
+use once_cell::sync::Lazy;
+use prusti_common::config;
+use prusti_interface::{
+    environment::Environment,
+    specs::typed::{ProcedureSpecification, ProcedureSpecificationKind, SpecificationItem},
+};
+use prusti_rustc_interface::{
+    hir::{def::DefKind, def_id::DefId},
+    middle::{
+        mir::{self, visit::Visitor},
+        ty::{self, GenericArgKind, Ty, TyCtxt},
+    },
+    span::Span,
+};
+use prusti_server::ProtocolVersion;
+use regex::Regex;
+use rustc_hash::{FxHashSet, FxHasher};
+use std::{
+    fmt::Write,
+    hash::{Hash, Hasher},
+};
+
+/// The configuration flags that do not change the result of the
+/// verification.
+const IGNORED_FLAGS: &[&str] = &[
+    "chrome_trace",
+    "fingerprint_dir",
+    "log_dir",
+    "profile_top_items",
+    "profile_verification",
+    "quiet",
+    "report_dir",
+    "verify_only_items",
+    "verify_skip_items",
+];
+
+/// How the verification of a procedure depends on an item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Dependency {
+    /// The signature, specifications and body of a procedure, or the body of
+    /// a closure or a constant.
+    Body,
+    /// The body of a specification, e.g. of a precondition or a predicate.
+    Spec,
+    /// The signature and specifications of a called procedure. For pure
+    /// functions, this includes their body.
+    Contract,
+    /// The definition and the specifications of a type.
+    Type,
+}
+
+/// Compute the dependency fingerprint of `proc_id`, which covers its body and
+/// specifications, the contracts of the procedures it calls, the types it
+/// uses, the configuration and the version of Prusti. Items of other crates
+/// are covered by the hash of their crate.
+pub(crate) fn procedure_fingerprint(encoder: &Encoder, proc_id: DefId) -> String {
+    let mut collector = DependencyCollector {
+        encoder,
+        visited: FxHashSet::default(),
+        queue: vec![],
+        descriptions: vec![],
+    };
+    collector.enqueue(proc_id, Dependency::Body);
+    while let Some((def_id, dependency)) = collector.queue.pop() {
+        let description = collector.describe(def_id, dependency);
+        collector.descriptions.push(description);
+    }
+    collector.descriptions.sort();
+
+    let mut hasher = FxHasher::default();
+    ProtocolVersion::current().to_string().hash(&mut hasher);
+    for flag in config::dump().split("\n\n") {
+        let name = flag.split('=').next().unwrap_or_default();
+        if !IGNORED_FLAGS.contains(&name) {
+            flag.hash(&mut hasher);
+        }
+    }
+    collector.descriptions.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// Remove the parts of a description that differ between compilations of the
+/// same source code, i.e. the indices of `DefId`s and the UUIDs of the
+/// specifications.
+fn normalize(description: &str) -> String {
+    static DEF_INDEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"DefId\(\d+:\d+ ~ ").unwrap());
+    static UUID: Lazy<Regex> = Lazy::new(|| {
+        Regex::new("[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}|[0-9a-f]{32}")
+            .unwrap()
+    });
+    let description = DEF_INDEX.replace_all(description, "DefId(");
+    UUID.replace_all(&description, "$$(UUID)").into_owned()
+}
+
+/// All the values of a specification item, including the refined ones.
+fn values<T>(item: &SpecificationItem<T>) -> Vec<&T> {
+    match item {
+        SpecificationItem::Empty => vec![],
+        SpecificationItem::Inherent(value) | SpecificationItem::Inherited(value) => vec![value],
+        SpecificationItem::Refined(from, to) => vec![from, to],
+    }
+}
+
+/// The specification functions of a procedure, e.g. its preconditions.
+fn spec_functions(spec: &ProcedureSpecification) -> Vec<DefId> {
+    let mut spec_functions = vec![];
+    for conditions in values(&spec.pres).into_iter().chain(values(&spec.posts)) {
+        spec_functions.extend(conditions);
+    }
+    for pledges in values(&spec.pledges) {
+        for pledge in pledges {
+            spec_functions.extend(pledge.lhs);
+            spec_functions.push(pledge.rhs);
+        }
+    }
+    for panics_if in values(&spec.panics_if).into_iter().flatten() {
+        spec_functions.push(*panics_if);
+    }
+    for posts_on_panic in values(&spec.posts_on_panic) {
+        spec_functions.extend(posts_on_panic);
+    }
+    for terminates in values(&spec.terminates).into_iter().flatten() {
+        spec_functions.push(terminates.to_def_id());
+    }
+    for kind in values(&spec.kind) {
+        if let ProcedureSpecificationKind::Predicate(Some(body)) = kind {
+            spec_functions.push(*body);
+        }
+    }
+    spec_functions
+}
+
+/// The types and the constants used by a MIR body.
+#[derive(Default)]
+struct BodyItems<'tcx> {
+    tys: Vec<Ty<'tcx>>,
+    constants: Vec<DefId>,
+}
+
+impl<'tcx> Visitor<'tcx> for BodyItems<'tcx> {
+    fn visit_ty(&mut self, ty: Ty<'tcx>, _: mir::visit::TyContext) {
+        self.tys.push(ty);
+    }
+
+    fn visit_constant(&mut self, constant: &mir::Constant<'tcx>, location: mir::Location) {
+        self.super_constant(constant, location);
+        if let mir::ConstantKind::Unevaluated(unevaluated, _) = constant.literal {
+            if unevaluated.promoted.is_none() {
+                self.constants.push(unevaluated.def);
+            }
+        }
+    }
+}
+
+struct DependencyCollector<'p, 'v: 'p, 'tcx: 'v> {
+    encoder: &'p Encoder<'v, 'tcx>,
+    visited: FxHashSet<(DefId, Dependency)>,
+    queue: Vec<(DefId, Dependency)>,
+    /// A description of each dependency that was visited.
+    descriptions: Vec<String>,
+}
+
+impl<'p, 'v: 'p, 'tcx: 'v> DependencyCollector<'p, 'v, 'tcx> {
+    fn env(&self) -> &'v Environment<'tcx> {
+        self.encoder.env()
+    }
+
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        self.env().tcx()
+    }
+
+    fn enqueue(&mut self, def_id: DefId, dependency: Dependency) {
+        if self.visited.insert((def_id, dependency)) {
+            self.queue.push((def_id, dependency));
+        }
+    }
+
+    fn snippet(&self, span: Span) -> String {
+        self.env()
+            .query
+            .codemap()
+            .span_to_snippet(span.source_callsite())
+            .unwrap_or_default()
+    }
+
+    /// Describe the parts of `def_id` that `dependency` covers, enqueuing the
+    /// items that they depend on in turn.
+    fn describe(&mut self, def_id: DefId, dependency: Dependency) -> String {
+        let tcx = self.tcx();
+        let mut description = format!("{dependency:?} {}\n", tcx.def_path_str(def_id));
+        match dependency {
+            Dependency::Type => self.describe_type(def_id, &mut description),
+            Dependency::Spec => {
+                if def_id.is_local() {
+                    let env = self.env();
+                    let body = env.body.get_expression_body(
+                        def_id,
+                        env.query.identity_substs(def_id),
+                        def_id,
+                    );
+                    self.describe_body(&body, def_id, &mut description);
+                }
+            }
+            Dependency::Body
+                if matches!(tcx.def_kind(def_id), DefKind::Const | DefKind::AssocConst) =>
+            {
+                self.describe_constant(def_id, &mut description)
+            }
+            Dependency::Body | Dependency::Contract => {
+                self.describe_procedure(def_id, dependency, &mut description)
+            }
+        }
+        let mut description = normalize(&description);
+        if !def_id.is_local() {
+            // External items only change with their crate. Their
+            // specifications can still be given locally with `extern_spec`,
+            // which is why they are described above.
+            writeln!(description, "crate {}", tcx.crate_hash(def_id.krate)).unwrap();
+        }
+        description
+    }
+
+    fn describe_constant(&self, def_id: DefId, description: &mut String) {
+        let Some(local_def_id) = def_id.as_local() else {
+            return;
+        };
+        let tcx = self.tcx();
+        match tcx.const_eval_poly(def_id) {
+            Ok(value) => writeln!(description, "{value:?}").unwrap(),
+            // E.g. the constant is generic.
+            Err(_) => {
+                let hir_id = tcx.local_def_id_to_hir_id(local_def_id);
+                let span = tcx.hir().span_with_body(hir_id);
+                writeln!(description, "{}", self.snippet(span)).unwrap();
+            }
+        }
+    }
+
+    fn describe_type(&mut self, def_id: DefId, description: &mut String) {
+        let tcx = self.tcx();
+        if def_id.is_local() {
+            let adt_def = tcx.adt_def(def_id);
+            for variant in adt_def.variants() {
+                writeln!(description, "variant {}", variant.name).unwrap();
+                for field in &variant.fields {
+                    let field_ty = tcx.type_of(field.did).instantiate_identity();
+                    writeln!(description, "  {}: {field_ty:?}", field.name).unwrap();
+                    self.enqueue_ty(field_ty, def_id);
+                }
+            }
+        }
+        if let Some(type_specs) = self.encoder.get_type_specs(def_id) {
+            writeln!(description, "{type_specs:?}").unwrap();
+            for invariants in values(&type_specs.invariant) {
+                for &invariant in invariants {
+                    self.enqueue(invariant, Dependency::Spec);
+                }
+            }
+        }
+    }
+
+    fn describe_procedure(
+        &mut self,
+        def_id: DefId,
+        dependency: Dependency,
+        description: &mut String,
+    ) {
+        let tcx = self.tcx();
+        let env = self.env();
+        if tcx.is_closure(def_id) {
+            if def_id.is_local() {
+                let body = env.body.get_closure_body(
+                    def_id,
+                    env.query.identity_substs(def_id),
+                    tcx.typeck_root_def_id(def_id),
+                );
+                self.describe_body(&body, def_id, description);
+            }
+            return;
+        }
+        if matches!(
+            tcx.def_kind(def_id),
+            DefKind::Fn | DefKind::AssocFn | DefKind::Ctor(..)
+        ) {
+            writeln!(
+                description,
+                "{:?}",
+                tcx.fn_sig(def_id).instantiate_identity()
+            )
+            .unwrap();
+        }
+        let mut is_pure = false;
+        let mut is_trusted = false;
+        if let Some(spec_graph) = self.encoder.get_user_procedure_specs(def_id) {
+            let specs = std::iter::once(&spec_graph.base_spec)
+                .chain(spec_graph.specs_with_constraints.values());
+            for spec in specs {
+                writeln!(description, "{spec:?}").unwrap();
+                is_pure |= spec.kind.is_pure().unwrap_or(false);
+                is_trusted |= spec.trusted.extract_inherit().unwrap_or(false);
+                for spec_function in spec_functions(spec) {
+                    self.enqueue(spec_function, Dependency::Spec);
+                }
+            }
+        }
+        let item_config = self.encoder.get_item_config(def_id);
+        if !item_config.is_empty() {
+            writeln!(description, "config {item_config}").unwrap();
+        }
+        // The bodies of trusted procedures are not verified, and only the
+        // bodies of pure functions are part of their contract.
+        let needs_body = (dependency == Dependency::Body || is_pure) && !is_trusted;
+        if let Some(local_def_id) = def_id.as_local() {
+            if needs_body
+                && env.query.has_body(def_id)
+                && matches!(tcx.def_kind(def_id), DefKind::Fn | DefKind::AssocFn)
+            {
+                let body = if is_pure {
+                    env.body
+                        .get_pure_fn_body(def_id, env.query.identity_substs(def_id), def_id)
+                } else {
+                    env.body.get_impure_fn_body_identity(local_def_id)
+                };
+                self.describe_body(&body, def_id, description);
+            }
+        }
+    }
+
+    /// Describe the source and the MIR of a body, enqueuing the procedures,
+    /// closures, constants and types that it uses.
+    fn describe_body(
+        &mut self,
+        body: &mir::Body<'tcx>,
+        caller_def_id: DefId,
+        description: &mut String,
+    ) {
+        // Promoted constants are not part of the MIR, but of the source.
+        writeln!(description, "{}", self.snippet(body.span)).unwrap();
+        for (local, local_decl) in body.local_decls.iter_enumerated() {
+            writeln!(description, "{local:?}: {:?}", local_decl.ty).unwrap();
+        }
+        for (bb, data) in body.basic_blocks.iter_enumerated() {
+            writeln!(description, "{bb:?}:").unwrap();
+            for statement in &data.statements {
+                writeln!(description, "  {:?}", statement.kind).unwrap();
+            }
+            writeln!(description, "  {:?}", data.terminator().kind).unwrap();
+        }
+        let mut body_items = BodyItems::default();
+        body_items.visit_body(body);
+        for ty in body_items.tys {
+            self.enqueue_ty(ty, caller_def_id);
+        }
+        for constant in body_items.constants {
+            self.enqueue(constant, Dependency::Body);
+        }
+    }
+
+    /// Enqueue the types, the called procedures and the closures mentioned by
+    /// `ty`, which is used in `caller_def_id`.
+    fn enqueue_ty(&mut self, ty: Ty<'tcx>, caller_def_id: DefId) {
+        for arg in ty.walk() {
+            let GenericArgKind::Type(ty) = arg.unpack() else {
+                continue;
+            };
+            match ty.kind() {
+                ty::TyKind::Adt(adt_def, _) => self.enqueue(adt_def.did(), Dependency::Type),
+                ty::TyKind::Closure(def_id, _) => self.enqueue(*def_id, Dependency::Body),
+                ty::TyKind::FnDef(def_id, substs) => {
+                    self.enqueue(*def_id, Dependency::Contract);
+                    let (resolved_def_id, _) =
+                        self.env()
+                            .query
+                            .resolve_method_call(caller_def_id, *def_id, substs);
+                    self.enqueue(resolved_def_id, Dependency::Contract);
+                }
+                _ => {}
+            }
+        }
+    }
+}
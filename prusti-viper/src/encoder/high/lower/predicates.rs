@@ -1,5 +1,8 @@
 use super::super::types::{create_value_field, interface::HighTypeEncoderInterfacePrivate};
-use crate::encoder::{errors::EncodingResult, high::lower::IntoPolymorphic};
+use crate::encoder::{
+    errors::{EncodingError, EncodingResult},
+    high::lower::IntoPolymorphic,
+};
 use vir_crate::{
     high as vir_high,
     polymorphic::{self as vir_poly, ExprIterator},
@@ -22,6 +25,11 @@ impl IntoPredicates for vir_high::TypeDecl {
         ty: &vir_high::Type,
         encoder: &impl HighTypeEncoderInterfacePrivate,
     ) -> Predicates {
+        if ty.contains_sets() || contains_sets(self) {
+            return Err(EncodingError::unsupported(
+                "sets and multisets are supported only with the unsafe core proof",
+            ));
+        }
         match self {
             vir_high::TypeDecl::Bool => construct_bool_predicate(encoder),
             vir_high::TypeDecl::Int(ty_decl) => ty_decl.lower(ty, encoder),
@@ -35,6 +43,9 @@ impl IntoPredicates for vir_high::TypeDecl {
             vir_high::TypeDecl::Slice(ty_decl) => ty_decl.lower(ty, encoder),
             vir_high::TypeDecl::Sequence(_ty_decl) => unimplemented!(),
             vir_high::TypeDecl::Map(_ty_decl) => unimplemented!(),
+            vir_high::TypeDecl::Set(_) | vir_high::TypeDecl::MultiSet(_) => {
+                unreachable!("Sets and multisets are rejected above")
+            }
             vir_high::TypeDecl::Reference(ty_decl) => ty_decl.lower(ty, encoder),
             vir_high::TypeDecl::Pointer(ty_decl) => ty_decl.lower(ty, encoder),
             vir_high::TypeDecl::Never => construct_never_predicate(encoder),
@@ -47,6 +58,33 @@ impl IntoPredicates for vir_high::TypeDecl {
     }
 }
 
+/// Whether a component of the declared type, such as a field, is a set or a
+/// multiset. The lowering of types cannot fail, so such declarations have to
+/// be rejected before lowering them.
+fn contains_sets(type_decl: &vir_high::TypeDecl) -> bool {
+    let struct_contains_sets = |decl: &vir_high::type_decl::Struct| {
+        decl.fields.iter().any(|field| field.ty.contains_sets())
+    };
+    match type_decl {
+        vir_high::TypeDecl::Set(_) | vir_high::TypeDecl::MultiSet(_) => true,
+        vir_high::TypeDecl::Tuple(decl) => decl.arguments.iter().any(vir_high::Type::contains_sets),
+        vir_high::TypeDecl::Struct(decl) => struct_contains_sets(decl),
+        vir_high::TypeDecl::Enum(decl) => {
+            decl.arguments.iter().any(vir_high::Type::contains_sets)
+                || decl.variants.iter().any(struct_contains_sets)
+        }
+        vir_high::TypeDecl::Array(decl) => decl.element_type.contains_sets(),
+        vir_high::TypeDecl::Slice(decl) => decl.element_type.contains_sets(),
+        vir_high::TypeDecl::Reference(decl) => decl.target_type.contains_sets(),
+        vir_high::TypeDecl::Pointer(decl) => decl.target_type.contains_sets(),
+        vir_high::TypeDecl::Closure(decl) => {
+            decl.arguments.iter().any(vir_high::Type::contains_sets)
+        }
+        vir_high::TypeDecl::Unsupported(decl) => decl.ty.contains_sets(),
+        _ => false,
+    }
+}
+
 fn construct_bool_predicate(encoder: &impl HighTypeEncoderInterfacePrivate) -> Predicates {
     let ty = vir_high::Type::Bool;
     let field = create_value_field(ty.clone())?.lower(encoder);
@@ -24,6 +24,9 @@ impl IntoPolymorphic<vir_poly::Type> for vir_high::Type {
                 key_type: Box::new(ty.key_type.lower(encoder)),
                 val_type: Box::new(ty.val_type.lower(encoder)),
             }),
+            vir_high::Type::Set(_) | vir_high::Type::MultiSet(_) => {
+                unimplemented!("Sets and multisets are supported only in the unsafe core proof")
+            }
             vir_high::Type::Float(float) => {
                 vir_poly::Type::typed_ref(float.to_string().to_lowercase())
             }
@@ -111,6 +111,12 @@ impl<'p, 'v, 'tcx> super::super::ensurer::Context for Visitor<'p, 'v, 'tcx> {
             }
             vir_typed::TypeDecl::Sequence(_) => unimplemented!("ty: {}", ty),
             vir_typed::TypeDecl::Map(_) => unimplemented!("ty: {}", ty),
+            vir_typed::TypeDecl::Set(_) | vir_typed::TypeDecl::MultiSet(_) => {
+                // The only field of `Set` and `Multiset` is private and they
+                // are encoded as values without fields, so a user program
+                // cannot access a place inside a set.
+                unreachable!("Sets have no sub-places: {}", ty)
+            }
             vir_typed::TypeDecl::Never => unimplemented!("ty: {}", ty),
             vir_typed::TypeDecl::Closure(_) => unimplemented!("ty: {}", ty),
             vir_typed::TypeDecl::Unsupported(_) => unimplemented!("ty: {}", ty),
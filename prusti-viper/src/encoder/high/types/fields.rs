@@ -22,6 +22,10 @@ pub(crate) fn create_value_field(ty: vir::Type) -> EncodingResult<vir::FieldDecl
 
         vir::Type::Map(ref _map) => vir::FieldDecl::new("val_map", 0usize, ty),
 
+        vir::Type::Set(ref _set) => vir::FieldDecl::new("val_set", 0usize, ty),
+
+        vir::Type::MultiSet(ref _multiset) => vir::FieldDecl::new("val_multiset", 0usize, ty),
+
         // For composed data structures, we typically use a snapshot rather than a field.
         // To unify how parameters are passed to functions, we treat them like a reference.
         vir::Type::Tuple(_)
@@ -1470,7 +1470,9 @@ impl<'p, 'v: 'p, 'tcx: 'v> BuiltinMethodsInterface for Lowerer<'p, 'v, 'tcx> {
                 | vir_mid::TypeDecl::Float(_)
                 | vir_mid::TypeDecl::Pointer(_)
                 | vir_mid::TypeDecl::Sequence(_)
-                | vir_mid::TypeDecl::Map(_) => {
+                | vir_mid::TypeDecl::Map(_)
+                | vir_mid::TypeDecl::Set(_)
+                | vir_mid::TypeDecl::MultiSet(_) => {
                     builder.add_memory_block_copy_call()?;
                 }
                 vir_mid::TypeDecl::Reference(vir_mid::type_decl::Reference {
@@ -1638,7 +1640,9 @@ impl<'p, 'v: 'p, 'tcx: 'v> BuiltinMethodsInterface for Lowerer<'p, 'v, 'tcx> {
                 | vir_mid::TypeDecl::Float(_)
                 | vir_mid::TypeDecl::Pointer(_)
                 | vir_mid::TypeDecl::Sequence(_)
-                | vir_mid::TypeDecl::Map(_) => {
+                | vir_mid::TypeDecl::Map(_)
+                | vir_mid::TypeDecl::Set(_)
+                | vir_mid::TypeDecl::MultiSet(_) => {
                     builder.add_memory_block_copy_call()?;
                 }
                 vir_mid::TypeDecl::Struct(decl) => {
@@ -1709,7 +1713,9 @@ impl<'p, 'v: 'p, 'tcx: 'v> BuiltinMethodsInterface for Lowerer<'p, 'v, 'tcx> {
                 | vir_mid::TypeDecl::Float(_)
                 | vir_mid::TypeDecl::Pointer(_)
                 | vir_mid::TypeDecl::Sequence(_)
-                | vir_mid::TypeDecl::Map(_) => {
+                | vir_mid::TypeDecl::Map(_)
+                | vir_mid::TypeDecl::Set(_)
+                | vir_mid::TypeDecl::MultiSet(_) => {
                     builder.add_write_address_constant_call()?;
                 }
                 vir_mid::TypeDecl::TypeVar(_)
@@ -2021,7 +2027,9 @@ impl<'p, 'v: 'p, 'tcx: 'v> BuiltinMethodsInterface for Lowerer<'p, 'v, 'tcx> {
                 | vir_mid::TypeDecl::Reference(_)
                 | vir_mid::TypeDecl::Pointer(_)
                 | vir_mid::TypeDecl::Sequence(_)
-                | vir_mid::TypeDecl::Map(_) => {
+                | vir_mid::TypeDecl::Map(_)
+                | vir_mid::TypeDecl::Set(_)
+                | vir_mid::TypeDecl::MultiSet(_) => {
                     // Primitive type. Nothing to do.
                 }
                 vir_mid::TypeDecl::TypeVar(_)
@@ -117,7 +117,9 @@ impl<'p, 'v: 'p, 'tcx: 'v> ComputeAddressInterface for Lowerer<'p, 'v, 'tcx> {
                 | vir_mid::TypeDecl::Trusted(_)
                 | vir_mid::TypeDecl::TypeVar(_)
                 | vir_mid::TypeDecl::Sequence(_)
-                | vir_mid::TypeDecl::Map(_) => {
+                | vir_mid::TypeDecl::Map(_)
+                | vir_mid::TypeDecl::Set(_)
+                | vir_mid::TypeDecl::MultiSet(_) => {
                     // Nothing to do.
                 }
                 vir_mid::TypeDecl::Struct(decl) => {
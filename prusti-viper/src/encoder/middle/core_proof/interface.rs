@@ -60,7 +60,11 @@ impl<'v, 'tcx: 'v> MidCoreProofEncoderInterface<'tcx> for super::super::super::E
             functions,
             methods,
         };
-        if config::inline_caller_for() {
+        // The SMT-LIB backend expects the functions to be inlined, like it
+        // expects the predicates to be removed from specification programs.
+        let verified_with_smt_lib = check_mode == CheckMode::Specifications
+            && config::verify_specifications_backend() == "smtlib";
+        if config::inline_caller_for() || verified_with_smt_lib {
             super::transformations::inline_functions::inline_caller_for(&mut program);
         }
         self.mid_core_proof_encoder_state
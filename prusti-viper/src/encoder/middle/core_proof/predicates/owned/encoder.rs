@@ -84,7 +84,9 @@ impl<'l, 'p, 'v, 'tcx> PredicateEncoder<'l, 'p, 'v, 'tcx> {
             | vir_mid::TypeDecl::Float(_)
             | vir_mid::TypeDecl::Pointer(_)
             | vir_mid::TypeDecl::Sequence(_)
-            | vir_mid::TypeDecl::Map(_) => {
+            | vir_mid::TypeDecl::Map(_)
+            | vir_mid::TypeDecl::Set(_)
+            | vir_mid::TypeDecl::MultiSet(_) => {
                 builder.add_base_memory_block()?;
                 builder.add_bytes_snapshot_equality()?;
             }
@@ -214,6 +216,8 @@ impl<'l, 'p, 'v, 'tcx> PredicateEncoder<'l, 'p, 'v, 'tcx> {
                 | vir_mid::TypeDecl::Sequence(_)
                 | vir_mid::TypeDecl::Trusted(_)
                 | vir_mid::TypeDecl::Map(_)
+                | vir_mid::TypeDecl::Set(_)
+                | vir_mid::TypeDecl::MultiSet(_)
                 | vir_mid::TypeDecl::TypeVar(_)
         ) {
             builder.create_body();
@@ -228,6 +232,8 @@ impl<'l, 'p, 'v, 'tcx> PredicateEncoder<'l, 'p, 'v, 'tcx> {
             | vir_mid::TypeDecl::Sequence(_)
             | vir_mid::TypeDecl::Trusted(_)
             | vir_mid::TypeDecl::Map(_)
+            | vir_mid::TypeDecl::Set(_)
+            | vir_mid::TypeDecl::MultiSet(_)
             | vir_mid::TypeDecl::TypeVar(_) => {}
             vir_mid::TypeDecl::Struct(decl) => {
                 for field in &decl.fields {
@@ -313,6 +319,8 @@ impl<'l, 'p, 'v, 'tcx> PredicateEncoder<'l, 'p, 'v, 'tcx> {
                 | vir_mid::TypeDecl::Sequence(_)
                 | vir_mid::TypeDecl::Trusted(_)
                 | vir_mid::TypeDecl::Map(_)
+                | vir_mid::TypeDecl::Set(_)
+                | vir_mid::TypeDecl::MultiSet(_)
                 | vir_mid::TypeDecl::TypeVar(_)
         ) {
             builder.create_body();
@@ -327,6 +335,8 @@ impl<'l, 'p, 'v, 'tcx> PredicateEncoder<'l, 'p, 'v, 'tcx> {
             | vir_mid::TypeDecl::Sequence(_)
             | vir_mid::TypeDecl::Trusted(_)
             | vir_mid::TypeDecl::Map(_)
+            | vir_mid::TypeDecl::Set(_)
+            | vir_mid::TypeDecl::MultiSet(_)
             | vir_mid::TypeDecl::TypeVar(_) => {}
             vir_mid::TypeDecl::Struct(decl) => {
                 for field in &decl.fields {
@@ -492,6 +492,34 @@ pub(super) trait IntoSnapshotLowerer<'p, 'v: 'p, 'tcx: 'v> {
             ))
         };
 
+        let set = |low_kind| {
+            Ok(vir_low::Expression::container_op(
+                low_kind,
+                vir_low::Type::set(ty_args[0].clone()),
+                args.clone(),
+                app.position,
+            ))
+        };
+
+        let multiset = |low_kind| {
+            Ok(vir_low::Expression::container_op(
+                low_kind,
+                vir_low::Type::multi_set(ty_args[0].clone()),
+                args.clone(),
+                app.position,
+            ))
+        };
+
+        // Viper expects the element before the container in `e in s`.
+        let set_element = |container_type, low_kind| {
+            vir_low::Expression::container_op(
+                low_kind,
+                container_type,
+                vec![args[1].clone(), args[0].clone()],
+                app.position,
+            )
+        };
+
         match app.function {
             BuiltinFunc::Size => {
                 let return_type = self.type_to_snapshot(lowerer, &app.return_type)?;
@@ -550,6 +578,12 @@ pub(super) trait IntoSnapshotLowerer<'p, 'v: 'p, 'tcx: 'v> {
                 let m = lowerer.construct_constant_snapshot(app.get_type(), m, app.position)?;
                 self.ensure_bool_expression(lowerer, app.get_type(), m, expect_math_bool)
             }
+            BuiltinFunc::MapDomain => Ok(vir_low::Expression::container_op(
+                ContainerOpKind::MapDomain,
+                vir_low::Type::set(ty_args[0].clone()),
+                args,
+                app.position,
+            )),
             BuiltinFunc::LookupSeq => {
                 use vir_low::operations::ty::Typed;
                 assert!(
@@ -585,6 +619,124 @@ pub(super) trait IntoSnapshotLowerer<'p, 'v: 'p, 'tcx: 'v> {
                 let value = seq(ContainerOpKind::SeqLen)?;
                 lowerer.construct_constant_snapshot(app.get_type(), value, app.position)
             }
+            BuiltinFunc::TakeSeq | BuiltinFunc::DropSeq | BuiltinFunc::UpdateSeq => {
+                let kind = match app.function {
+                    BuiltinFunc::TakeSeq => ContainerOpKind::SeqTake,
+                    BuiltinFunc::DropSeq => ContainerOpKind::SeqDrop,
+                    _ => ContainerOpKind::SeqUpdate,
+                };
+                let mut operands = args.clone();
+                operands[1] = lowerer.obtain_constant_value(
+                    app.arguments[1].get_type(),
+                    args[1].clone(),
+                    args[1].position(),
+                )?;
+                Ok(vir_low::Expression::container_op(
+                    kind,
+                    vir_low::Type::seq(ty_args[0].clone()),
+                    operands,
+                    app.position,
+                ))
+            }
+            BuiltinFunc::SliceSeq => {
+                // `s.slice(lo, hi)` is `s[..hi][lo..]`.
+                let seq_type = vir_low::Type::seq(ty_args[0].clone());
+                let lo = lowerer.obtain_constant_value(
+                    app.arguments[1].get_type(),
+                    args[1].clone(),
+                    args[1].position(),
+                )?;
+                let hi = lowerer.obtain_constant_value(
+                    app.arguments[2].get_type(),
+                    args[2].clone(),
+                    args[2].position(),
+                )?;
+                let prefix = vir_low::Expression::container_op(
+                    ContainerOpKind::SeqTake,
+                    seq_type.clone(),
+                    vec![args[0].clone(), hi],
+                    app.position,
+                );
+                Ok(vir_low::Expression::container_op(
+                    ContainerOpKind::SeqDrop,
+                    seq_type,
+                    vec![prefix, lo],
+                    app.position,
+                ))
+            }
+            BuiltinFunc::SeqContains => {
+                let value = set_element(
+                    vir_low::Type::seq(ty_args[0].clone()),
+                    ContainerOpKind::SeqContains,
+                );
+                let value =
+                    lowerer.construct_constant_snapshot(app.get_type(), value, app.position)?;
+                self.ensure_bool_expression(lowerer, app.get_type(), value, expect_math_bool)
+            }
+            BuiltinFunc::ToSeq => {
+                // The reference to the array or slice was dereferenced above,
+                // and the snapshot of its target is a sequence of element
+                // snapshots, just like the snapshot of `Seq<T>`.
+                use vir_low::operations::ty::Typed;
+                assert_eq!(args.len(), 1);
+                let target = args.pop().unwrap();
+                let seq_type = self.type_to_snapshot(lowerer, &app.return_type)?;
+                assert_eq!(
+                    target.get_type(),
+                    &seq_type,
+                    "Expected the snapshot of an array or slice"
+                );
+                Ok(target)
+            }
+            BuiltinFunc::EmptySet | BuiltinFunc::SingleSet => set(ContainerOpKind::SetConstructor),
+            BuiltinFunc::UnionSet => set(ContainerOpKind::SetUnion),
+            BuiltinFunc::IntersectionSet => set(ContainerOpKind::SetIntersection),
+            BuiltinFunc::DifferenceSet => set(ContainerOpKind::SetMinus),
+            BuiltinFunc::SetContains => {
+                let value = set_element(
+                    vir_low::Type::set(ty_args[0].clone()),
+                    ContainerOpKind::SetContains,
+                );
+                let value =
+                    lowerer.construct_constant_snapshot(app.get_type(), value, app.position)?;
+                self.ensure_bool_expression(lowerer, app.get_type(), value, expect_math_bool)
+            }
+            BuiltinFunc::SetLen => {
+                let value = set(ContainerOpKind::SetCardinality)?;
+                lowerer.construct_constant_snapshot(app.get_type(), value, app.position)
+            }
+            BuiltinFunc::EmptyMultiSet | BuiltinFunc::SingleMultiSet => {
+                multiset(ContainerOpKind::MultiSetConstructor)
+            }
+            BuiltinFunc::UnionMultiSet => multiset(ContainerOpKind::MultiSetUnion),
+            BuiltinFunc::IntersectionMultiSet => multiset(ContainerOpKind::MultiSetIntersection),
+            BuiltinFunc::DifferenceMultiSet => multiset(ContainerOpKind::MultiSetMinus),
+            BuiltinFunc::MultiSetContains => {
+                let count = set_element(
+                    vir_low::Type::multi_set(ty_args[0].clone()),
+                    ContainerOpKind::MultiSetContains,
+                );
+                let value = vir_low::Expression::binary_op(
+                    vir_low::BinaryOpKind::GtCmp,
+                    count,
+                    0.into(),
+                    app.position,
+                );
+                let value =
+                    lowerer.construct_constant_snapshot(app.get_type(), value, app.position)?;
+                self.ensure_bool_expression(lowerer, app.get_type(), value, expect_math_bool)
+            }
+            BuiltinFunc::MultiSetCount => {
+                let value = set_element(
+                    vir_low::Type::multi_set(ty_args[0].clone()),
+                    ContainerOpKind::MultiSetContains,
+                );
+                lowerer.construct_constant_snapshot(app.get_type(), value, app.position)
+            }
+            BuiltinFunc::MultiSetLen => {
+                let value = multiset(ContainerOpKind::MultiSetCardinality)?;
+                lowerer.construct_constant_snapshot(app.get_type(), value, app.position)
+            }
             BuiltinFunc::LifetimeIncluded => {
                 assert_eq!(args.len(), 2);
                 lowerer.encode_lifetime_included()?;
@@ -80,6 +80,11 @@ impl<'p, 'v: 'p, 'tcx: 'v> Private for Lowerer<'p, 'v, 'tcx> {
                 | vir_mid::TypeDecl::Pointer(_) => {
                     unreachable!("place: {}", place);
                 }
+                vir_mid::TypeDecl::Set(_) | vir_mid::TypeDecl::MultiSet(_) => {
+                    // The only field of `Set` and `Multiset` is private, so a
+                    // user program cannot assign to a place inside a set.
+                    unreachable!("place: {}", place);
+                }
                 vir_mid::TypeDecl::Trusted(_) | vir_mid::TypeDecl::TypeVar(_) => {
                     unimplemented!("ty: {}", type_decl)
                 }
@@ -105,7 +105,9 @@ impl<'p, 'v: 'p, 'tcx: 'v> Private for Lowerer<'p, 'v, 'tcx> {
                 // FIXME: we should make sure that the snapshot and validity
                 // function is generated, but nothing else.
             }
-            vir_mid::TypeDecl::Map(_) => {
+            vir_mid::TypeDecl::Map(_)
+            | vir_mid::TypeDecl::Set(_)
+            | vir_mid::TypeDecl::MultiSet(_) => {
                 // FIXME: we should generate validity and to_bytes functions.
                 // The ghost containers should be valid iff the values they
                 // contain are valid.
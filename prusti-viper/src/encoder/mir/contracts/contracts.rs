@@ -105,6 +105,57 @@ impl<L: fmt::Debug, P: fmt::Debug> ProcedureContractGeneric<L, P> {
         }
     }
 
+    pub fn functional_panic_condition<'a, 'tcx>(
+        &'a self,
+        env: &'a Environment<'tcx>,
+        substs: GenericArgsRef<'tcx>,
+    ) -> Option<(DefId, GenericArgsRef<'tcx>)> {
+        match self.specification.panics_if {
+            typed::SpecificationItem::Empty => None,
+            typed::SpecificationItem::Inherent(c) | typed::SpecificationItem::Refined(_, c) => {
+                c.map(|inherent_def_id| (inherent_def_id, substs))
+            }
+            typed::SpecificationItem::Inherited(c) => c.map(|inherited_def_id| {
+                (
+                    inherited_def_id,
+                    // Same comment as `functional_precondition` applies.
+                    env.query
+                        .find_trait_method_substs(self.def_id, substs)
+                        .unwrap()
+                        .1,
+                )
+            }),
+        }
+    }
+
+    pub fn functional_postcondition_on_panic<'a, 'tcx>(
+        &'a self,
+        env: &'a Environment<'tcx>,
+        substs: GenericArgsRef<'tcx>,
+    ) -> Vec<(DefId, GenericArgsRef<'tcx>)> {
+        match &self.specification.posts_on_panic {
+            typed::SpecificationItem::Empty => vec![],
+            typed::SpecificationItem::Inherent(posts)
+            | typed::SpecificationItem::Refined(_, posts) => posts
+                .iter()
+                .map(|inherent_def_id| (*inherent_def_id, substs))
+                .collect(),
+            typed::SpecificationItem::Inherited(posts) => posts
+                .iter()
+                .map(|inherited_def_id| {
+                    (
+                        *inherited_def_id,
+                        // Same comment as `functional_precondition` applies.
+                        env.query
+                            .find_trait_method_substs(self.def_id, substs)
+                            .unwrap()
+                            .1,
+                    )
+                })
+                .collect(),
+        }
+    }
+
     pub fn functional_termination_measure<'a, 'tcx>(
         &'a self,
         env: &'a Environment<'tcx>,
@@ -224,6 +224,9 @@ impl<'p, 'v, 'tcx> BuiltinFuncAppEncoder<'p, 'v, 'tcx> for super::ProcedureEncod
             "prusti_contracts::Map::<K, V>::lookup" => {
                 make_builtin_call(self, block_builder, vir_high::BuiltinFunc::LookupMap)?
             }
+            "prusti_contracts::Map::<K, V>::domain" => {
+                make_builtin_call(self, block_builder, vir_high::BuiltinFunc::MapDomain)?
+            }
             "prusti_contracts::Seq::<T>::empty" => {
                 make_builtin_call(self, block_builder, vir_high::BuiltinFunc::EmptySeq)?
             }
@@ -236,6 +239,70 @@ impl<'p, 'v, 'tcx> BuiltinFuncAppEncoder<'p, 'v, 'tcx> for super::ProcedureEncod
             "prusti_contracts::Seq::<T>::lookup" => {
                 make_builtin_call(self, block_builder, vir_high::BuiltinFunc::LookupSeq)?
             }
+            "prusti_contracts::Seq::<T>::take" => {
+                make_builtin_call(self, block_builder, vir_high::BuiltinFunc::TakeSeq)?
+            }
+            "prusti_contracts::Seq::<T>::drop" => {
+                make_builtin_call(self, block_builder, vir_high::BuiltinFunc::DropSeq)?
+            }
+            "prusti_contracts::Seq::<T>::slice" => {
+                make_builtin_call(self, block_builder, vir_high::BuiltinFunc::SliceSeq)?
+            }
+            "prusti_contracts::Seq::<T>::update" => {
+                make_builtin_call(self, block_builder, vir_high::BuiltinFunc::UpdateSeq)?
+            }
+            "prusti_contracts::Seq::<T>::contains" => {
+                make_builtin_call(self, block_builder, vir_high::BuiltinFunc::SeqContains)?
+            }
+            "prusti_contracts::Set::<T>::empty" => {
+                make_builtin_call(self, block_builder, vir_high::BuiltinFunc::EmptySet)?
+            }
+            "prusti_contracts::Set::<T>::single" => {
+                make_builtin_call(self, block_builder, vir_high::BuiltinFunc::SingleSet)?
+            }
+            "prusti_contracts::Set::<T>::union" => {
+                make_builtin_call(self, block_builder, vir_high::BuiltinFunc::UnionSet)?
+            }
+            "prusti_contracts::Set::<T>::intersection" => {
+                make_builtin_call(self, block_builder, vir_high::BuiltinFunc::IntersectionSet)?
+            }
+            "prusti_contracts::Set::<T>::difference" => {
+                make_builtin_call(self, block_builder, vir_high::BuiltinFunc::DifferenceSet)?
+            }
+            "prusti_contracts::Set::<T>::contains" => {
+                make_builtin_call(self, block_builder, vir_high::BuiltinFunc::SetContains)?
+            }
+            "prusti_contracts::Set::<T>::len" => {
+                make_builtin_call(self, block_builder, vir_high::BuiltinFunc::SetLen)?
+            }
+            "prusti_contracts::Multiset::<T>::empty" => {
+                make_builtin_call(self, block_builder, vir_high::BuiltinFunc::EmptyMultiSet)?
+            }
+            "prusti_contracts::Multiset::<T>::single" => {
+                make_builtin_call(self, block_builder, vir_high::BuiltinFunc::SingleMultiSet)?
+            }
+            "prusti_contracts::Multiset::<T>::union" => {
+                make_builtin_call(self, block_builder, vir_high::BuiltinFunc::UnionMultiSet)?
+            }
+            "prusti_contracts::Multiset::<T>::intersection" => make_builtin_call(
+                self,
+                block_builder,
+                vir_high::BuiltinFunc::IntersectionMultiSet,
+            )?,
+            "prusti_contracts::Multiset::<T>::difference" => make_builtin_call(
+                self,
+                block_builder,
+                vir_high::BuiltinFunc::DifferenceMultiSet,
+            )?,
+            "prusti_contracts::Multiset::<T>::contains" => {
+                make_builtin_call(self, block_builder, vir_high::BuiltinFunc::MultiSetContains)?
+            }
+            "prusti_contracts::Multiset::<T>::count" => {
+                make_builtin_call(self, block_builder, vir_high::BuiltinFunc::MultiSetCount)?
+            }
+            "prusti_contracts::Multiset::<T>::len" => {
+                make_builtin_call(self, block_builder, vir_high::BuiltinFunc::MultiSetLen)?
+            }
             "prusti_contracts::Ghost::<T>::new" => {
                 make_manual_assign(self, block_builder, &mut |_, args, _| args[0].clone())?
             }
@@ -272,7 +339,9 @@ impl<'p, 'v, 'tcx> BuiltinFuncAppEncoder<'p, 'v, 'tcx> for super::ProcedureEncod
                         target_type:
                             box vir_high::Type::Int(vir_high::ty::Int::Unbounded)
                             | box vir_high::Type::Sequence(..)
-                            | box vir_high::Type::Map(..),
+                            | box vir_high::Type::Map(..)
+                            | box vir_high::Type::Set(..)
+                            | box vir_high::Type::MultiSet(..),
                         ..
                     })
                 ) {
@@ -56,6 +56,8 @@ impl<'a, 'p, 'v, 'tcx> GhostChecker<'a, 'p, 'v, 'tcx> {
             _ if ty_str.starts_with("Adt(prusti_contracts::Int,") => return true,
             _ if ty_str.starts_with("Adt(prusti_contracts::Seq,") => return true,
             _ if ty_str.starts_with("Adt(prusti_contracts::Map,") => return true,
+            _ if ty_str.starts_with("Adt(prusti_contracts::Set,") => return true,
+            _ if ty_str.starts_with("Adt(prusti_contracts::Multiset,") => return true,
             _ => {}
         }
 
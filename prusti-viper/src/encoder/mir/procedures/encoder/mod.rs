@@ -1926,6 +1926,12 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         // Encode loop invariants.
         let mut loop_invariant_blocks = self.specification_blocks.loop_invariant_blocks().clone();
         for loop_head in &self.procedure.loop_info().loop_heads {
+            if config::bmc() {
+                return Err(SpannedEncodingError::unsupported(
+                    "bounded model checking is not supported with the core proof encoding",
+                    self.mir[*loop_head].terminator().source_info.span,
+                ));
+            }
             let (invariant_location, specification_blocks) =
                 if let Some(blocks) = loop_invariant_blocks.remove(loop_head) {
                     (blocks.location, blocks.specification_blocks)
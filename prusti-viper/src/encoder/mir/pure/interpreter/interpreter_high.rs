@@ -572,6 +572,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ExpressionBackwardInterpreter<'p, 'v, 'tcx> {
                 "lookup" => (LookupMap, val_type),
                 "delete" => unimplemented!(),
                 "contains" => (MapContains, Type::bool()),
+                "domain" => (MapDomain, encoded_lhs.get_type().clone()),
                 _ => unreachable!("no further Map functions"),
             });
         } else if let Some(proc_name) = proc_name.strip_prefix("prusti_contracts::Seq::<T>::") {
@@ -586,8 +587,63 @@ impl<'p, 'v: 'p, 'tcx: 'v> ExpressionBackwardInterpreter<'p, 'v, 'tcx> {
                 "len" => (SeqLen, Type::Int(vir_high::ty::Int::Unbounded)),
                 "lookup" => (LookupSeq, elem_type),
                 "concat" => (ConcatSeq, seq_type),
+                "take" => (TakeSeq, seq_type),
+                "drop" => (DropSeq, seq_type),
+                "slice" => (SliceSeq, seq_type),
+                "update" => (UpdateSeq, seq_type),
+                "contains" => (SeqContains, Type::bool()),
                 _ => unreachable!("no further Seq functions"),
             });
+        } else if let Some(proc_name) = proc_name.strip_prefix("prusti_contracts::Set::<T>::") {
+            assert_eq!(type_arguments.len(), 1);
+
+            let elem_type = type_arguments[0].clone();
+            let set_type = Type::set(elem_type, lifetimes);
+
+            return builtin(match proc_name {
+                "empty" => (EmptySet, set_type),
+                "single" => (SingleSet, set_type),
+                "union" => (UnionSet, set_type),
+                "intersection" => (IntersectionSet, set_type),
+                "difference" => (DifferenceSet, set_type),
+                "contains" => (SetContains, Type::bool()),
+                "len" => (SetLen, Type::Int(Int::Unbounded)),
+                _ => unreachable!("no further Set functions"),
+            });
+        } else if let Some(proc_name) = proc_name.strip_prefix("prusti_contracts::Multiset::<T>::")
+        {
+            assert_eq!(type_arguments.len(), 1);
+
+            let elem_type = type_arguments[0].clone();
+            let multiset_type = Type::multi_set(elem_type, lifetimes);
+
+            return builtin(match proc_name {
+                "empty" => (EmptyMultiSet, multiset_type),
+                "single" => (SingleMultiSet, multiset_type),
+                "union" => (UnionMultiSet, multiset_type),
+                "intersection" => (IntersectionMultiSet, multiset_type),
+                "difference" => (DifferenceMultiSet, multiset_type),
+                "contains" => (MultiSetContains, Type::bool()),
+                "count" => (MultiSetCount, Type::Int(Int::Unbounded)),
+                "len" => (MultiSetLen, Type::Int(Int::Unbounded)),
+                _ => unreachable!("no further Multiset functions"),
+            });
+        } else if proc_name == "prusti_contracts::ToSeq::to_seq" {
+            // The snapshots of arrays and slices are already sequences.
+            let is_array_or_slice = matches!(
+                encoded_args[0].get_type(),
+                Type::Reference(Reference {
+                    target_type: box Type::Array(_) | box Type::Slice(_),
+                    ..
+                })
+            );
+            if !is_array_or_slice {
+                return Err(SpannedEncodingError::unsupported(
+                    "`to_seq` is supported only for arrays and slices",
+                    span,
+                ));
+            }
+            return builtin((ToSeq, encoded_lhs.get_type().clone()));
         } else if let Some(proc_name) = proc_name.strip_prefix("prusti_contracts::Int::") {
             assert!(type_arguments.is_empty());
             return match proc_name {
@@ -334,56 +334,59 @@ impl<'v, 'tcx: 'v> PureFunctionEncoderInterface<'v, 'tcx>
             let is_bodyless = self.is_trusted(proc_def_id, Some(substs))
                 || !self.env().query.has_body(proc_def_id);
 
-            let maybe_identifier: SpannedEncodingResult<vir_poly::FunctionIdentifier> = (|| {
-                let proc_kind = self.get_proc_kind(proc_def_id, Some(substs));
-                let mut function = if is_bodyless {
-                    pure_function_encoder.encode_bodyless_function()?
-                } else {
-                    match proc_kind {
-                        ProcedureSpecificationKind::Predicate(Some(predicate_body)) => {
-                            pure_function_encoder.encode_predicate_function(&predicate_body)?
-                        }
-                        ProcedureSpecificationKind::Predicate(None) => {
-                            pure_function_encoder.encode_bodyless_function()?
-                        }
-                        ProcedureSpecificationKind::Pure => {
-                            let function = pure_function_encoder.encode_function()?;
-                            if config::use_new_encoder() {
-                                // Test the new encoding.
-                                let _ = super::encoder_high::encode_function_decl(
-                                    self,
-                                    proc_def_id,
-                                    proc_def_id,
-                                    substs,
-                                )?;
+            // A pure function is encoded once for all its callers, so it uses
+            // its own configuration overrides instead of theirs.
+            let item_config = self.get_item_config(proc_def_id);
+            let maybe_identifier: SpannedEncodingResult<vir_poly::FunctionIdentifier> =
+                config::with_item_config(&item_config, || {
+                    let proc_kind = self.get_proc_kind(proc_def_id, Some(substs));
+                    let mut function = if is_bodyless {
+                        pure_function_encoder.encode_bodyless_function()?
+                    } else {
+                        match proc_kind {
+                            ProcedureSpecificationKind::Predicate(Some(predicate_body)) => {
+                                pure_function_encoder.encode_predicate_function(&predicate_body)?
+                            }
+                            ProcedureSpecificationKind::Predicate(None) => {
+                                pure_function_encoder.encode_bodyless_function()?
+                            }
+                            ProcedureSpecificationKind::Pure => {
+                                let function = pure_function_encoder.encode_function()?;
+                                if config::use_new_encoder() {
+                                    // Test the new encoding.
+                                    let _ = super::encoder_high::encode_function_decl(
+                                        self,
+                                        proc_def_id,
+                                        proc_def_id,
+                                        substs,
+                                    )?;
+                                }
+                                function
+                            }
+                            ProcedureSpecificationKind::Impure => {
+                                unreachable!("trying to encode an impure function in pure encoder")
                             }
-                            function
-                        }
-                        ProcedureSpecificationKind::Impure => {
-                            unreachable!("trying to encode an impure function in pure encoder")
                         }
+                    };
+
+                    let needs_patching = matches!(
+                        proc_kind,
+                        ProcedureSpecificationKind::Pure
+                            | ProcedureSpecificationKind::Predicate(Some(_)),
+                    );
+                    if needs_patching {
+                        self.mirror_encoder
+                            .borrow_mut()
+                            .encode_mirrors(proc_def_id, &mut function);
                     }
-                };
-
-                let needs_patching = matches!(
-                    proc_kind,
-                    ProcedureSpecificationKind::Pure
-                        | ProcedureSpecificationKind::Predicate(Some(_)),
-                );
-                if needs_patching {
-                    self.mirror_encoder
-                        .borrow_mut()
-                        .encode_mirrors(proc_def_id, &mut function);
-                }
 
-                function = self
-                    .patch_snapshots_function(function)
-                    .with_span(mir_span)?;
+                    function = self
+                        .patch_snapshots_function(function)
+                        .with_span(mir_span)?;
 
-                self.log_vir_program_before_viper(function.to_string());
-                Ok(self.insert_function(function))
-            })(
-            );
+                    self.log_vir_program_before_viper(function.to_string());
+                    Ok(self.insert_function(function))
+                });
             match maybe_identifier {
                 Ok(identifier) => {
                     self.pure_function_encoder_state
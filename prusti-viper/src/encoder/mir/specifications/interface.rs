@@ -1,4 +1,5 @@
 use crate::encoder::mir::specifications::specs::Specifications;
+use prusti_common::config;
 use prusti_interface::{
     specs::{
         typed,
@@ -88,6 +89,10 @@ pub(crate) trait SpecificationsInterface<'tcx> {
     /// `prusti::loop_body_invariant_spec` attribute.
     fn get_loop_specs(&self, def_id: DefId) -> Option<typed::LoopSpecification>;
 
+    /// Get the configuration overrides of the `def_id` procedure, set with
+    /// the `#[prusti::config(...)]` attribute.
+    fn get_item_config(&self, def_id: DefId) -> config::ItemConfig;
+
     /// Get the specifications attached to the `def_id` type.
     fn get_type_specs(&self, def_id: DefId) -> Option<typed::TypeSpecification>;
 
@@ -113,6 +118,13 @@ pub(crate) trait SpecificationsInterface<'tcx> {
         substs: GenericArgsRef<'tcx>,
     ) -> Option<typed::ProcedureSpecification>;
 
+    /// Get the specifications attached to a function as written by the user,
+    /// i.e. before they are refined.
+    fn get_user_procedure_specs(
+        &self,
+        def_id: DefId,
+    ) -> Option<typed::SpecGraph<typed::ProcedureSpecification>>;
+
     /// Get the specifications attached to a function for a function call.
     fn get_procedure_specs_for_call(
         &self,
@@ -141,6 +153,9 @@ impl<'v, 'tcx: 'v> SpecificationsInterface<'tcx> for super::super::super::Encode
         let func_name = self.env().name.get_unique_item_name(def_id);
         if func_name.starts_with("prusti_contracts::prusti_contracts::Map")
             || func_name.starts_with("prusti_contracts::prusti_contracts::Seq")
+            || func_name.starts_with("prusti_contracts::prusti_contracts::Set")
+            || func_name.starts_with("prusti_contracts::prusti_contracts::Multiset")
+            || func_name.starts_with("prusti_contracts::prusti_contracts::ToSeq")
             || func_name.starts_with("prusti_contracts::prusti_contracts::Ghost")
             || func_name.starts_with("prusti_contracts::prusti_contracts::Int")
         {
@@ -213,6 +228,15 @@ impl<'v, 'tcx: 'v> SpecificationsInterface<'tcx> for super::super::super::Encode
             .cloned()
     }
 
+    fn get_item_config(&self, def_id: DefId) -> config::ItemConfig {
+        self.specifications_state
+            .specs
+            .borrow()
+            .get_item_config(&def_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
     fn get_type_specs(&self, def_id: DefId) -> Option<typed::TypeSpecification> {
         self.specifications_state
             .specs
@@ -272,6 +296,17 @@ impl<'v, 'tcx: 'v> SpecificationsInterface<'tcx> for super::super::super::Encode
         Some(spec.clone())
     }
 
+    fn get_user_procedure_specs(
+        &self,
+        def_id: DefId,
+    ) -> Option<typed::SpecGraph<typed::ProcedureSpecification>> {
+        self.specifications_state
+            .specs
+            .borrow()
+            .get_user_proc_spec(&def_id)
+            .cloned()
+    }
+
     fn get_procedure_specs_for_call(
         &self,
         called_def_id: DefId,
@@ -6,12 +6,14 @@ use crate::encoder::{
     },
 };
 use log::debug;
+use prusti_common::config;
 use prusti_interface::{
     environment::Environment,
     specs::typed::{
         DefSpecificationMap, GhostBegin, GhostEnd, LoopSpecification, ProcedureSpecification,
         ProcedureSpecificationKind, ProcedureSpecificationKindError, PrustiAssertion,
-        PrustiAssumption, PrustiRefutation, Refinable, SpecificationItem, TypeSpecification,
+        PrustiAssumption, PrustiRefutation, Refinable, SpecGraph, SpecificationItem,
+        TypeSpecification,
     },
     PrustiError,
 };
@@ -76,6 +78,19 @@ impl<'tcx> Specifications<'tcx> {
         self.user_typed_specs.get_loop_spec(def_id)
     }
 
+    #[tracing::instrument(level = "trace", skip(self))]
+    pub(super) fn get_item_config(&self, def_id: &DefId) -> Option<&config::ItemConfig> {
+        self.user_typed_specs.get_item_config(def_id)
+    }
+
+    #[tracing::instrument(level = "trace", skip(self))]
+    pub(super) fn get_user_proc_spec(
+        &self,
+        def_id: &DefId,
+    ) -> Option<&SpecGraph<ProcedureSpecification>> {
+        self.user_typed_specs.get_proc_spec(def_id)
+    }
+
     #[tracing::instrument(level = "trace", skip(self))]
     pub(super) fn get_type_spec(&self, def_id: &DefId) -> Option<&TypeSpecification> {
         self.user_typed_specs.get_type_spec(def_id)
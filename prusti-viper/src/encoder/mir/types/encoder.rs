@@ -48,6 +48,8 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
             type_name,
             "prusti_contracts::Seq"
                 | "prusti_contracts::Map"
+                | "prusti_contracts::Set"
+                | "prusti_contracts::Multiset"
                 | "prusti_contracts::Int"
                 | "prusti_contracts::Ghost"
         )
@@ -137,6 +139,16 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
                         val_type: enc_substs[1].clone(),
                         lifetimes,
                     })
+                } else if type_name == "prusti_contracts::Set" {
+                    vir::Type::Set(vir::ty::Set {
+                        element_type: enc_substs[0].clone(),
+                        lifetimes,
+                    })
+                } else if type_name == "prusti_contracts::Multiset" {
+                    vir::Type::MultiSet(vir::ty::MultiSet {
+                        element_type: enc_substs[0].clone(),
+                        lifetimes,
+                    })
                 } else if type_name == "prusti_contracts::Int" {
                     vir::Type::Int(vir::ty::Int::Unbounded)
                 } else if type_name == "prusti_contracts::Ghost" {
@@ -438,6 +450,12 @@ impl<'p, 'v, 'r: 'v, 'tcx: 'v> TypeEncoder<'p, 'v, 'tcx> {
                         enc_substs[0].clone(),
                         enc_substs[1].clone(),
                     ),
+                    "prusti_contracts::Set" => {
+                        vir::TypeDecl::set(lifetimes, const_parameters, enc_substs[0].clone())
+                    }
+                    "prusti_contracts::Multiset" => {
+                        vir::TypeDecl::multi_set(lifetimes, const_parameters, enc_substs[0].clone())
+                    }
                     "prusti_contracts::Int" => vir::TypeDecl::Int(vir::type_decl::Int {
                         lower_bound: None,
                         upper_bound: None,
@@ -4,6 +4,7 @@ use crate::encoder::{
     high::types::HighTypeEncoderInterface,
     mir::types::interface::ty::GenericArgsRef,
 };
+use prusti_common::config;
 use prusti_rustc_interface::{
     abi::FieldIdx,
     errors::MultiSpan,
@@ -416,9 +417,14 @@ impl<'v, 'tcx: 'v> MirTypeEncoderInterface<'tcx> for super::super::super::Encode
                     vir_high::TypeDecl::Struct(encoded_union.into_variant(&variant.index).unwrap())
                 }
                 _ => {
-                    let original_ty = self.decode_type_high(ty);
-                    let type_encoder = TypeEncoder::new(self, original_ty);
-                    type_encoder.encode_type_def_high()?
+                    // Type declarations are shared by all the procedures, so
+                    // they ignore the configuration overrides of the procedure
+                    // being encoded.
+                    config::with_item_config(&config::ItemConfig::default(), || {
+                        let original_ty = self.decode_type_high(ty);
+                        let type_encoder = TypeEncoder::new(self, original_ty);
+                        type_encoder.encode_type_def_high()
+                    })?
                 }
             };
             self.mir_type_encoder_state
@@ -9,7 +9,7 @@ pub use self::encoder::Encoder;
 mod builtin_encoder;
 #[allow(clippy::module_inception)]
 mod encoder;
-mod errors;
+pub(crate) mod errors;
 mod foldunfold;
 mod initialisation;
 mod interface;
@@ -31,4 +31,5 @@ mod middle;
 mod purifier;
 pub mod counterexamples;
 mod definition_collector;
+pub(crate) mod fingerprint;
 mod versioning;
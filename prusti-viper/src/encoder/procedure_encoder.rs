@@ -80,6 +80,7 @@ use std::{
     collections::BTreeMap,
     convert::TryInto,
     fmt::{Debug, Write},
+    time::Instant,
 };
 use vir_crate::polymorphic::{
     self as vir, borrows::Borrow, collect_assigned_vars, compute_identifier, CfgBlockIndex,
@@ -136,6 +137,14 @@ pub struct ProcedureEncoder<'p, 'v: 'p, 'tcx: 'v> {
     /// Type substitutions inside this procedure. Most likely identity for the
     /// given proc_def_id.
     substs: GenericArgsRef<'tcx>,
+    /// The panic condition of the procedure, with the local variable that stores its value in
+    /// the pre-state and its span.
+    panic_condition: Option<(vir::LocalVar, vir::Expr, Span)>,
+    /// The postconditions on panic of the procedure.
+    posts_on_panic: Vec<vir::Expr>,
+    /// The blocks of the loops that were unrolled for bounded model checking.
+    /// After such a loop, the label of one of its locations is ambiguous.
+    unrolled_loop_blocks: FxHashSet<BasicBlockIndex>,
 }
 
 impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
@@ -196,6 +205,9 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             old_ghost_vars: FxHashMap::default(),
             cached_loop_invariant_block: FxHashMap::default(),
             substs,
+            panic_condition: None,
+            posts_on_panic: vec![],
+            unrolled_loop_blocks: FxHashSet::default(),
         })
     }
 
@@ -505,6 +517,9 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
 
         self.encode_specification_blocks()?;
 
+        // Encode the panic specification, which is checked where the procedure might panic
+        self.encode_panic_specification()?;
+
         // Encode all blocks
         let (opt_body_head, unresolved_edges) = self.encode_blocks_group(
             "",
@@ -577,23 +592,27 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             .map(|(loan, location)| (loan.index().into(), *location))
             .collect();
         let method_pos = self.register_error(self.mir.span, ErrorCtxt::Unexpected);
+        let fold_unfold_start = Instant::now();
         let method_with_fold_unfold = foldunfold::add_fold_unfold(
             self.encoder,
             self.cfg_method,
             &loan_locations,
             &self.cfg_blocks_map,
             method_pos,
-        )
-        .map_err(|foldunfold_error| match foldunfold_error {
-            foldunfold::FoldUnfoldError::Unsupported(msg) => {
-                SpannedEncodingError::unsupported(msg, mir_span)
-            }
+        );
+        self.encoder
+            .add_fold_unfold_time(fold_unfold_start.elapsed());
+        let method_with_fold_unfold =
+            method_with_fold_unfold.map_err(|foldunfold_error| match foldunfold_error {
+                foldunfold::FoldUnfoldError::Unsupported(msg) => {
+                    SpannedEncodingError::unsupported(msg, mir_span)
+                }
 
-            _ => SpannedEncodingError::internal(
-                format!("cannot generate fold-unfold Viper statements. {foldunfold_error}",),
-                mir_span,
-            ),
-        })?;
+                _ => SpannedEncodingError::internal(
+                    format!("cannot generate fold-unfold Viper statements. {foldunfold_error}",),
+                    mir_span,
+                ),
+            })?;
 
         // Fix variable declarations.
         let method_with_fold_unfold = fix_ghost_vars(method_with_fold_unfold);
@@ -624,9 +643,51 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         group_loop_depth: usize,
         return_block: CfgBlockIndex,
     ) -> SpannedEncodingResult<(Option<CfgBlockIndex>, Vec<(CfgBlockIndex, BasicBlockIndex)>)> {
-        // Encode the CFG blocks
+        let (bb_map, unresolved_edges) = self.encode_blocks(
+            label_prefix,
+            ordered_group_blocks,
+            group_loop_depth,
+            return_block,
+        )?;
+
+        // Return unresolved CFG edges
+        let group_head = ordered_group_blocks.get(0).map(|bb| {
+            debug_assert!(
+                bb_map.contains_key(bb),
+                "Block {:?} (depth: {}, loop head: {}) has not been encoded \
+                (group_loop_depth: {}, ordered_group_blocks: {:?})",
+                bb,
+                self.loop_encoder.loops().get_loop_depth(*bb),
+                self.loop_encoder.loops().is_loop_head(*bb),
+                group_loop_depth,
+                ordered_group_blocks,
+            );
+            bb_map[bb]
+        });
+        let still_unresolved_edges =
+            self.encode_unresolved_edges(unresolved_edges, |bb| bb_map.get(&bb).cloned())?;
+        Ok((group_head, still_unresolved_edges))
+    }
+
+    /// Encodes the blocks of a topologically ordered group of blocks, without
+    /// linking them.
+    ///
+    /// Returns:
+    /// * The CFG block encoding each block, or each nested loop by its head.
+    /// * A vector of the edges between the encoded blocks.
+    #[allow(clippy::type_complexity)]
+    fn encode_blocks(
+        &mut self,
+        label_prefix: &str,
+        ordered_group_blocks: &[BasicBlockIndex],
+        group_loop_depth: usize,
+        return_block: CfgBlockIndex,
+    ) -> SpannedEncodingResult<(
+        FxHashMap<BasicBlockIndex, CfgBlockIndex>,
+        Vec<(CfgBlockIndex, BasicBlockIndex)>,
+    )> {
         let mut bb_map: FxHashMap<_, _> = FxHashMap::default();
-        let mut unresolved_edges: Vec<_> = vec![];
+        let mut edges: Vec<_> = vec![];
         for &curr_bb in ordered_group_blocks.iter() {
             let loop_info = self.loop_encoder.loops();
             let curr_loop_depth = loop_info.get_loop_depth(curr_bb);
@@ -646,26 +707,9 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                 }
             };
             bb_map.insert(curr_bb, curr_block);
-            unresolved_edges.extend(curr_edges);
+            edges.extend(curr_edges);
         }
-
-        // Return unresolved CFG edges
-        let group_head = ordered_group_blocks.get(0).map(|bb| {
-            debug_assert!(
-                bb_map.contains_key(bb),
-                "Block {:?} (depth: {}, loop head: {}) has not been encoded \
-                (group_loop_depth: {}, ordered_group_blocks: {:?})",
-                bb,
-                self.loop_encoder.loops().get_loop_depth(*bb),
-                self.loop_encoder.loops().is_loop_head(*bb),
-                group_loop_depth,
-                ordered_group_blocks,
-            );
-            bb_map[bb]
-        });
-        let still_unresolved_edges =
-            self.encode_unresolved_edges(unresolved_edges, |bb| bb_map.get(&bb).cloned())?;
-        Ok((group_head, still_unresolved_edges))
+        Ok((bb_map, edges))
     }
 
     fn encode_unresolved_edges<F: Fn(BasicBlockIndex) -> Option<CfgBlockIndex>>(
@@ -891,6 +935,16 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             })
             .collect();
 
+        if config::bmc() {
+            return self.encode_unrolled_loop(
+                &loop_label_prefix,
+                loop_head,
+                &loop_body,
+                loop_depth,
+                return_block,
+            );
+        }
+
         // Identify important blocks
         let loop_exit_blocks = loop_info.get_loop_exit_blocks(loop_head);
         let loop_exit_blocks_set: FxHashSet<_> = loop_exit_blocks.iter().cloned().collect();
@@ -1325,6 +1379,114 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         Ok((start_block, still_unresolved_edges))
     }
 
+    /// Encodes a loop for bounded model checking, by unrolling it such that
+    /// the executions that iterate it at most `config::bmc_unroll_depth()`
+    /// times are verified. The loop invariants, if any, are ignored.
+    ///
+    /// Returns:
+    /// * The first CFG block of the encoding
+    /// * A vector of unresolved CFG edges
+    ///
+    /// With a `bmc_unroll_depth` of 1, the encoding transforms
+    /// ```text
+    /// while { g = G; g } { B }
+    /// ```
+    /// into
+    /// ```text
+    /// g = G
+    /// if (g) {
+    ///   B
+    ///   g = G
+    ///   if (g) {
+    ///     B
+    ///     assume false
+    ///   }
+    /// }
+    /// ```
+    #[tracing::instrument(level = "debug", skip(self, loop_body))]
+    fn encode_unrolled_loop(
+        &mut self,
+        loop_label_prefix: &str,
+        loop_head: BasicBlockIndex,
+        loop_body: &[BasicBlockIndex],
+        loop_depth: usize,
+        return_block: CfgBlockIndex,
+    ) -> SpannedEncodingResult<(CfgBlockIndex, Vec<(CfgBlockIndex, BasicBlockIndex)>)> {
+        // The executions that would iterate the loop once more are assumed away.
+        let bound_block = self.cfg_method.add_block(
+            &format!("{loop_label_prefix}_bound"),
+            vec![
+                vir::Stmt::comment(format!("========== {loop_label_prefix}_bound ==========")),
+                vir::Stmt::Inhale(vir::Inhale { expr: false.into() }),
+            ],
+        );
+        self.cfg_method
+            .set_successor(bound_block, Successor::Return);
+
+        // Each iteration has its own labels, which are recorded by MIR location.
+        // The iterations are encoded in execution order, such that a loan that
+        // was created by the previous iteration refers to the label of that
+        // iteration. No loan of the loop body is alive when entering the loop.
+        let loop_body_set: FxHashSet<_> = loop_body.iter().copied().collect();
+        self.forget_locations_in(&loop_body_set);
+        let mut iterations = vec![];
+        for iteration in 0..=config::bmc_unroll_depth() {
+            iterations.push(self.encode_blocks(
+                &format!("{loop_label_prefix}_iteration{iteration}_"),
+                loop_body,
+                loop_depth,
+                return_block,
+            )?);
+        }
+        // After the loop, a label of the loop body could belong to any iteration.
+        self.forget_locations_in(&loop_body_set);
+        self.unrolled_loop_blocks.extend(loop_body_set);
+
+        // Link the back edges of each iteration to the head of the next one.
+        let mut heads: Vec<_> = iterations
+            .iter()
+            .map(|(bb_map, _)| bb_map[&loop_head])
+            .collect();
+        heads.push(bound_block);
+        let mut still_unresolved_edges = vec![];
+        for (iteration, (bb_map, edges)) in iterations.into_iter().enumerate() {
+            let next_head = heads[iteration + 1];
+            still_unresolved_edges.extend(self.encode_unresolved_edges(edges, |bb| {
+                if bb == loop_head {
+                    Some(next_head)
+                } else {
+                    bb_map.get(&bb).cloned()
+                }
+            })?);
+        }
+
+        let start_block = self.cfg_method.add_block(
+            &format!("{loop_label_prefix}_start"),
+            vec![vir::Stmt::comment(format!(
+                "========== {loop_label_prefix}_start =========="
+            ))],
+        );
+        self.cfg_method
+            .set_successor(start_block, Successor::Goto(heads[0]));
+        Ok((start_block, still_unresolved_edges))
+    }
+
+    /// Forget the labels, and the other encoding state recorded by MIR
+    /// location, of the locations in `blocks`.
+    fn forget_locations_in(&mut self, blocks: &FxHashSet<BasicBlockIndex>) {
+        let is_kept = |location: &mir::Location| !blocks.contains(&location.block);
+        self.label_after_location
+            .retain(|location, _| is_kept(location));
+        self.magic_wand_at_location
+            .retain(|location, _| is_kept(location));
+        self.array_magic_wand_at
+            .retain(|location, _| is_kept(location));
+        self.slice_created_at
+            .retain(|location, _| is_kept(location));
+        self.procedure_contracts
+            .retain(|location, _| is_kept(location));
+    }
+
     /// Encode a block.
     ///
     /// Returns:
@@ -2060,6 +2222,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
 
         let loan_location = self.polonius_info().get_loan_location(&loan);
         trace!("loan_location: {:?}", loan_location);
+        self.check_not_after_unrolled_loop(loan_location)?;
 
         let loan_places = self
             .polonius_info()
@@ -2604,19 +2767,17 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
 
                             let panic_cause =
                                 self.mir_encoder.encode_panic_cause(term.source_info.span);
-                            let pos = self.register_error(
-                                term.source_info.span,
-                                ErrorCtxt::Panic(panic_cause),
-                            );
 
                             if self.check_panics {
                                 stmts.push(vir::Stmt::comment(format!(
                                     "Rust panic - {panic_message}"
                                 )));
-                                stmts.push(vir::Stmt::Assert(vir::Assert {
-                                    expr: false.into(),
-                                    position: pos,
-                                }));
+                                stmts.extend(self.encode_panic_check(
+                                    false.into(),
+                                    term.source_info.span,
+                                    ErrorCtxt::Panic(panic_cause),
+                                    false,
+                                ));
                             } else {
                                 debug!("Absence of panic will not be checked")
                             }
@@ -2874,10 +3035,12 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
 
                 stmts.push(vir::Stmt::comment(format!("Rust assertion: {assert_msg}")));
                 if self.check_panics {
-                    stmts.push(vir::Stmt::Assert(vir::Assert {
-                        expr: viper_guard,
-                        position: self.register_error(term.source_info.span, error_ctxt),
-                    }));
+                    stmts.extend(self.encode_panic_check(
+                        viper_guard,
+                        term.source_info.span,
+                        error_ctxt,
+                        false,
+                    ));
                 } else {
                     stmts.push(vir::Stmt::comment("This assertion will not be checked"));
                     stmts.push(vir::Stmt::Inhale(vir::Inhale { expr: viper_guard }));
@@ -3063,16 +3226,14 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                 )?;
                 if self.check_panics {
                     // Check indexing in bounds
-                    stmts.push(vir::Stmt::Assert(vir::Assert {
-                        expr: vir_expr! { [start_expr] >= [vir::Expr::from(0usize)] },
-                        position: self.register_error(
-                            error_span,
-                            ErrorCtxt::SliceRangeBoundsCheckAssert(
-                                "the range start value may be smaller than 0 when slicing"
-                                    .to_string(),
-                            ),
+                    stmts.extend(self.encode_panic_check(
+                        vir_expr! { [start_expr] >= [vir::Expr::from(0usize)] },
+                        error_span,
+                        ErrorCtxt::SliceRangeBoundsCheckAssert(
+                            "the range start value may be smaller than 0 when slicing".to_string(),
                         ),
-                    }));
+                        false,
+                    ));
                 }
                 start_expr
             }
@@ -3100,15 +3261,14 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                         .encode_struct_field_value(encoded_idx, "end", usize_ty)?;
                 if self.check_panics {
                     // Check indexing in bounds
-                    stmts.push(vir::Stmt::Assert(vir::Assert {
-                        expr: vir_expr! { [end_expr] <= [original_len] },
-                        position: self.register_error(
-                            error_span,
-                            ErrorCtxt::SliceRangeBoundsCheckAssert(
-                                "the range end value may be out of bounds when slicing".to_string(),
-                            ),
+                    stmts.extend(self.encode_panic_check(
+                        vir_expr! { [end_expr] <= [original_len] },
+                        error_span,
+                        ErrorCtxt::SliceRangeBoundsCheckAssert(
+                            "the range end value may be out of bounds when slicing".to_string(),
                         ),
-                    }));
+                        false,
+                    ));
                 }
                 end_expr
             }
@@ -3125,15 +3285,14 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
                 let end_expr = vir_expr! { [end_expr] + [vir::Expr::from(1usize)] };
                 if self.check_panics {
                     // Check indexing in bounds
-                    stmts.push(vir::Stmt::Assert(vir::Assert {
-                        expr: vir_expr! { [end_expr] <= [original_len] },
-                        position: self.register_error(
-                            error_span,
-                            ErrorCtxt::SliceRangeBoundsCheckAssert(
-                                "the range end value may be out of bounds when slicing".to_string(),
-                            ),
+                    stmts.extend(self.encode_panic_check(
+                        vir_expr! { [end_expr] <= [original_len] },
+                        error_span,
+                        ErrorCtxt::SliceRangeBoundsCheckAssert(
+                            "the range end value may be out of bounds when slicing".to_string(),
                         ),
-                    }));
+                        false,
+                    ));
                 }
                 end_expr
             }
@@ -3156,15 +3315,14 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         if self.check_panics {
             // start must be leq than end
             if idx_ident != "std::ops::RangeFull" && idx_ident != "core::ops::RangeFull" {
-                stmts.push(vir::Stmt::Assert(vir::Assert {
-                    expr: vir_expr! { [start] <= [end] },
-                    position: self.register_error(
-                        error_span,
-                        ErrorCtxt::SliceRangeBoundsCheckAssert(
-                            "the range end may be smaller than the start when slicing".to_string(),
-                        ),
+                stmts.extend(self.encode_panic_check(
+                    vir_expr! { [start] <= [end] },
+                    error_span,
+                    ErrorCtxt::SliceRangeBoundsCheckAssert(
+                        "the range end may be smaller than the start when slicing".to_string(),
                     ),
-                }));
+                    false,
+                ));
             }
         }
 
@@ -3605,6 +3763,47 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             );
         }
 
+        // Evaluate the panic condition of the called procedure, which panics
+        // if and only if it holds.
+        let panic_condition_var = if let Some((assertion, assertion_substs)) =
+            procedure_contract.functional_panic_condition(self.encoder.env(), substs)
+        {
+            let encoded_args: Vec<vir::Expr> = procedure_contract
+                .args
+                .iter()
+                .map(|local| self.encode_prusti_local(*local).into())
+                .collect();
+            let panic_condition = self.encoder.encode_assertion(
+                &assertion,
+                None,
+                &encoded_args,
+                None,
+                false,
+                self.proc_def_id,
+                assertion_substs,
+            )?;
+            let panic_condition_pos = self
+                .mir_encoder
+                .register_span(self.encoder.env().query.get_def_span(assertion));
+            let var = self.cfg_method.add_fresh_local_var(vir::Type::Bool);
+            stmts.push(vir::Stmt::Assign(vir::Assign {
+                target: var.clone().into(),
+                source: replace_fake_exprs(panic_condition),
+                kind: vir::AssignKind::Copy,
+            }));
+            if self.check_panics {
+                stmts.extend(self.encode_panic_check(
+                    vir::Expr::not(var.clone().into()).set_pos(panic_condition_pos),
+                    call_site_span,
+                    ErrorCtxt::Panic(PanicCause::PanicCondition),
+                    true,
+                ));
+            }
+            Some(var)
+        } else {
+            None
+        };
+
         // Encode precondition.
         let (pre_type_spec, pre_mandatory_type_spec, pre_invs_spec, pre_func_spec) =
             self.encode_precondition_expr(&procedure_contract, substs, fake_expr_spans)?;
@@ -3712,6 +3911,12 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         stmts.push(vir::Stmt::Inhale(vir::Inhale {
             expr: replace_fake_exprs(post_func_spec),
         }));
+        if let Some(var) = panic_condition_var {
+            // The called procedure returned, so its panic condition did not hold.
+            stmts.push(vir::Stmt::Inhale(vir::Inhale {
+                expr: vir::Expr::not(var.into()),
+            }));
+        }
 
         // Exhale the permissions that were moved into magic wands.
         assert!(!pos.is_default());
@@ -4343,9 +4548,114 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         );
         self.cfg_method
             .add_stmt(start_cfg_block, vir::Stmt::label(PRECONDITION_LABEL));
+        if let Some((var, expr, _)) = &self.panic_condition {
+            self.cfg_method.add_stmt(
+                start_cfg_block,
+                vir::Stmt::comment("Evaluate the panic condition in the pre-state"),
+            );
+            self.cfg_method.add_stmt(
+                start_cfg_block,
+                vir::Stmt::Assign(vir::Assign {
+                    target: var.clone().into(),
+                    source: expr.clone(),
+                    kind: vir::AssignKind::Copy,
+                }),
+            );
+        }
         Ok(())
     }
 
+    /// Encode the panic condition and the postconditions on panic of the
+    /// procedure, which are checked wherever the procedure might panic.
+    fn encode_panic_specification(&mut self) -> SpannedEncodingResult<()> {
+        // This clone is only due to borrow checker restrictions
+        let contract = self.procedure_contract().clone();
+        let encoded_args: Vec<vir::Expr> = contract
+            .args
+            .iter()
+            .map(|local| self.encode_prusti_local(*local).into())
+            .collect();
+        if let Some((assertion, assertion_substs)) =
+            contract.functional_panic_condition(self.encoder.env(), self.substs)
+        {
+            let expr = self.encoder.encode_assertion(
+                &assertion,
+                None,
+                &encoded_args,
+                None,
+                false,
+                self.proc_def_id,
+                assertion_substs,
+            )?;
+            let span = self.encoder.env().query.get_def_span(assertion);
+            let var = self.cfg_method.add_fresh_local_var(vir::Type::Bool);
+            self.panic_condition = Some((var, expr, span));
+        }
+        for (assertion, assertion_substs) in
+            contract.functional_postcondition_on_panic(self.encoder.env(), self.substs)
+        {
+            // Like in a precondition, the arguments refer to their current
+            // values and `old(..)` to the values in the pre-state.
+            let expr = self.encoder.encode_assertion(
+                &assertion,
+                None,
+                &encoded_args,
+                None,
+                false,
+                self.proc_def_id,
+                assertion_substs,
+            )?;
+            self.posts_on_panic.push(expr);
+        }
+        Ok(())
+    }
+
+    /// Encode the check of a point where the procedure panics unless
+    /// `no_panic` holds. Without a panic specification, this is just an
+    /// `assert no_panic`. Otherwise, the panic condition and the
+    /// postconditions on panic have to hold if the procedure panics, and the
+    /// encoding continues on the path where it does not panic. `is_call` is
+    /// `true` if the panic is caused by a called procedure, in whose state
+    /// the postconditions on panic cannot be checked.
+    fn encode_panic_check(
+        &self,
+        no_panic: vir::Expr,
+        span: Span,
+        error_ctxt: ErrorCtxt,
+        is_call: bool,
+    ) -> Vec<vir::Stmt> {
+        let mut stmts = vec![];
+        let (allowed_panic, error_ctxt) = match &self.panic_condition {
+            Some((var, _, panic_condition_span)) => (
+                vir::Expr::or(no_panic.clone(), var.clone().into()).set_pos(no_panic.pos()),
+                ErrorCtxt::PanicOutsidePanicCondition(Box::new(error_ctxt), *panic_condition_span),
+            ),
+            None => (no_panic.clone(), error_ctxt),
+        };
+        stmts.push(vir::Stmt::Assert(vir::Assert {
+            expr: allowed_panic,
+            position: self.register_error(span, error_ctxt),
+        }));
+        if self.panic_condition.is_none() && self.posts_on_panic.is_empty() {
+            return stmts;
+        }
+        if is_call && !self.posts_on_panic.is_empty() {
+            stmts.push(vir::Stmt::Assert(vir::Assert {
+                expr: no_panic.clone(),
+                position: self.register_error(span, ErrorCtxt::UnsupportedCallPanic),
+            }));
+        } else {
+            for post in &self.posts_on_panic {
+                stmts.push(vir::Stmt::Assert(vir::Assert {
+                    expr: vir::Expr::or(no_panic.clone(), post.clone()).set_pos(post.pos()),
+                    position: self.register_error(span, ErrorCtxt::AssertPostconditionOnPanic),
+                }));
+            }
+        }
+        stmts.push(vir::Stmt::Inhale(vir::Inhale { expr: no_panic }));
+        stmts
+    }
+
     /// Encode the magic wand used in the postcondition with its
     /// functional specification. Returns (lhs, rhs).
     #[tracing::instrument(level = "debug", skip(self))]
@@ -5121,6 +5431,19 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
             }),
         );
 
+        // Assert that the panic condition does not hold
+        if let Some((var, _, panic_condition_span)) = &self.panic_condition {
+            let pos = self.register_error(self.mir.span, ErrorCtxt::AssertPanicConditionOnReturn);
+            let panic_condition_pos = self.mir_encoder.register_span(*panic_condition_span);
+            self.cfg_method.add_stmt(
+                return_cfg_block,
+                vir::Stmt::Assert(vir::Assert {
+                    expr: vir::Expr::not(var.clone().into()).set_default_pos(panic_condition_pos),
+                    position: pos,
+                }),
+            );
+        }
+
         // Assert type invariants
         self.cfg_method.add_stmt(
             return_cfg_block,
@@ -7073,6 +7396,7 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
     ) -> SpannedEncodingResult<Option<&String>> {
         let opt_label = self.label_after_location.get(&location);
         if opt_label.is_none() {
+            self.check_not_after_unrolled_loop(location)?;
             if config::allow_unreachable_unsupported_code() {
                 // The encoding of unsupported code does not generate the expected labels
                 debug!("Location {:?} has not yet been encoded", location);
@@ -7087,6 +7411,22 @@ impl<'p, 'v: 'p, 'tcx: 'v> ProcedureEncoder<'p, 'v, 'tcx> {
         Ok(opt_label)
     }
 
+    /// Report the use of the encoding of `location` after the loop that
+    /// contains it was unrolled for bounded model checking, because it could
+    /// belong to any of the iterations.
+    fn check_not_after_unrolled_loop(&self, location: mir::Location) -> SpannedEncodingResult<()> {
+        if self.unrolled_loop_blocks.contains(&location.block)
+            && !self.label_after_location.contains_key(&location)
+        {
+            return Err(SpannedEncodingError::unsupported(
+                "bounded model checking does not support loans that are created in a loop \
+                and are alive after it",
+                self.mir_encoder.get_span_of_location(location),
+            ));
+        }
+        Ok(())
+    }
+
     fn get_loop_span(&self, loop_head: mir::BasicBlock) -> Span {
         let loop_info = self.loop_encoder.loops();
         debug_assert!(loop_info.is_loop_head(loop_head));
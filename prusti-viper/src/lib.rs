@@ -24,4 +24,7 @@
 
 pub mod encoder;
 mod utils;
+mod verification_fingerprints;
+mod verification_profile;
+mod verification_report;
 pub mod verifier;
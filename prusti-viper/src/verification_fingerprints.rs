@@ -0,0 +1,95 @@
+// © 2023, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! The dependency fingerprints of the procedures of a crate that verified,
+//! which allow skipping the procedures that did not change since they were
+//! verified (see the `fingerprint_dir` configuration flag).
+
+use crate::encoder::{fingerprint::procedure_fingerprint, Encoder};
+use prusti_rustc_interface::hir::def_id::DefId;
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+pub struct VerificationFingerprints {
+    path: PathBuf,
+    /// The fingerprints of the procedures that verified, by unique item name.
+    verified: BTreeMap<String, String>,
+    /// The unique item name and the fingerprint of each procedure to verify.
+    current: FxHashMap<DefId, (String, String)>,
+}
+
+impl VerificationFingerprints {
+    /// Compute the fingerprints of `procedures`, and load the fingerprints of
+    /// the procedures that verified from `<dir>/<crate>-<source file>.json`.
+    pub fn new(encoder: &Encoder, procedures: &[DefId], dir: &Path) -> Self {
+        let env = encoder.env();
+        // A package can have a binary and a library crate with the same name.
+        let source_stem = env
+            .name
+            .source_path()
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let path = dir.join(format!(
+            "{}-{source_stem}.json",
+            env.name.local_crate_name()
+        ));
+        // A missing or invalid file means that nothing verified yet.
+        let verified = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        let current = procedures
+            .iter()
+            .map(|&proc_id| {
+                let name = env.name.get_unique_item_name(proc_id);
+                let _span = tracing::info_span!(
+                    target: tracing::PHASE_TARGET,
+                    "fingerprint procedure",
+                    procedure = %name
+                )
+                .entered();
+                let fingerprint = procedure_fingerprint(encoder, proc_id);
+                (proc_id, (name, fingerprint))
+            })
+            .collect();
+        Self {
+            path,
+            verified,
+            current,
+        }
+    }
+
+    /// Whether `proc_id` verified before and its fingerprint did not change
+    /// since then.
+    pub fn is_unchanged(&self, proc_id: DefId) -> bool {
+        self.current
+            .get(&proc_id)
+            .map_or(false, |(name, fingerprint)| {
+                self.verified.get(name) == Some(fingerprint)
+            })
+    }
+
+    /// Record that all the procedures to verify but the `failed` ones
+    /// verified, and write the fingerprints of the procedures that verified.
+    pub fn write(mut self, failed: &FxHashSet<DefId>) -> io::Result<()> {
+        for (proc_id, (name, fingerprint)) in self.current {
+            if failed.contains(&proc_id) {
+                self.verified.remove(&name);
+            } else {
+                self.verified.insert(name, fingerprint);
+            }
+        }
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, serde_json::to_string_pretty(&self.verified)?)
+    }
+}
@@ -0,0 +1,159 @@
+// © 2023, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! A per-item profile of the verification of a crate, collected when the
+//! `profile_verification` configuration flag is enabled.
+
+use prusti_interface::environment::Environment;
+use prusti_rustc_interface::hir::def_id::DefId;
+use rustc_hash::FxHashMap;
+use std::{fmt::Write, time::Duration};
+
+/// The time spent encoding a procedure.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EncodingProfile {
+    /// The total time spent encoding the procedure.
+    pub encoding_time: Duration,
+    /// The part of `encoding_time` spent adding fold-unfold statements.
+    pub fold_unfold_time: Duration,
+}
+
+#[derive(Debug)]
+struct ItemProfile {
+    name: String,
+    encoding: EncodingProfile,
+    /// The sum of the sizes of the programs encoding the item.
+    program_size: usize,
+    programs: usize,
+    verification_time: Duration,
+    /// The number of programs whose result was cached, if known.
+    cache_hits: Option<usize>,
+}
+
+impl ItemProfile {
+    fn total_time(&self) -> Duration {
+        self.encoding.encoding_time + self.verification_time
+    }
+
+    fn cells(&self) -> [String; 6] {
+        let cache_hits = match self.cache_hits {
+            Some(cache_hits) => format!("{cache_hits}/{}", self.programs),
+            None => "-".to_string(),
+        };
+        [
+            self.name.clone(),
+            format!("{:.3}", self.encoding.encoding_time.as_secs_f64()),
+            format!("{:.3}", self.encoding.fold_unfold_time.as_secs_f64()),
+            self.program_size.to_string(),
+            format!("{:.3}", self.verification_time.as_secs_f64()),
+            cache_hits,
+        ]
+    }
+}
+
+pub struct VerificationProfile {
+    items: Vec<ItemProfile>,
+    item_indices: FxHashMap<DefId, usize>,
+}
+
+impl VerificationProfile {
+    pub fn new(
+        env: &Environment<'_>,
+        procedures: &[DefId],
+        mut encoding_profiles: FxHashMap<DefId, EncodingProfile>,
+    ) -> Self {
+        let mut items = vec![];
+        let mut item_indices = FxHashMap::default();
+        for &proc_id in procedures {
+            item_indices.insert(proc_id, items.len());
+            items.push(ItemProfile {
+                name: env.name.get_unique_item_name(proc_id),
+                encoding: encoding_profiles.remove(&proc_id).unwrap_or_default(),
+                program_size: 0,
+                programs: 0,
+                verification_time: Duration::ZERO,
+                cache_hits: Some(0),
+            });
+        }
+        Self {
+            items,
+            item_indices,
+        }
+    }
+
+    fn item_mut(&mut self, proc_id: Option<DefId>) -> Option<&mut ItemProfile> {
+        let index = *self.item_indices.get(&proc_id?)?;
+        Some(&mut self.items[index])
+    }
+
+    /// Record a program of `size` encoding `proc_id`, if known.
+    pub fn add_program(&mut self, proc_id: Option<DefId>, size: usize) {
+        if let Some(item) = self.item_mut(proc_id) {
+            item.program_size += size;
+            item.programs += 1;
+        }
+    }
+
+    /// Record that a program encoding `proc_id` was verified in
+    /// `verification_time`. `cache_hit` is `None` if it is not known whether
+    /// the result was cached.
+    pub fn add_program_result(
+        &mut self,
+        proc_id: Option<DefId>,
+        verification_time: Duration,
+        cache_hit: Option<bool>,
+    ) {
+        if let Some(item) = self.item_mut(proc_id) {
+            item.verification_time += verification_time;
+            item.cache_hits = match (item.cache_hits, cache_hit) {
+                (Some(cache_hits), Some(cache_hit)) => Some(cache_hits + usize::from(cache_hit)),
+                _ => None,
+            };
+        }
+    }
+
+    /// A table of at most `max_items` items, from the slowest to the fastest.
+    pub fn table(&self, max_items: usize) -> String {
+        let header = [
+            "item",
+            "encoding (s)",
+            "fold-unfold (s)",
+            "program size",
+            "verification (s)",
+            "cache hits",
+        ];
+        let mut items: Vec<_> = self.items.iter().collect();
+        items.sort_by(|a, b| b.total_time().cmp(&a.total_time()));
+        let rows: Vec<_> = items
+            .into_iter()
+            .take(max_items)
+            .map(ItemProfile::cells)
+            .collect();
+        let mut widths = header.map(str::len);
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.len());
+            }
+        }
+        let mut table = String::new();
+        let mut write_row = |cells: &[&str]| {
+            for (column, (cell, width)) in cells.iter().zip(widths).enumerate() {
+                if column == 0 {
+                    write!(table, "{cell:<width$}").unwrap();
+                } else {
+                    write!(table, "  {cell:>width$}").unwrap();
+                }
+            }
+            writeln!(table).unwrap();
+        };
+        write_row(&header);
+        for row in &rows {
+            let cells: Vec<_> = row.iter().map(String::as_str).collect();
+            write_row(&cells);
+        }
+        table
+    }
+}
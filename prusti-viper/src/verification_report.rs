@@ -0,0 +1,349 @@
+// © 2023, ETH Zurich
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! A machine-readable report of the verification of a crate, written in JSON
+//! and in SARIF 2.1 when the `report_dir` configuration flag is set.
+
+use crate::encoder::errors::{EncodingErrorKind, SpannedEncodingError};
+use prusti_interface::{environment::Environment, PrustiError};
+use prusti_rustc_interface::{
+    errors::MultiSpan,
+    hir::def_id::DefId,
+    span::{source_map::SourceMap, Span},
+};
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde_json::{json, Value};
+use std::{fs, io, path::Path, time::Duration};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemStatus {
+    /// All the programs encoding the item verified.
+    Verified,
+    /// The verification of the item failed, or its encoding failed for a
+    /// reason other than an unsupported feature.
+    Failed,
+    /// The item uses features that Prusti does not support.
+    Unsupported,
+    /// The item is marked as trusted, so it is not verified.
+    Trusted,
+    /// No program encoding the item was verified, e.g. because it is a
+    /// predicate.
+    Skipped,
+}
+
+impl ItemStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ItemStatus::Verified => "verified",
+            ItemStatus::Failed => "failed",
+            ItemStatus::Unsupported => "unsupported",
+            ItemStatus::Trusted => "trusted",
+            ItemStatus::Skipped => "skipped",
+        }
+    }
+}
+
+/// A location in the source code, with 1-based lines and columns.
+#[derive(Debug, Clone)]
+struct SourceSpan {
+    file: String,
+    start_line: usize,
+    start_column: usize,
+    end_line: usize,
+    end_column: usize,
+}
+
+impl SourceSpan {
+    fn new(source_map: &SourceMap, span: Span) -> Option<Self> {
+        if span.is_dummy() {
+            return None;
+        }
+        let span = span.source_callsite();
+        let start = source_map.lookup_char_pos(span.lo());
+        let end = source_map.lookup_char_pos(span.hi());
+        Some(SourceSpan {
+            file: start.file.name.prefer_local().to_string(),
+            start_line: start.line,
+            start_column: start.col.0 + 1,
+            end_line: end.line,
+            end_column: end.col.0 + 1,
+        })
+    }
+
+    fn from_multi_span(source_map: &SourceMap, span: &MultiSpan) -> Option<Self> {
+        span.primary_span()
+            .and_then(|span| Self::new(source_map, span))
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "file": self.file,
+            "start_line": self.start_line,
+            "start_column": self.start_column,
+            "end_line": self.end_line,
+            "end_column": self.end_column,
+        })
+    }
+
+    fn to_sarif(&self) -> Value {
+        json!({
+            "physicalLocation": {
+                "artifactLocation": { "uri": self.file.replace('\\', "/") },
+                "region": {
+                    "startLine": self.start_line,
+                    "startColumn": self.start_column,
+                    "endLine": self.end_line,
+                    "endColumn": self.end_column,
+                },
+            },
+        })
+    }
+}
+
+/// An error reported for an item.
+#[derive(Debug, Clone)]
+struct ReportedError {
+    /// The `ErrorCtxt` of the failed obligation, or the kind of the error if
+    /// it is not a verification error (e.g. `Unsupported`).
+    kind: String,
+    message: String,
+    span: Option<SourceSpan>,
+}
+
+impl ReportedError {
+    fn to_json(&self) -> Value {
+        json!({
+            "kind": self.kind,
+            "message": self.message,
+            "span": self.span.as_ref().map(SourceSpan::to_json),
+        })
+    }
+
+    fn to_sarif(&self, item_name: Option<&str>) -> Value {
+        let mut locations = vec![];
+        if let Some(span) = &self.span {
+            let mut location = span.to_sarif();
+            if let Some(item_name) = item_name {
+                location["logicalLocations"] =
+                    json!([{ "fullyQualifiedName": item_name, "kind": "function" }]);
+            }
+            locations.push(location);
+        }
+        json!({
+            "ruleId": self.kind,
+            "level": "error",
+            "message": { "text": self.message },
+            "locations": locations,
+        })
+    }
+}
+
+#[derive(Debug)]
+struct ItemReport {
+    name: String,
+    def_path: String,
+    span: Option<SourceSpan>,
+    status: ItemStatus,
+    errors: Vec<ReportedError>,
+    verification_time: Duration,
+}
+
+impl ItemReport {
+    fn to_json(&self) -> Value {
+        json!({
+            "name": self.name,
+            "def_path": self.def_path,
+            "span": self.span.as_ref().map(SourceSpan::to_json),
+            "status": self.status.as_str(),
+            "verification_seconds": self.verification_time.as_secs_f64(),
+            "errors": self.errors.iter().map(ReportedError::to_json).collect::<Vec<_>>(),
+        })
+    }
+}
+
+/// Collects the status of each verified item while the results of the
+/// verification arrive.
+pub struct VerificationReport<'tcx> {
+    source_map: &'tcx SourceMap,
+    crate_name: String,
+    /// The name of the file of the report, without extension.
+    file_stem: String,
+    items: Vec<ItemReport>,
+    item_indices: FxHashMap<DefId, usize>,
+    /// Errors that could not be attributed to an item.
+    other_errors: Vec<ReportedError>,
+}
+
+impl<'tcx> VerificationReport<'tcx> {
+    /// Create a report for `procedures`, which are all skipped until their
+    /// status is known.
+    pub fn new(
+        env: &Environment<'tcx>,
+        procedures: &[DefId],
+        is_trusted: impl Fn(DefId) -> bool,
+    ) -> Self {
+        let source_map = env.query.codemap();
+        let crate_name = env.name.local_crate_name();
+        // A package can have a binary and a library crate with the same name.
+        let source_path = env.name.source_path();
+        let source_stem = source_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let mut items = vec![];
+        let mut item_indices = FxHashMap::default();
+        for &proc_id in procedures {
+            item_indices.insert(proc_id, items.len());
+            items.push(ItemReport {
+                name: env.name.get_unique_item_name(proc_id),
+                def_path: env.name.get_item_def_path(proc_id),
+                span: SourceSpan::new(source_map, env.query.get_def_span(proc_id)),
+                status: if is_trusted(proc_id) {
+                    ItemStatus::Trusted
+                } else {
+                    ItemStatus::Skipped
+                },
+                errors: vec![],
+                verification_time: Duration::ZERO,
+            });
+        }
+        Self {
+            source_map,
+            file_stem: format!("{crate_name}-{source_stem}"),
+            crate_name,
+            items,
+            item_indices,
+            other_errors: vec![],
+        }
+    }
+
+    fn item_mut(&mut self, proc_id: Option<DefId>) -> Option<&mut ItemReport> {
+        let index = *self.item_indices.get(&proc_id?)?;
+        Some(&mut self.items[index])
+    }
+
+    /// Record an error that prevented `proc_id` from being encoded.
+    pub fn add_encoding_error(&mut self, proc_id: DefId, error: &SpannedEncodingError) {
+        let (kind, status) = match error.kind() {
+            EncodingErrorKind::Unsupported(_) => ("Unsupported", ItemStatus::Unsupported),
+            EncodingErrorKind::Incorrect(_) => ("Incorrect", ItemStatus::Failed),
+            EncodingErrorKind::Internal(_) => ("Internal", ItemStatus::Failed),
+        };
+        let prusti_error: PrustiError = error.clone().into();
+        if !prusti_error.is_error() {
+            return;
+        }
+        let reported_error = ReportedError {
+            kind: kind.to_string(),
+            message: prusti_error.message().to_string(),
+            span: SourceSpan::from_multi_span(self.source_map, prusti_error.span()),
+        };
+        if let Some(item) = self.item_mut(Some(proc_id)) {
+            if item.status != ItemStatus::Failed {
+                item.status = status;
+            }
+            item.errors.push(reported_error);
+        }
+    }
+
+    /// Record an error of the verification of `proc_id`, if known. `kind`
+    /// describes what failed, e.g. the kind of the `ErrorCtxt` of a failed
+    /// obligation. Warnings are ignored.
+    pub fn add_error(&mut self, proc_id: Option<DefId>, kind: &str, error: &PrustiError) {
+        if !error.is_error() {
+            return;
+        }
+        let reported_error = ReportedError {
+            kind: kind.to_string(),
+            message: error.message().to_string(),
+            span: SourceSpan::from_multi_span(self.source_map, error.span()),
+        };
+        match self.item_mut(proc_id) {
+            Some(item) => {
+                item.status = ItemStatus::Failed;
+                item.errors.push(reported_error);
+            }
+            None => self.other_errors.push(reported_error),
+        }
+    }
+
+    /// Record that `proc_id` was not verified again because it verified
+    /// before and its dependencies did not change.
+    pub fn add_unchanged(&mut self, proc_id: DefId) {
+        if let Some(item) = self.item_mut(Some(proc_id)) {
+            item.status = ItemStatus::Verified;
+        }
+    }
+
+    /// Record that a program encoding `proc_id` was verified in
+    /// `verification_time`. Its errors, if any, must be added with
+    /// `add_error`.
+    pub fn add_program_result(&mut self, proc_id: Option<DefId>, verification_time: Duration) {
+        if let Some(item) = self.item_mut(proc_id) {
+            item.verification_time += verification_time;
+            if item.status == ItemStatus::Skipped {
+                item.status = ItemStatus::Verified;
+            }
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "crate": self.crate_name,
+            "items": self.items.iter().map(ItemReport::to_json).collect::<Vec<_>>(),
+            "errors": self.other_errors.iter().map(ReportedError::to_json).collect::<Vec<_>>(),
+        })
+    }
+
+    pub fn to_sarif(&self) -> Value {
+        let mut rules = vec![];
+        let mut rule_ids = FxHashSet::default();
+        let mut results = vec![];
+        let errors = self
+            .items
+            .iter()
+            .flat_map(|item| {
+                item.errors
+                    .iter()
+                    .map(|error| (Some(item.name.as_str()), error))
+            })
+            .chain(self.other_errors.iter().map(|error| (None, error)));
+        for (item_name, error) in errors {
+            if rule_ids.insert(error.kind.clone()) {
+                rules.push(json!({ "id": error.kind }));
+            }
+            results.push(error.to_sarif(item_name));
+        }
+        json!({
+            "$schema": SARIF_SCHEMA,
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "Prusti",
+                        "informationUri": "https://github.com/viperproject/prusti-dev",
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "rules": rules,
+                    },
+                },
+                "results": results,
+            }],
+        })
+    }
+
+    /// Write the report to `<dir>/<crate>-<source file>.json` and
+    /// `<dir>/<crate>-<source file>.sarif`.
+    pub fn write(&self, dir: &Path) -> io::Result<()> {
+        fs::create_dir_all(dir)?;
+        let json_path = dir.join(format!("{}.json", self.file_stem));
+        fs::write(json_path, serde_json::to_string_pretty(&self.to_json())?)?;
+        let sarif_path = dir.join(format!("{}.sarif", self.file_stem));
+        fs::write(sarif_path, serde_json::to_string_pretty(&self.to_sarif())?)?;
+        Ok(())
+    }
+}
@@ -4,16 +4,29 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use crate::encoder::{
-    counterexamples::{counterexample_translation, counterexample_translation_refactored},
-    Encoder,
+use crate::{
+    encoder::{
+        counterexamples::{counterexample_translation, counterexample_translation_refactored},
+        errors::ErrorCtxt,
+        Encoder,
+    },
+    verification_fingerprints::VerificationFingerprints,
+    verification_profile::VerificationProfile,
+    verification_report::VerificationReport,
 };
 use ::log::{debug, error, info};
+use futures::{
+    channel::mpsc,
+    future, pin_mut,
+    stream::{self, Stream, StreamExt},
+};
 use once_cell::sync::Lazy;
 use prusti_common::{
     config,
-    report::log,
-    vir::{optimizations::optimize_program, program::Program},
+    report::{log, user},
+    vir::{
+        obligation_splitting::split_obligations, optimizations::optimize_program, program::Program,
+    },
     Stopwatch,
 };
 use prusti_interface::{
@@ -22,12 +35,20 @@ use prusti_interface::{
     specs::typed,
     PrustiError,
 };
-use prusti_rustc_interface::span::DUMMY_SP;
+use prusti_rustc_interface::{hir::def_id::DefId, span::DUMMY_SP};
 use prusti_server::{
-    process_verification_request, spawn_server_thread, tokio::runtime::Builder, PrustiClient,
-    VerificationRequest, ViperBackendConfig,
+    process_verification_request, spawn_server_thread, tokio::runtime::Builder, ClientError,
+    PrustiClient, RemoteCache, RemoteCacheClient, VerificationRequest, ViperBackendConfig,
+};
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::{
+    panic,
+    path::Path,
+    thread,
+    time::{Duration, Instant},
 };
-use viper::{self, PersistentCache, Viper};
+use tracing::Instrument;
+use viper::{self, Cache, PersistentCache, VerificationBackend, Viper};
 use vir_crate::common::check_mode::CheckMode;
 
 /// A verifier is an object for verifying a single crate, potentially
@@ -57,16 +78,90 @@ impl<'v, 'tcx> Verifier<'v, 'tcx> {
             log::report("config", "prusti", config::dump());
         }
 
+        // Only the workers of a server can be stopped to enforce a deadline, and
+        // only they run the backends of a portfolio concurrently.
+        let feature_requiring_workers = if config::request_timeout().is_some() {
+            Some("`REQUEST_TIMEOUT`")
+        } else if config::viper_backend() == "portfolio"
+            || config::verify_specifications_backend() == "portfolio"
+        {
+            Some("the `Portfolio` backend")
+        } else {
+            None
+        };
+        if let Some(feature) = feature_requiring_workers {
+            if config::server_address().map_or(true, |address| address == "MOCK") {
+                self.env.diagnostic.span_err_with_help_and_notes(
+                    DUMMY_SP,
+                    &format!(
+                        "[Prusti: invalid configuration] {feature} requires a Prusti server \
+                        started with `--workers`"
+                    ),
+                    &Some("set `SERVER_ADDRESS` to the address of such a server".to_string()),
+                    &[],
+                );
+                return VerificationResult::Failure;
+            }
+        }
+
+        // Only the specification programs are predicate-free; removing the
+        // predicates from the other programs would skip the checks of the
+        // core proof.
+        if config::viper_backend() == "smtlib" {
+            self.env.diagnostic.span_err_with_help_and_notes(
+                DUMMY_SP,
+                "[Prusti: invalid configuration] the `SmtLib` backend can only verify \
+                specification programs",
+                &Some(
+                    "set `VERIFY_SPECIFICATIONS_BACKEND` to `SmtLib` instead of `VIPER_BACKEND`"
+                        .to_string(),
+                ),
+                &[],
+            );
+            return VerificationResult::Failure;
+        }
+
+        let mut procedures_by_program_name = FxHashMap::default();
         for &proc_id in &task.procedures {
+            procedures_by_program_name.insert(self.env.name.get_unique_item_name(proc_id), proc_id);
             let proc_name = self.env.name.get_absolute_item_name(proc_id);
             let proc_def_path = self.env.name.get_item_def_path(proc_id);
             let proc_span = self.env.query.get_def_span(proc_id);
             info!(" - {} ({})", proc_name, proc_def_path);
             info!("   Source: {:?}", proc_span);
         }
+
+        // Procedures that verified before and whose dependencies did not
+        // change are neither encoded nor verified.
+        let fingerprint_dir = config::fingerprint_dir();
+        let fingerprints = (!fingerprint_dir.is_empty() && !config::print_hash()).then(|| {
+            VerificationFingerprints::new(
+                &self.encoder,
+                &task.procedures,
+                Path::new(&fingerprint_dir),
+            )
+        });
+        let unchanged_procedures: FxHashSet<DefId> = fingerprints
+            .iter()
+            .flat_map(|fingerprints| {
+                task.procedures
+                    .iter()
+                    .copied()
+                    .filter(|&proc_id| fingerprints.is_unchanged(proc_id))
+            })
+            .collect();
+        if !unchanged_procedures.is_empty() {
+            user::message(format!(
+                "Skipping the verification of {} unchanged items",
+                unchanged_procedures.len()
+            ));
+        }
+
         for &proc_id in task.procedures.iter().rev() {
             // FIXME: Use the loop above.
-            self.encoder.queue_procedure_encoding(proc_id);
+            if !unchanged_procedures.contains(&proc_id) {
+                self.encoder.queue_procedure_encoding(proc_id);
+            }
         }
         for &type_id in task.types.iter().rev() {
             // FIXME: Use the loop above.
@@ -76,6 +171,61 @@ impl<'v, 'tcx> Verifier<'v, 'tcx> {
 
         let encoding_errors_count = self.encoder.count_encoding_errors();
 
+        let mut report = config::report_dir().map(|_| {
+            VerificationReport::new(self.env, &task.procedures, |proc_id| {
+                self.encoder.is_trusted_procedure(proc_id)
+            })
+        });
+        let procedure_encoding_errors = self.encoder.take_procedure_encoding_errors();
+        if let Some(report) = &mut report {
+            for (proc_id, errors) in &procedure_encoding_errors {
+                for error in errors {
+                    report.add_encoding_error(*proc_id, error);
+                }
+            }
+            for &proc_id in &unchanged_procedures {
+                report.add_unchanged(proc_id);
+            }
+        }
+
+        // The procedures that did not verify, for the fingerprints.
+        let mut failed_procedures: FxHashSet<DefId> =
+            procedure_encoding_errors.keys().copied().collect();
+        let procedure_encoding_errors_count = procedure_encoding_errors
+            .values()
+            .flatten()
+            .filter(|error| PrustiError::from((*error).clone()).is_error())
+            .count();
+        // Errors that cannot be attributed to a procedure might be caused by
+        // any of them.
+        let mut has_unattributed_errors = encoding_errors_count > procedure_encoding_errors_count;
+
+        let mut profile = config::profile_verification().then(|| {
+            VerificationProfile::new(
+                self.env,
+                &task.procedures,
+                self.encoder.take_procedure_encoding_profiles(),
+            )
+        });
+
+        // The configuration overrides of the procedure encoded by each program.
+        let program_configs: FxHashMap<String, config::ItemConfig> = procedures_by_program_name
+            .iter()
+            .map(|(program_name, &proc_id)| {
+                (
+                    program_name.clone(),
+                    self.encoder.get_procedure_config(proc_id),
+                )
+            })
+            .filter(|(_, item_config)| !item_config.is_empty())
+            .collect();
+        let program_config = |program_name: &str| {
+            program_configs
+                .get(program_name)
+                .cloned()
+                .unwrap_or_default()
+        };
+
         let polymorphic_programs = self.encoder.get_viper_programs();
 
         let mut programs: Vec<Program> = if config::simplify_encoding() {
@@ -83,7 +233,17 @@ impl<'v, 'tcx> Verifier<'v, 'tcx> {
             let source_file_name = self.encoder.env().name.source_file_name();
             polymorphic_programs
                 .into_iter()
-                .map(|program| Program::Legacy(optimize_program(program, &source_file_name).into()))
+                .map(|program| {
+                    let _span = tracing::info_span!(
+                        target: tracing::PHASE_TARGET,
+                        "optimize_program",
+                        program = %program.name
+                    )
+                    .entered();
+                    config::with_item_config(&program_config(&program.name), || {
+                        Program::Legacy(optimize_program(program, &source_file_name).into())
+                    })
+                })
                 .collect()
         } else {
             polymorphic_programs
@@ -93,58 +253,236 @@ impl<'v, 'tcx> Verifier<'v, 'tcx> {
         };
         programs.extend(self.encoder.get_core_proof_programs());
 
-        stopwatch.start_next("verifying Viper program");
-        let verification_results = verify_programs(self.env, programs);
-        stopwatch.finish();
-
-        // Group verification results
-        let mut verification_errors: Vec<_> = vec![];
-        let mut consistency_errors: Vec<_> = vec![];
-        let mut java_exceptions: Vec<_> = vec![];
-        for (method_name, result) in verification_results.into_iter() {
-            match result {
-                viper::VerificationResult::Success => {}
-                viper::VerificationResult::ConsistencyErrors(errors) => {
-                    for error in errors.into_iter() {
-                        consistency_errors.push((method_name.clone(), error));
-                    }
+        // The name of the split program of each part of a split program.
+        let mut split_program_names = FxHashMap::default();
+        programs = programs
+            .into_iter()
+            .flat_map(|program| {
+                let program_name = program.get_name().to_string();
+                if !config::with_item_config(
+                    &program_config(&program_name),
+                    config::split_obligations,
+                ) {
+                    return vec![program];
                 }
-                viper::VerificationResult::Failure(errors) => {
-                    for error in errors.into_iter() {
-                        verification_errors.push((method_name.clone(), error));
+                let parts = split_obligations(program);
+                if parts.len() > 1 {
+                    for part in &parts {
+                        split_program_names
+                            .insert(part.get_name().to_string(), program_name.clone());
                     }
                 }
-                viper::VerificationResult::JavaException(exception) => {
-                    java_exceptions.push((method_name, exception));
-                }
+                parts
+            })
+            .collect();
+        let original_program_name = |program_name: &str| {
+            split_program_names
+                .get(program_name)
+                .cloned()
+                .unwrap_or_else(|| program_name.to_string())
+        };
+
+        if let Some(profile) = &mut profile {
+            for program in &programs {
+                let proc_id = procedures_by_program_name
+                    .get(&original_program_name(program.get_name()))
+                    .copied();
+                profile.add_program(proc_id, program.get_size());
             }
         }
 
-        // Convert verification results to Prusti errors
-        let error_manager = self.encoder.error_manager();
+        stopwatch.start_next("verifying Viper program");
+        let verification_results = match verify_programs(self.env, programs, |program_name| {
+            program_config(&original_program_name(program_name))
+        }) {
+            Ok(verification_results) => verification_results,
+            Err(error) => {
+                let help = matches!(error, ClientError::IncompatibleServer(_)).then(|| {
+                    "use a server built from the same version of Prusti as the client".to_string()
+                });
+                self.env.diagnostic.span_err_with_help_and_notes(
+                    DUMMY_SP,
+                    &format!("[Prusti: server error] could not use the Prusti server: {error}"),
+                    &help,
+                    &[],
+                );
+                if let Some(report) = &report {
+                    self.write_report(report);
+                }
+                return VerificationResult::Failure;
+            }
+        };
+
+        // The results are reported as soon as they arrive, instead of waiting for the
+        // verification of all the programs to finish.
+        let runtime = Builder::new_current_thread()
+            .thread_name("prusti-viper")
+            .enable_all()
+            .build()
+            .expect("failed to construct Tokio runtime");
         let mut result = VerificationResult::Success;
+        let mut reported_errors = FxHashSet::default();
+        runtime.block_on(async {
+            pin_mut!(verification_results);
+            while let Some(program_result) = verification_results.next().await {
+                let method_name = original_program_name(&program_result.program_name);
+                let proc_id = procedures_by_program_name.get(&method_name).copied();
+                if let Some(report) = &mut report {
+                    report.add_program_result(proc_id, program_result.verification_time);
+                }
+                if let Some(profile) = &mut profile {
+                    profile.add_program_result(
+                        proc_id,
+                        program_result.verification_time,
+                        program_result.cache_hit,
+                    );
+                }
+                if self.report_backend_result(
+                    &method_name,
+                    proc_id,
+                    program_result.result,
+                    &mut report,
+                    &mut reported_errors,
+                ) == VerificationResult::Failure
+                {
+                    result = VerificationResult::Failure;
+                    match proc_id {
+                        Some(proc_id) => {
+                            failed_procedures.insert(proc_id);
+                        }
+                        None => has_unattributed_errors = true,
+                    }
+                }
+            }
+        });
+        stopwatch.finish();
 
-        for (method, error) in consistency_errors.into_iter() {
-            PrustiError::internal(
-                format!("consistency error in {method}: {error}"),
-                DUMMY_SP.into(),
-            )
-            .emit(&self.env.diagnostic);
-            result = VerificationResult::Failure;
+        if let Some(report) = &report {
+            self.write_report(report);
         }
-
-        for (method, exception) in java_exceptions.into_iter() {
-            error!("Java exception: {}", exception.get_stack_trace());
-            PrustiError::internal(format!("in {method}: {exception}"), DUMMY_SP.into())
+        if let Some(profile) = &profile {
+            self.write_profile(profile);
+        }
+        if let Some(fingerprints) = fingerprints {
+            if has_unattributed_errors {
+                failed_procedures.extend(&task.procedures);
+            }
+            if let Err(error) = fingerprints.write(&failed_procedures) {
+                PrustiError::warning(
+                    format!("could not write the fingerprints to {fingerprint_dir}: {error}"),
+                    DUMMY_SP.into(),
+                )
                 .emit(&self.env.diagnostic);
+            }
+        }
+
+        if encoding_errors_count != 0 {
             result = VerificationResult::Failure;
         }
 
-        // Report verification errors
+        result
+    }
+
+    /// Translate the result of verifying a single program to Prusti errors
+    /// and emit them, also adding them to the `report`, if any. `proc_id` is
+    /// the procedure encoded by the program, if any. The verification errors
+    /// already in `reported_errors`, e.g. reported by another part of the same
+    /// split program, are not emitted again.
+    fn report_backend_result(
+        &self,
+        method_name: &str,
+        proc_id: Option<DefId>,
+        backend_result: Result<viper::VerificationResult, ClientError>,
+        report: &mut Option<VerificationReport<'tcx>>,
+        reported_errors: &mut FxHashSet<ReportedErrorKey>,
+    ) -> VerificationResult {
+        let mut emit = |kind: &str, prusti_error: PrustiError| {
+            if let Some(report) = report {
+                report.add_error(proc_id, kind, &prusti_error);
+            }
+            prusti_error.emit(&self.env.diagnostic);
+        };
+        let span = proc_id.map_or(DUMMY_SP, |proc_id| self.env.query.get_def_span(proc_id));
+        let backend_result = match backend_result {
+            Ok(backend_result) => backend_result,
+            Err(ClientError::DeadlineExceeded(timeout)) => {
+                emit(
+                    "DeadlineExceeded",
+                    PrustiError::verification(
+                        format!(
+                            "the verification of {method_name} did not finish within {} seconds",
+                            timeout.as_secs()
+                        ),
+                        span.into(),
+                    )
+                    .add_note(
+                        "the deadline can be changed with the `REQUEST_TIMEOUT` configuration flag",
+                        None,
+                    ),
+                );
+                return VerificationResult::Failure;
+            }
+            Err(error) => {
+                emit(
+                    "Internal",
+                    PrustiError::internal(
+                        format!("the verification request of {method_name} failed: {error}"),
+                        span.into(),
+                    ),
+                );
+                return VerificationResult::Failure;
+            }
+        };
+        let verification_errors = match backend_result {
+            viper::VerificationResult::Success => return VerificationResult::Success,
+            viper::VerificationResult::ConsistencyErrors(errors) => {
+                for error in errors.into_iter() {
+                    emit(
+                        "Internal",
+                        PrustiError::internal(
+                            format!("consistency error in {method_name}: {error}"),
+                            DUMMY_SP.into(),
+                        ),
+                    );
+                }
+                return VerificationResult::Failure;
+            }
+            viper::VerificationResult::JavaException(exception) => {
+                error!("Java exception: {}", exception.get_stack_trace());
+                emit(
+                    "Internal",
+                    PrustiError::internal(
+                        format!("in {method_name}: {exception}"),
+                        DUMMY_SP.into(),
+                    ),
+                );
+                return VerificationResult::Failure;
+            }
+            viper::VerificationResult::Failure(errors) => errors,
+        };
+
+        // Convert verification errors to Prusti errors
+        let has_errors = !verification_errors.is_empty();
+        let error_manager = self.encoder.error_manager();
         let mut prusti_errors: Vec<_> = vec![];
-        for (method, verification_error) in verification_errors.into_iter() {
-            debug!("Verification error in {}: {:?}", method, verification_error);
+        for verification_error in verification_errors.into_iter() {
+            debug!(
+                "Verification error in {}: {:?}",
+                method_name, verification_error
+            );
+            if !reported_errors.insert((
+                method_name.to_string(),
+                verification_error.full_id.clone(),
+                verification_error.offending_pos_id.clone(),
+                verification_error.reason_pos_id.clone(),
+            )) {
+                debug!("The error was already reported");
+                continue;
+            }
             let mut prusti_error = error_manager.translate_verification_error(&verification_error);
+            let error_kind = error_manager
+                .get_error_context(&verification_error)
+                .map_or("Unknown", ErrorCtxt::kind);
 
             // annotate with counterexample, if requested
             if config::counterexample() {
@@ -162,7 +500,7 @@ impl<'v, 'tcx> Verifier<'v, 'tcx> {
                         } else {
                             prusti_error = prusti_error.add_note(
                                 format!(
-                                    "the verifier produced a counterexample for {method}, but it could not be mapped to source code"
+                                    "the verifier produced a counterexample for {method_name}, but it could not be mapped to source code"
                                 ),
                                 None,
                             );
@@ -179,7 +517,7 @@ impl<'v, 'tcx> Verifier<'v, 'tcx> {
                     } else {
                         prusti_error = prusti_error.add_note(
                             format!(
-                                "the verifier produced a counterexample for {method}, but it could not be mapped to source code"
+                                "the verifier produced a counterexample for {method_name}, but it could not be mapped to source code"
                             ),
                             None,
                         );
@@ -187,34 +525,112 @@ impl<'v, 'tcx> Verifier<'v, 'tcx> {
                 }
             }
 
-            prusti_errors.push(prusti_error);
+            if config::bmc() {
+                prusti_error = prusti_error.set_bounded().add_note(
+                    format!(
+                        "this error was found by bounded model checking, which only \
+                        verifies the executions that iterate each loop at most {} times",
+                        config::bmc_unroll_depth()
+                    ),
+                    None,
+                );
+            }
+
+            prusti_errors.push((prusti_error, error_kind));
         }
         prusti_errors.sort();
 
-        for prusti_error in prusti_errors {
+        for (prusti_error, error_kind) in prusti_errors {
             debug!("Prusti error: {:?}", prusti_error);
             if prusti_error.is_disabled() {
                 prusti_error.cancel();
             } else {
-                prusti_error.emit(&self.env.diagnostic);
+                emit(error_kind, prusti_error);
             }
-            result = VerificationResult::Failure;
         }
+        // A program whose errors were all reported before still failed.
+        if has_errors {
+            VerificationResult::Failure
+        } else {
+            VerificationResult::Success
+        }
+    }
 
-        if encoding_errors_count != 0 {
-            result = VerificationResult::Failure;
+    fn write_report(&self, report: &VerificationReport<'tcx>) {
+        let report_dir = config::report_dir().unwrap();
+        if let Err(error) = report.write(Path::new(&report_dir)) {
+            PrustiError::warning(
+                format!("could not write the verification report to {report_dir}: {error}"),
+                DUMMY_SP.into(),
+            )
+            .emit(&self.env.diagnostic);
         }
+    }
 
+    /// Print the slowest items of the profile and write all of them to the
+    /// log directory.
+    fn write_profile(&self, profile: &VerificationProfile) {
+        // A package can have a binary and a library crate with the same name.
+        let file_name = format!(
+            "{}-{}.txt",
+            self.env.name.local_crate_name(),
+            self.env.name.source_file_name()
+        );
+        log::report("profile", &file_name, profile.table(usize::MAX));
+        eprintln!(
+            "Slowest verified items (all items are listed in {}):\n{}",
+            config::log_dir().join("profile").join(&file_name).display(),
+            profile.table(config::profile_top_items())
+        );
+    }
+}
+
+/// Identifies a verification error of a program: the name of the program,
+/// the id of the error and the ids of its offending and reason positions.
+type ReportedErrorKey = (String, String, Option<String>, Option<String>);
+
+/// The result of verifying a program.
+struct ProgramResult {
+    program_name: String,
+    result: Result<viper::VerificationResult, ClientError>,
+    /// The time spent verifying the program, including the cache lookup.
+    verification_time: Duration,
+    /// Whether the result was found in the cache, if known.
+    cache_hit: Option<bool>,
+}
+
+/// A cache that records whether the result of a request was found in it.
+struct RecordingCache<'a, C> {
+    cache: C,
+    hit: &'a mut bool,
+}
+
+impl<C: Cache> Cache for RecordingCache<'_, C> {
+    fn get(&mut self, request: u64) -> Option<viper::VerificationResult> {
+        let result = self.cache.get(request);
+        *self.hit = result.is_some();
         result
     }
+
+    fn insert_from_backend(
+        self,
+        request: u64,
+        result: viper::VerificationResult,
+        backend: Option<VerificationBackend>,
+    ) -> Option<viper::VerificationResult> {
+        self.cache.insert_from_backend(request, result, backend)
+    }
 }
 
-/// Verify a list of programs.
-/// Returns a list of (program_name, verification_result) tuples.
+/// Verify a list of programs, each with the configuration overrides given by
+/// `program_config` for its name.
+/// Returns a stream that yields the result of each program as soon as it is
+/// available, or an error if the server could not be connected to.
 fn verify_programs(
     env: &Environment,
     programs: Vec<Program>,
-) -> Vec<(String, viper::VerificationResult)> {
+    program_config: impl Fn(&str) -> config::ItemConfig,
+) -> Result<impl Stream<Item = ProgramResult>, ClientError> {
     let source_path = env.name.source_path();
     let rust_program_name = source_path
         .file_name()
@@ -222,24 +638,38 @@ fn verify_programs(
         .to_str()
         .unwrap()
         .to_owned();
-    let verification_requests = programs.into_iter().map(|mut program| {
-        let program_name = program.get_name().to_string();
-        let check_mode = program.get_check_mode();
-        // Prepend the Rust file name to the program.
-        program.set_name(format!("{rust_program_name}_{program_name}"));
-        let backend = if check_mode == CheckMode::Specifications {
-            config::verify_specifications_backend()
-        } else {
-            config::viper_backend()
-        }
-        .parse()
-        .unwrap();
-        let request = VerificationRequest {
-            program,
-            backend_config: ViperBackendConfig::new(backend),
-        };
-        (program_name, request)
-    });
+    let verification_requests: Vec<_> = programs
+        .into_iter()
+        .map(|mut program| {
+            let program_name = program.get_name().to_string();
+            let check_mode = program.get_check_mode();
+            // Prepend the Rust file name to the program.
+            program.set_name(format!("{rust_program_name}_{program_name}"));
+            let backend = if check_mode == CheckMode::Specifications {
+                config::verify_specifications_backend()
+            } else {
+                config::viper_backend()
+            };
+            let (backend_config, portfolio) =
+                config::with_item_config(&program_config(&program_name), || {
+                    if backend == "portfolio" {
+                        (
+                            ViperBackendConfig::new(VerificationBackend::Silicon),
+                            vec![ViperBackendConfig::new(VerificationBackend::Carbon)],
+                        )
+                    } else {
+                        (ViperBackendConfig::new(backend.parse().unwrap()), vec![])
+                    }
+                });
+            let request = VerificationRequest {
+                program,
+                backend_config,
+                portfolio,
+                timeout: config::request_timeout().map(Duration::from_secs),
+            };
+            (program_name, request)
+        })
+        .collect();
     if let Some(server_address) = config::server_address() {
         let server_address = if server_address == "MOCK" {
             spawn_server_thread().to_string()
@@ -247,39 +677,98 @@ fn verify_programs(
             server_address
         };
         info!("Connecting to Prusti server at {}", server_address);
-        let client = PrustiClient::new(&server_address).unwrap_or_else(|error| {
-            panic!("Could not parse server address ({server_address}) due to {error:?}")
-        });
-        // Here we construct a Tokio runtime to block until completion of the futures returned by
-        // `client.verify`. However, to report verification errors as early as possible,
-        // `verify_programs` should return an asynchronous stream of verification results.
-        let runtime = Builder::new_current_thread()
-            .thread_name("prusti-viper")
-            .enable_all()
-            .build()
-            .expect("failed to construct Tokio runtime");
-        verification_requests
-            .map(|(program_name, request)| {
-                let remote_result = runtime.block_on(client.verify(request));
-                let result = remote_result.unwrap_or_else(|error| {
-                    panic!("Verification request of program {program_name} failed: {error:?}")
-                });
-                (program_name, result)
+        let client = PrustiClient::new(&server_address)?;
+        Ok(stream::iter(verification_requests)
+            .then(move |(program_name, request)| {
+                let client = client.clone();
+                let span = tracing::info_span!(
+                    target: tracing::PHASE_TARGET,
+                    "verification request",
+                    program = %program_name
+                );
+                async move {
+                    let start = Instant::now();
+                    let result = client.verify(request).instrument(span).await;
+                    ProgramResult {
+                        program_name,
+                        result,
+                        verification_time: start.elapsed(),
+                        // The server doesn't tell whether the result was cached.
+                        cache_hit: None,
+                    }
+                }
             })
-            .collect()
+            .left_stream())
     } else {
-        let mut stopwatch = Stopwatch::start("prusti-viper", "JVM startup");
-        stopwatch.start_next("attach current thread to the JVM");
-        let viper =
-            Lazy::new(|| Viper::new_with_args(&config::viper_home(), config::extra_jvm_args()));
-        let viper_thread = Lazy::new(|| viper.attach_current_thread());
-        stopwatch.finish();
-        let mut cache = PersistentCache::load_cache(config::cache_path());
-        verification_requests
-            .map(|(program_name, request)| {
-                let result = process_verification_request(&viper_thread, request, &mut cache);
-                (program_name, result)
+        // The JVM is not thread safe, so all the requests are verified on a
+        // dedicated thread that sends back each result as soon as it is known.
+        let (sender, receiver) = mpsc::unbounded();
+        let uses_viper = verification_requests
+            .iter()
+            .any(|(_, request)| request.backend_config.backend != VerificationBackend::SmtLib);
+        let verification_thread = thread::Builder::new()
+            .name("prusti-viper-verification".to_string())
+            .spawn(move || {
+                let mut stopwatch = Stopwatch::start("prusti-viper", "JVM startup");
+                stopwatch.start_next("attach current thread to the JVM");
+                let viper = Lazy::new(|| {
+                    let _span =
+                        tracing::info_span!(target: tracing::PHASE_TARGET, "JVM startup").entered();
+                    Viper::new_with_args(&config::viper_home(), config::extra_jvm_args())
+                });
+                let viper_thread = Lazy::new(|| viper.attach_current_thread());
+                if config::profile_verification() && uses_viper {
+                    // Don't count the JVM startup in the verification time of
+                    // the first program.
+                    Lazy::force(&viper_thread);
+                }
+                stopwatch.finish();
+                let mut cache = PersistentCache::load_cache(config::cache_path());
+                cache.set_max_entries(config::cache_max_entries());
+                let remote_cache = config::remote_cache_address().map(|address| {
+                    info!("Using the remote cache at {}", address);
+                    RemoteCacheClient::new(&address).unwrap_or_else(|error| {
+                        panic!("Could not parse remote cache address ({address}) due to {error:?}")
+                    })
+                });
+                for (program_name, request) in verification_requests {
+                    let start = Instant::now();
+                    let mut cache_hit = false;
+                    let result = if let Some(remote_cache) = &remote_cache {
+                        let cache = RecordingCache {
+                            cache: RemoteCache::new(remote_cache, &mut cache),
+                            hit: &mut cache_hit,
+                        };
+                        process_verification_request(&viper_thread, request, cache)
+                    } else {
+                        let cache = RecordingCache {
+                            cache: &mut cache,
+                            hit: &mut cache_hit,
+                        };
+                        process_verification_request(&viper_thread, request, cache)
+                    };
+                    let program_result = ProgramResult {
+                        program_name,
+                        result: Ok(result),
+                        verification_time: start.elapsed(),
+                        cache_hit: Some(cache_hit),
+                    };
+                    if sender.unbounded_send(program_result).is_err() {
+                        // The receiver is gone, so nobody is interested in the
+                        // remaining results.
+                        break;
+                    }
+                }
             })
-            .collect()
+            .expect("failed to spawn the verification thread");
+        // Once all the results have been received, wait for the thread to
+        // finish (e.g. saving the cache) and propagate its panics, if any.
+        let join_verification_thread = stream::once(async move {
+            if let Err(panic) = verification_thread.join() {
+                panic::resume_unwind(panic);
+            }
+        })
+        .filter_map(|()| future::ready(None));
+        Ok(receiver.chain(join_verification_thread).right_stream())
     }
 }
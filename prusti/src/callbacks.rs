@@ -17,10 +17,38 @@ use prusti_rustc_interface::{
         ty::TyCtxt,
     },
     session::Session,
+    span::Symbol,
 };
+use std::env;
+use tracing::{dispatcher, info_span, Span};
 
 #[derive(Default)]
-pub struct PrustiCompilerCalls;
+pub struct PrustiCompilerCalls {
+    /// The phase of the compiler that runs until the next callback.
+    compiler_phase: Option<CompilerPhase>,
+}
+
+/// A span of the Chrome trace covering the work done by the compiler between
+/// two callbacks. Unlike the guard of an entered span, it can be kept in the
+/// callbacks, which must be `Send`.
+struct CompilerPhase(Span);
+
+impl CompilerPhase {
+    fn enter(span: Span) -> Self {
+        if let Some(id) = span.id() {
+            dispatcher::get_default(|dispatch| dispatch.enter(&id));
+        }
+        CompilerPhase(span)
+    }
+}
+
+impl Drop for CompilerPhase {
+    fn drop(&mut self) {
+        if let Some(id) = self.0.id() {
+            dispatcher::get_default(|dispatch| dispatch.exit(&id));
+        }
+    }
+}
 
 // Running `get_body_with_borrowck_facts` can be very slow, therefore we avoid it when not
 // necessary; for crates which won't be verified or spec_fns it suffices to load just the fn body
@@ -72,6 +100,17 @@ fn mir_promoted<'tcx>(
     result
 }
 
+/// Record the environment variables selecting the procedures to verify in the
+/// dep-info file, so that cargo verifies the crate again when they change (e.g.
+/// with a different `cargo prusti --only` pattern) even if its sources did not.
+fn track_item_selection(session: &Session) {
+    let mut env_depinfo = session.parse_sess.env_depinfo.borrow_mut();
+    for var in ["PRUSTI_VERIFY_ONLY_ITEMS", "PRUSTI_VERIFY_SKIP_ITEMS"] {
+        let value = env::var(var).ok().map(|value| Symbol::intern(&value));
+        env_depinfo.insert((Symbol::intern(var), value));
+    }
+}
+
 impl prusti_rustc_interface::driver::Callbacks for PrustiCompilerCalls {
     fn config(&mut self, config: &mut Config) {
         assert!(config.override_queries.is_none());
@@ -81,6 +120,10 @@ impl prusti_rustc_interface::driver::Callbacks for PrustiCompilerCalls {
                 providers.mir_promoted = mir_promoted;
             },
         );
+        self.compiler_phase = Some(CompilerPhase::enter(info_span!(
+            target: tracing::PHASE_TARGET,
+            "rustc parsing and expansion"
+        )));
     }
     #[tracing::instrument(level = "debug", skip_all)]
     fn after_expansion<'tcx>(
@@ -88,6 +131,7 @@ impl prusti_rustc_interface::driver::Callbacks for PrustiCompilerCalls {
         compiler: &Compiler,
         queries: &'tcx Queries<'tcx>,
     ) -> Compilation {
+        self.compiler_phase = None;
         if compiler.session().is_rust_2015() {
             compiler
                 .session()
@@ -99,6 +143,7 @@ impl prusti_rustc_interface::driver::Callbacks for PrustiCompilerCalls {
                 .emit();
         }
         compiler.session().abort_if_errors();
+        track_item_selection(compiler.session());
         if config::print_desugared_specs() {
             // based on the implementation of rustc_driver::pretty::print_after_parsing
             queries.global_ctxt().unwrap().enter(|tcx| {
@@ -128,6 +173,10 @@ impl prusti_rustc_interface::driver::Callbacks for PrustiCompilerCalls {
                 );
             });
         }
+        self.compiler_phase = Some(CompilerPhase::enter(info_span!(
+            target: tracing::PHASE_TARGET,
+            "rustc analysis"
+        )));
         Compilation::Continue
     }
 
@@ -137,9 +186,12 @@ impl prusti_rustc_interface::driver::Callbacks for PrustiCompilerCalls {
         compiler: &Compiler,
         queries: &'tcx Queries<'tcx>,
     ) -> Compilation {
+        self.compiler_phase = None;
         compiler.session().abort_if_errors();
         queries.global_ctxt().unwrap().enter(|tcx| {
             let mut env = Environment::new(tcx, env!("CARGO_PKG_VERSION"));
+            let spec_collection_span =
+                info_span!(target: tracing::PHASE_TARGET, "spec collection").entered();
             let spec_checker = specs::checker::SpecChecker::new();
             spec_checker.check(&env);
             compiler.session().abort_if_errors();
@@ -156,6 +208,7 @@ impl prusti_rustc_interface::driver::Callbacks for PrustiCompilerCalls {
                 }
             }
             CrossCrateSpecs::import_export_cross_crate(&mut env, &mut def_spec);
+            drop(spec_collection_span);
             if !config::no_verify() {
                 verify(env, def_spec);
             }
@@ -21,9 +21,15 @@ use prusti_rustc_interface::{
     driver, errors,
     session::{self, EarlyErrorHandler},
 };
-use std::env;
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
 use tracing_chrome::{ChromeLayerBuilder, FlushGuard};
-use tracing_subscriber::{filter::EnvFilter, prelude::*};
+use tracing_subscriber::{
+    filter::{filter_fn, EnvFilter},
+    prelude::*,
+};
 
 /// Link to report Prusti bugs
 const BUG_REPORT_URL: &str = "https://github.com/viperproject/prusti-dev/issues/new";
@@ -38,11 +44,51 @@ fn get_prusti_version_info() -> String {
     )
 }
 
+/// The file of the Chrome trace of the crate compiled with `rustc_args`, if
+/// they compile a crate (e.g. `cargo` also uses `-vV` to query the version).
+fn chrome_trace_path(rustc_args: &[String]) -> Option<PathBuf> {
+    let source_file = rustc_args.iter().skip(1).find(|arg| arg.ends_with(".rs"))?;
+    let crate_name = match arg_value(rustc_args, "--crate-name", |_| true) {
+        Some(crate_name) => crate_name.to_string(),
+        None => Path::new(source_file)
+            .file_stem()?
+            .to_string_lossy()
+            .into_owned(),
+    };
+    // A package can have a binary and a library crate with the same name.
+    let crate_type = arg_value(rustc_args, "--crate-type", |_| true).unwrap_or("bin");
+    Some(
+        config::log_dir()
+            .join("chrome_trace")
+            .join(format!("{crate_name}-{crate_type}.json")),
+    )
+}
+
 /// Initialize Prusti and the Rust compiler loggers.
-fn init_loggers() -> Option<FlushGuard> {
+fn init_loggers(rustc_args: &[String]) -> Vec<FlushGuard> {
+    let mut guards = vec![];
+    // Only the spans of the phases of Prusti go to the Chrome trace.
+    let trace_path = if config::chrome_trace() {
+        chrome_trace_path(rustc_args)
+    } else {
+        None
+    };
+    let phase_layer = trace_path.map(|path| {
+        std::fs::create_dir_all(path.parent().unwrap())
+            .expect("failed to create the Chrome trace directory");
+        let (chrome_layer, guard) = ChromeLayerBuilder::new()
+            .file(path)
+            .include_args(true)
+            .build();
+        guards.push(guard);
+        chrome_layer.with_filter(filter_fn(|metadata| {
+            metadata.target() == tracing::PHASE_TARGET
+        }))
+    });
+
     // TODO: The `config::log() != ""` here is very bad; it makes us ignore the `log_tracing` flag
     // It's enabled so that we only create a `trace.json` file if the user has explicitly requested logging
-    let guard = if config::log_tracing() && !config::log().is_empty() {
+    if config::log_tracing() && !config::log().is_empty() {
         let log_dir = config::log_dir();
         std::fs::create_dir_all(&log_dir).expect("failed to create log directory");
         let filter = EnvFilter::new(config::log());
@@ -50,25 +96,31 @@ fn init_loggers() -> Option<FlushGuard> {
             .file(log_dir.join("trace.json"))
             .include_args(true)
             .build();
+        guards.push(guard);
         tracing_subscriber::registry()
-            .with(filter)
-            .with(chrome_layer)
+            .with(chrome_layer.with_filter(filter))
+            .with(phase_layer)
             .init();
-        Some(guard)
     } else {
         env_logger::init_from_env(
             env_logger::Env::new()
                 .filter_or("PRUSTI_LOG", config::log())
                 .write_style_or("PRUSTI_LOG_STYLE", config::log_style()),
         );
-        None
-    };
+        if let Some(phase_layer) = phase_layer {
+            // Unlike `init`, this leaves the `log` records to `env_logger`.
+            tracing::subscriber::set_global_default(
+                tracing_subscriber::registry().with(phase_layer),
+            )
+            .expect("failed to set up the Chrome trace");
+        }
+    }
 
     let error_handler = EarlyErrorHandler::new(session::config::ErrorOutputType::HumanReadable(
         errors::emitter::HumanReadableErrorType::Default(errors::emitter::ColorConfig::Auto),
     ));
     prusti_rustc_interface::driver::init_rustc_env_logger(&error_handler);
-    guard
+    guards
 }
 
 fn main() {
@@ -95,7 +147,7 @@ fn main() {
 
     // Initialize Prusti and the Rust compiler loggers.
     // This must be done after the build script check, otherwise Tokio's global tracing will fail.
-    let _log_flush_guard = init_loggers();
+    let log_flush_guards = init_loggers(&original_rustc_args);
 
     // This environment variable will not be set when building dependencies.
     let is_primary_package = env::var("CARGO_PRIMARY_PACKAGE").is_ok();
@@ -185,7 +237,7 @@ fn main() {
             ));
         }
 
-        let mut callbacks = PrustiCompilerCalls;
+        let mut callbacks = PrustiCompilerCalls::default();
 
         driver::RunCompiler::new(&rustc_args, &mut callbacks).run()
     });
@@ -200,5 +252,7 @@ fn main() {
         );
     }
 
+    // `exit` doesn't run destructors, but the traces must be flushed.
+    drop(log_flush_guards);
     std::process::exit(exit_code)
 }
@@ -3,7 +3,7 @@
 use log::{debug, warn};
 use prusti_common::{config, report::user};
 use prusti_interface::{
-    data::{VerificationResult, VerificationTask},
+    data::{ItemSelection, VerificationResult, VerificationTask},
     environment::Environment,
     specs::typed,
 };
@@ -17,7 +17,15 @@ pub fn verify(env: Environment<'_>, def_spec: typed::DefSpecificationMap) {
         debug!("Prepare verification task...");
         // TODO: can we replace `get_annotated_procedures` with information
         // that is already in `def_spec`?
-        let (annotated_procedures, types) = env.get_annotated_procedures_and_types();
+        let (mut annotated_procedures, types) = env.get_annotated_procedures_and_types();
+        let selection = ItemSelection::from_config(&env.name.local_crate_name());
+        if !selection.selects_all() {
+            annotated_procedures.retain(|&proc_id| {
+                // Closures are verified together with their enclosing item.
+                let item_id = env.tcx().typeck_root_def_id(proc_id);
+                selection.is_selected(&env.name.get_unique_item_name(item_id))
+            });
+        }
         let verification_task = VerificationTask {
             procedures: annotated_procedures,
             types,
@@ -64,6 +72,13 @@ pub fn verify(env: Environment<'_>, def_spec: typed::DefSpecificationMap) {
                         "Verification result is inconclusive because errors \
                                        were encountered during encoding.",
                     );
+                } else if config::bmc() {
+                    user::message(format!(
+                        "Successful bounded verification of {} items: no error was found \
+                        in the executions that iterate each loop at most {} times",
+                        verification_task.procedures.len(),
+                        config::bmc_unroll_depth()
+                    ));
                 } else {
                     user::message(format!(
                         "Successful verification of {} items",
@@ -6,3 +6,9 @@
 
 pub use proc_macro_tracing::instrument;
 pub use tracing::{instrument as tracing_instrument, *};
+
+/// The target of the spans of the phases of Prusti and of the work done for
+/// each item, which are written to a Chrome trace when the `chrome_trace`
+/// configuration flag is enabled. For example:
+/// `info_span!(target: tracing::PHASE_TARGET, "encode procedure", item = %name)`.
+pub const PHASE_TARGET: &str = "prusti_phase";
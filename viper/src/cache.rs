@@ -4,46 +4,195 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use log::{error, info, warn};
+use log::{debug, error, info, warn};
 
-use crate::verification_result::VerificationResult;
-use rustc_hash::FxHashMap;
+use crate::{verification_backend::VerificationBackend, verification_result::VerificationResult};
+use fs2::FileExt;
+use rustc_hash::{FxHashMap, FxHashSet};
 use std::{
     fs, io,
-    ops::DerefMut,
     path::{Path, PathBuf},
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
+use uuid::Uuid;
 
 pub trait Cache {
-    fn get(&self, request: u64) -> Option<VerificationResult>;
-    fn insert(self, request: u64, result: VerificationResult) -> Option<VerificationResult>;
+    fn get(&mut self, request: u64) -> Option<VerificationResult>;
+    fn insert(self, request: u64, result: VerificationResult) -> Option<VerificationResult>
+    where
+        Self: Sized,
+    {
+        self.insert_from_backend(request, result, None)
+    }
+    /// Insert a result, recording which backend of a portfolio produced it.
+    fn insert_from_backend(
+        self,
+        request: u64,
+        result: VerificationResult,
+        backend: Option<VerificationBackend>,
+    ) -> Option<VerificationResult>;
 }
 // We can change the representation here (e.g. adding fields):
 #[derive(Debug, Clone)]
 pub struct PersistentCache {
     updated: bool,
     load_loc: PathBuf,
-    data: FxHashMap<u64, VerificationResult>,
+    data: FxHashMap<u64, CacheEntry>,
+    /// The maximum number of entries to keep. When exceeded, the least
+    /// recently used entries are evicted.
+    max_entries: Option<usize>,
+    /// The entries that have been explicitly removed, and which should
+    /// therefore not be restored when merging with the cache file on disk.
+    removed: FxHashSet<u64>,
+    /// Whether the cache has been cleared, in which case the content of the
+    /// cache file on disk is discarded when saving.
+    cleared: bool,
+    hits: u64,
+    misses: u64,
+    /// The hits and misses that have already been saved to disk.
+    saved_hits: u64,
+    saved_misses: u64,
 }
 
-const RESULT_CACHE_VERSION: u64 = 4;
+/// The version of the format of the cache file and of the cached results.
+pub const RESULT_CACHE_VERSION: u64 = 7;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct CacheEntry {
+    result: VerificationResult,
+    /// The backend that produced the result, when the request was verified
+    /// by a portfolio of backends.
+    backend: Option<VerificationBackend>,
+    /// Nanoseconds since the UNIX epoch of the last time this entry was stored
+    /// or looked up. The accesses made by a process have distinct stamps, so
+    /// that the least recently used entry is well defined.
+    last_access: u64,
+}
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 struct ResultCache {
-    data: FxHashMap<u64, VerificationResult>,
+    data: FxHashMap<u64, CacheEntry>,
+    hits: u64,
+    misses: u64,
     version: u64,
 }
 
+/// Statistics about a `PersistentCache`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct CacheStatistics {
+    pub entries: usize,
+    pub max_entries: Option<usize>,
+    pub hits: u64,
+    pub misses: u64,
+    /// Seconds since the UNIX epoch of the least recent access to an entry.
+    pub oldest_access: Option<u64>,
+    /// Seconds since the UNIX epoch of the most recent access to an entry.
+    pub newest_access: Option<u64>,
+}
+
+impl CacheStatistics {
+    /// The ratio of lookups that were hits, if there were any lookups.
+    pub fn hit_ratio(&self) -> Option<f64> {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            None
+        } else {
+            Some(self.hits as f64 / lookups as f64)
+        }
+    }
+}
+
+impl std::fmt::Display for CacheStatistics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "entries: {}", self.entries)?;
+        match self.max_entries {
+            Some(max_entries) => writeln!(f, "max entries: {max_entries}")?,
+            None => writeln!(f, "max entries: unlimited")?,
+        }
+        writeln!(f, "hits: {}", self.hits)?;
+        writeln!(f, "misses: {}", self.misses)?;
+        match self.hit_ratio() {
+            Some(ratio) => write!(f, "hit ratio: {:.1}%", ratio * 100.0),
+            None => write!(f, "hit ratio: -"),
+        }
+    }
+}
+
+/// The most recent access stamp handed out by `now`.
+static LAST_ACCESS: AtomicU64 = AtomicU64::new(0);
+
+/// An access stamp in nanoseconds since the UNIX epoch that is strictly
+/// greater than all the stamps previously returned in this process, even if
+/// the clock is coarse or goes backwards.
+fn now() -> u64 {
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos() as u64)
+        .unwrap_or_default();
+    let previous = LAST_ACCESS
+        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |last| {
+            Some(time.max(last + 1))
+        })
+        .unwrap();
+    time.max(previous + 1)
+}
+
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+/// Keep only the `max_entries` most recently used entries.
+fn evict_least_recently_used(data: &mut FxHashMap<u64, CacheEntry>, max_entries: usize) {
+    if data.len() <= max_entries {
+        return;
+    }
+    let mut last_accesses: Vec<_> = data.values().map(|entry| entry.last_access).collect();
+    last_accesses.sort_unstable_by(|a, b| b.cmp(a));
+    let threshold = last_accesses[max_entries];
+    // Entries accessed at the threshold time are evicted in arbitrary order.
+    let mut to_keep_at_threshold = last_accesses[..max_entries]
+        .iter()
+        .filter(|&&last_access| last_access == threshold)
+        .count();
+    let evicted = data.len() - max_entries;
+    data.retain(|_, entry| {
+        if entry.last_access > threshold {
+            true
+        } else if entry.last_access == threshold && to_keep_at_threshold > 0 {
+            to_keep_at_threshold -= 1;
+            true
+        } else {
+            false
+        }
+    });
+    debug!("Evicted {evicted} cache entries");
+}
+
 impl From<(PathBuf, ResultCache)> for PersistentCache {
     /// Used when loading cache from disk
     fn from((load_loc, rc): (PathBuf, ResultCache)) -> Self {
-        assert_eq!(rc.version, RESULT_CACHE_VERSION);
-        let data = rc.data;
+        let (data, hits, misses) = if rc.version == RESULT_CACHE_VERSION {
+            (rc.data, rc.hits, rc.misses)
+        } else {
+            warn!(
+                "Discarding cache with version {} (expected {})",
+                rc.version, RESULT_CACHE_VERSION
+            );
+            (FxHashMap::default(), 0, 0)
+        };
         PersistentCache {
             updated: false,
             load_loc,
             data,
+            max_entries: None,
+            removed: FxHashSet::default(),
+            cleared: false,
+            hits,
+            misses,
+            saved_hits: hits,
+            saved_misses: misses,
         }
     }
 }
@@ -52,6 +201,8 @@ impl From<&PersistentCache> for ResultCache {
     fn from(cache: &PersistentCache) -> Self {
         ResultCache {
             data: cache.data.clone(),
+            hits: cache.hits,
+            misses: cache.misses,
             version: RESULT_CACHE_VERSION,
         }
     }
@@ -62,24 +213,11 @@ impl PersistentCache {
     pub fn load_cache(cache_loc: PathBuf) -> Self {
         let mut data_res: Option<ResultCache> = None;
         if !cache_loc.as_os_str().is_empty() {
-            if let Ok(f) = fs::File::open(&cache_loc) {
-                match bincode::deserialize_from::<_, ResultCache>(&mut io::BufReader::new(f)) {
-                    Ok(data) => {
-                        if data.version == RESULT_CACHE_VERSION {
-                            info!("Loaded cache from \"{}\"", cache_loc.display());
-                            data_res = Some(data);
-                        } else {
-                            error!(
-                                "Cache version mismatch when reading from \"{}\": \
-                                    expected={} actual={}",
-                                cache_loc.display(),
-                                RESULT_CACHE_VERSION,
-                                data.version
-                            )
-                        }
-                    }
-                    Err(e) => error!("Failed to read cache from \"{}\": {e}", cache_loc.display()),
-                }
+            // The cache file is always replaced atomically, so there is no need
+            // to hold the lock while reading it.
+            data_res = Self::read_cache_file(&cache_loc);
+            if data_res.is_some() {
+                info!("Loaded cache from \"{}\"", cache_loc.display());
             }
         }
         PersistentCache::from((
@@ -88,29 +226,275 @@ impl PersistentCache {
                 info!("Cache file doesn't exist or is invalid. Using fresh cache.");
                 ResultCache {
                     data: FxHashMap::default(),
+                    hits: 0,
+                    misses: 0,
                     version: RESULT_CACHE_VERSION,
                 }
             }),
         ))
     }
-    #[tracing::instrument(level = "debug")]
+
+    /// Limit the number of entries of the cache. When the limit is exceeded,
+    /// the least recently used entries are evicted.
+    pub fn set_max_entries(&mut self, max_entries: Option<usize>) {
+        self.max_entries = max_entries;
+        if let Some(max_entries) = max_entries {
+            if self.data.len() > max_entries {
+                let old_keys: Vec<_> = self.data.keys().copied().collect();
+                evict_least_recently_used(&mut self.data, max_entries);
+                self.removed.extend(
+                    old_keys
+                        .into_iter()
+                        .filter(|key| !self.data.contains_key(key)),
+                );
+                self.updated = true;
+            }
+        }
+    }
+
+    pub fn get(&mut self, request: u64) -> Option<VerificationResult> {
+        if let Some(entry) = self.data.get_mut(&request) {
+            // A hit also updates the access time, which needs to be saved.
+            self.updated = true;
+            self.hits += 1;
+            entry.last_access = now();
+            Some(entry.result.clone())
+        } else {
+            self.misses += 1;
+            None
+        }
+    }
+
+    pub fn insert(
+        &mut self,
+        request: u64,
+        result: VerificationResult,
+    ) -> Option<VerificationResult> {
+        self.insert_from_backend(request, result, None)
+    }
+
+    /// Like `insert`, but also records which backend of a portfolio produced
+    /// the result.
+    pub fn insert_from_backend(
+        &mut self,
+        request: u64,
+        result: VerificationResult,
+        backend: Option<VerificationBackend>,
+    ) -> Option<VerificationResult> {
+        self.updated = true;
+        self.removed.remove(&request);
+        let old_entry = self.data.insert(
+            request,
+            CacheEntry {
+                result,
+                backend,
+                last_access: now(),
+            },
+        );
+        if let Some(max_entries) = self.max_entries {
+            if self.data.len() > max_entries {
+                // The inserted entry is the most recently used one, unless
+                // an entry loaded from disk has a stamp in the future.
+                let least_recently_used = self
+                    .data
+                    .iter()
+                    .filter(|(&key, _)| key != request)
+                    .min_by_key(|(_, entry)| entry.last_access)
+                    .map(|(&key, _)| key);
+                if let Some(key) = least_recently_used {
+                    debug!("Evicting least recently used cache entry {key}");
+                    self.data.remove(&key);
+                    self.removed.insert(key);
+                }
+            }
+        }
+        old_entry.map(|entry| entry.result)
+    }
+
+    /// The backend of a portfolio that produced the cached result of the
+    /// request, if any.
+    pub fn backend(&self, request: u64) -> Option<VerificationBackend> {
+        self.data.get(&request).and_then(|entry| entry.backend)
+    }
+
+    pub fn statistics(&self) -> CacheStatistics {
+        CacheStatistics {
+            entries: self.data.len(),
+            max_entries: self.max_entries,
+            hits: self.hits,
+            misses: self.misses,
+            oldest_access: self
+                .data
+                .values()
+                .map(|entry| entry.last_access / NANOS_PER_SEC)
+                .min(),
+            newest_access: self
+                .data
+                .values()
+                .map(|entry| entry.last_access / NANOS_PER_SEC)
+                .max(),
+        }
+    }
+
+    /// Remove the entries that have not been accessed for longer than
+    /// `max_age`. Returns the number of removed entries.
+    pub fn prune(&mut self, max_age: Duration) -> usize {
+        let max_age = u64::try_from(max_age.as_nanos()).unwrap_or(u64::MAX);
+        let threshold = now().saturating_sub(max_age);
+        let old_len = self.data.len();
+        let removed = &mut self.removed;
+        self.data.retain(|&key, entry| {
+            let keep = entry.last_access >= threshold;
+            if !keep {
+                removed.insert(key);
+            }
+            keep
+        });
+        let pruned = old_len - self.data.len();
+        if pruned > 0 {
+            self.updated = true;
+        }
+        pruned
+    }
+
+    /// Remove all entries and reset the statistics.
+    pub fn clear(&mut self) {
+        self.data.clear();
+        self.removed.clear();
+        self.hits = 0;
+        self.misses = 0;
+        self.saved_hits = 0;
+        self.saved_misses = 0;
+        self.cleared = true;
+        self.updated = true;
+    }
+
+    /// Read a cache file, returning `None` if the file does not exist, is
+    /// corrupted or has been written by an incompatible version of Prusti.
+    fn read_cache_file(cache_loc: &Path) -> Option<ResultCache> {
+        let f = fs::File::open(cache_loc).ok()?;
+        match bincode::deserialize_from::<_, ResultCache>(&mut io::BufReader::new(f)) {
+            Ok(data) if data.version == RESULT_CACHE_VERSION => Some(data),
+            Ok(data) => {
+                warn!(
+                    "Cache version mismatch when reading from \"{}\": \
+                        expected={} actual={}",
+                    cache_loc.display(),
+                    RESULT_CACHE_VERSION,
+                    data.version
+                );
+                None
+            }
+            Err(e) => {
+                warn!(
+                    "Ignoring invalid cache file \"{}\": {e}",
+                    cache_loc.display()
+                );
+                None
+            }
+        }
+    }
+
+    /// The path of the file used to serialize concurrent updates of the cache
+    /// file. A separate file is needed because the cache file itself is
+    /// replaced on each save.
+    fn lock_file_path(cache_loc: &Path) -> PathBuf {
+        let mut lock_loc = cache_loc.as_os_str().to_owned();
+        lock_loc.push(".lock");
+        PathBuf::from(lock_loc)
+    }
+
+    /// Save the cache to `cache_loc`, merging it with the entries saved in the
+    /// meantime by other processes. This is safe to be called concurrently by
+    /// multiple processes: the file is locked while it is updated, and the new
+    /// content is written to a temporary file that then atomically replaces
+    /// the old one.
+    #[tracing::instrument(level = "debug", skip(self))]
     pub fn save_cache(&self, cache_loc: &Path) {
-        match fs::File::create(cache_loc) {
-            Ok(f) => {
+        let lock_loc = Self::lock_file_path(cache_loc);
+        let lock_file = match fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .open(&lock_loc)
+        {
+            Ok(f) => f,
+            Err(e) => {
+                error!(
+                    "Failed to create cache lock file \"{}\": {e}",
+                    lock_loc.display()
+                );
+                return;
+            }
+        };
+        if let Err(e) = lock_file.lock_exclusive() {
+            error!(
+                "Failed to lock the cache file \"{}\": {e}",
+                lock_loc.display()
+            );
+            return;
+        }
+
+        let mut cache = ResultCache::from(self);
+        if self.cleared {
+            debug!("Discarding the cache entries on disk");
+        } else if let Some(on_disk) = Self::read_cache_file(cache_loc) {
+            debug!(
+                "Merging {} cache entries with the {} entries on disk",
+                cache.data.len(),
+                on_disk.data.len()
+            );
+            let mut data = on_disk.data;
+            data.retain(|key, _| !self.removed.contains(key));
+            for (key, entry) in cache.data {
+                match data.get(&key) {
+                    Some(on_disk_entry) if on_disk_entry.last_access > entry.last_access => {}
+                    _ => {
+                        data.insert(key, entry);
+                    }
+                }
+            }
+            cache.data = data;
+            cache.hits = on_disk.hits + (self.hits - self.saved_hits);
+            cache.misses = on_disk.misses + (self.misses - self.saved_misses);
+        }
+        if let Some(max_entries) = self.max_entries {
+            evict_least_recently_used(&mut cache.data, max_entries);
+        }
+
+        let tmp_loc = cache_loc.with_file_name(format!(
+            ".{}.{}.tmp",
+            cache_loc
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            Uuid::new_v4()
+        ));
+        match Self::write_cache_file(&tmp_loc, &cache) {
+            Ok(()) => {
                 info!("Saving cache to \"{}\"", cache_loc.display());
-                let mut cache_buffer = io::BufWriter::new(f);
-                bincode::serialize_into(&mut cache_buffer, &ResultCache::from(self))
-                    .unwrap_or_else(|e| error!("Failed to serialize the cache: {e}"));
-                match cache_buffer.into_inner() {
-                    Err(e) => error!("Failed to flush the cache file: {e}"),
-                    Ok(f) => f
-                        .sync_all()
-                        .unwrap_or_else(|e| error!("Failed to sync the cache file to disk: {e}")),
+                if let Err(e) = fs::rename(&tmp_loc, cache_loc) {
+                    error!("Failed to replace the cache file: {e}");
+                    let _ = fs::remove_file(&tmp_loc);
                 }
             }
-            Err(e) => error!("Failed to create cache file: {e}"),
+            Err(e) => {
+                error!("Failed to write the cache file: {e}");
+                let _ = fs::remove_file(&tmp_loc);
+            }
         }
+
+        lock_file
+            .unlock()
+            .unwrap_or_else(|e| error!("Failed to unlock the cache file: {e}"));
     }
+
+    fn write_cache_file(path: &Path, cache: &ResultCache) -> io::Result<()> {
+        let mut cache_buffer = io::BufWriter::new(fs::File::create(path)?);
+        bincode::serialize_into(&mut cache_buffer, cache)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        cache_buffer.into_inner()?.sync_all()
+    }
+
     pub fn save(&mut self) {
         // Save cache to disk, if changed and save path is valid
         if self.updated {
@@ -120,7 +504,14 @@ impl PersistentCache {
                 let mut save_dir = self.load_loc.clone();
                 save_dir.pop();
                 match fs::create_dir_all(&save_dir) {
-                    Ok(()) => self.save_cache(&self.load_loc),
+                    Ok(()) => {
+                        self.save_cache(&self.load_loc);
+                        self.updated = false;
+                        self.cleared = false;
+                        self.removed.clear();
+                        self.saved_hits = self.hits;
+                        self.saved_misses = self.misses;
+                    }
                     Err(e) => error!("Failed to create cache dir: {e}"),
                 }
             }
@@ -135,22 +526,31 @@ impl Drop for PersistentCache {
 }
 
 impl Cache for &mut PersistentCache {
-    fn get(&self, request: u64) -> Option<VerificationResult> {
-        self.data.get(&request).cloned()
+    fn get(&mut self, request: u64) -> Option<VerificationResult> {
+        PersistentCache::get(self, request)
     }
-    fn insert(self, request: u64, result: VerificationResult) -> Option<VerificationResult> {
-        self.updated = true;
-        self.data.insert(request, result)
+    fn insert_from_backend(
+        self,
+        request: u64,
+        result: VerificationResult,
+        backend: Option<VerificationBackend>,
+    ) -> Option<VerificationResult> {
+        PersistentCache::insert_from_backend(self, request, result, backend)
     }
 }
 
 impl Cache for &Arc<Mutex<PersistentCache>> {
-    fn get(&self, request: u64) -> Option<VerificationResult> {
+    fn get(&mut self, request: u64) -> Option<VerificationResult> {
         let mut cache = self.lock().unwrap();
-        cache.deref_mut().get(request)
+        cache.get(request)
     }
-    fn insert(self, request: u64, result: VerificationResult) -> Option<VerificationResult> {
+    fn insert_from_backend(
+        self,
+        request: u64,
+        result: VerificationResult,
+        backend: Option<VerificationBackend>,
+    ) -> Option<VerificationResult> {
         let mut cache = self.lock().unwrap();
-        cache.insert(request, result)
+        cache.insert_from_backend(request, result, backend)
     }
 }
@@ -23,6 +23,12 @@ impl VerificationResult {
     pub fn is_success(&self) -> bool {
         matches!(self, Self::Success)
     }
+
+    /// Whether the verifier could decide if the program verifies, as opposed
+    /// to e.g. crashing. Only conclusive results end a portfolio verification.
+    pub fn is_conclusive(&self) -> bool {
+        matches!(self, Self::Success | Self::Failure(_))
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
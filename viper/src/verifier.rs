@@ -56,6 +56,9 @@ impl<'a> Verifier<'a> {
                     VerificationBackend::Carbon => {
                         carbon::CarbonFrontendAPI::with(env).new(reporter)
                     }
+                    VerificationBackend::SmtLib => {
+                        unreachable!("the SMT-LIB backend does not run on Viper")
+                    }
                 }
             };
             let frontend_instance = jni.unwrap_result(unwrapped_frontend_instance);
@@ -0,0 +1,159 @@
+use rustc_hash::FxHashMap;
+use std::{env, fs, path::PathBuf, thread, time::Duration};
+use viper::*;
+
+/// A fresh cache path in a temporary directory.
+fn cache_path() -> PathBuf {
+    let dir = env::temp_dir().join(format!("prusti-cache-test-{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&dir).unwrap();
+    dir.join("cache.bin")
+}
+
+#[test]
+fn roundtrip() {
+    let path = cache_path();
+    let mut cache = PersistentCache::load_cache(path.clone());
+    cache.insert(1, VerificationResult::Success);
+    drop(cache);
+
+    let mut cache = PersistentCache::load_cache(path);
+    assert_eq!(cache.get(1), Some(VerificationResult::Success));
+    assert_eq!(cache.get(2), None);
+}
+
+#[test]
+fn corrupt_file_is_ignored() {
+    let path = cache_path();
+    fs::write(&path, b"this is not a cache").unwrap();
+
+    let mut cache = PersistentCache::load_cache(path.clone());
+    assert_eq!(cache.get(1), None);
+    cache.insert(1, VerificationResult::Success);
+    drop(cache);
+
+    let mut cache = PersistentCache::load_cache(path);
+    assert_eq!(cache.get(1), Some(VerificationResult::Success));
+}
+
+#[test]
+fn old_version_is_ignored() {
+    let path = cache_path();
+    let mut data = FxHashMap::default();
+    data.insert(1u64, VerificationResult::Success);
+    // The layout of version 4 of the cache file.
+    fs::write(&path, bincode::serialize(&(data, 4u64)).unwrap()).unwrap();
+
+    let mut cache = PersistentCache::load_cache(path);
+    assert_eq!(cache.get(1), None);
+}
+
+#[test]
+fn concurrent_updates_are_merged() {
+    let path = cache_path();
+    let handles: Vec<_> = (0..8)
+        .map(|request| {
+            let path = path.clone();
+            thread::spawn(move || {
+                let mut cache = PersistentCache::load_cache(path);
+                cache.insert(request, VerificationResult::Success);
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let mut cache = PersistentCache::load_cache(path);
+    for request in 0..8 {
+        assert_eq!(cache.get(request), Some(VerificationResult::Success));
+    }
+}
+
+#[test]
+fn least_recently_used_entries_are_evicted() {
+    let path = cache_path();
+    let mut cache = PersistentCache::load_cache(path.clone());
+    cache.set_max_entries(Some(2));
+    cache.insert(1, VerificationResult::Success);
+    cache.insert(2, VerificationResult::Success);
+    // Looking up the first entry makes the second one the least recently used.
+    assert!(cache.get(1).is_some());
+    cache.insert(3, VerificationResult::Success);
+    assert_eq!(cache.statistics().entries, 2);
+    assert!(cache.get(2).is_none());
+    cache.insert(4, VerificationResult::Success);
+    drop(cache);
+
+    let mut cache = PersistentCache::load_cache(path);
+    assert_eq!(cache.statistics().entries, 2);
+    assert!(cache.get(1).is_none());
+    assert!(cache.get(3).is_some());
+    assert!(cache.get(4).is_some());
+}
+
+#[test]
+fn misses_do_not_rewrite_the_cache_file() {
+    let path = cache_path();
+    let mut cache = PersistentCache::load_cache(path.clone());
+    cache.insert(1, VerificationResult::Success);
+    drop(cache);
+    let modified = fs::metadata(&path).unwrap().modified().unwrap();
+
+    let mut cache = PersistentCache::load_cache(path.clone());
+    assert!(cache.get(2).is_none());
+    drop(cache);
+    assert_eq!(fs::metadata(&path).unwrap().modified().unwrap(), modified);
+}
+
+#[test]
+fn statistics_count_hits_and_misses() {
+    let path = cache_path();
+    let mut cache = PersistentCache::load_cache(path.clone());
+    cache.insert(1, VerificationResult::Success);
+    assert!(cache.get(1).is_some());
+    assert!(cache.get(2).is_none());
+    drop(cache);
+
+    let mut cache = PersistentCache::load_cache(path.clone());
+    assert!(cache.get(1).is_some());
+    let statistics = cache.statistics();
+    assert_eq!((statistics.hits, statistics.misses), (2, 1));
+    drop(cache);
+
+    let statistics = PersistentCache::load_cache(path).statistics();
+    assert_eq!((statistics.hits, statistics.misses), (2, 1));
+}
+
+#[test]
+fn prune_and_clear() {
+    let path = cache_path();
+    let mut cache = PersistentCache::load_cache(path.clone());
+    cache.insert(1, VerificationResult::Success);
+    assert_eq!(cache.prune(Duration::from_secs(3600)), 0);
+    drop(cache);
+
+    let mut cache = PersistentCache::load_cache(path.clone());
+    cache.clear();
+    drop(cache);
+
+    let mut cache = PersistentCache::load_cache(path);
+    assert_eq!(cache.statistics().entries, 0);
+    assert_eq!(cache.get(1), None);
+}
+
+#[test]
+fn portfolio_backend_is_recorded() {
+    let path = cache_path();
+    let mut cache = PersistentCache::load_cache(path.clone());
+    cache.insert(1, VerificationResult::Success);
+    cache.insert_from_backend(
+        2,
+        VerificationResult::Success,
+        Some(VerificationBackend::Carbon),
+    );
+    drop(cache);
+
+    let cache = PersistentCache::load_cache(path);
+    assert_eq!(cache.backend(1), None);
+    assert_eq!(cache.backend(2), Some(VerificationBackend::Carbon));
+}
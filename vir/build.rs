@@ -1,6 +1,10 @@
-use std::{env, path::Path};
+use std::{env, fs, path::Path};
 use vir_gen::generate_vir;
 
+/// The directories containing the definitions of the VIR data structures
+/// that are sent to `prusti-server`.
+const FORMAT_SOURCES: &[&str] = &["defs", "src/legacy"];
+
 fn main() {
     println!("cargo:rerun-if-changed=build.rs");
     println!("cargo:rerun-if-changed=defs");
@@ -8,7 +12,37 @@ fn main() {
         let entry = entry.unwrap();
         println!("cargo:rerun-if-changed={}", entry.path().display());
     }
+    println!("cargo:rerun-if-changed=src/legacy");
 
     let out_dir = Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap()).join("gen");
     generate_vir(Path::new("defs"), &out_dir);
+
+    println!("cargo:rustc-env=VIR_FORMAT_HASH={:016x}", format_hash());
+}
+
+/// A hash of the sources defining the VIR data structures, which changes
+/// whenever their serialized format might have changed. This uses FNV-1a,
+/// because, unlike the standard library hasher, it is stable across Rust
+/// versions.
+fn format_hash() -> u64 {
+    let mut files = vec![];
+    for source in FORMAT_SOURCES {
+        for entry in walkdir::WalkDir::new(source) {
+            let entry = entry.unwrap();
+            if entry.file_type().is_file() {
+                files.push(entry.into_path());
+            }
+        }
+    }
+    files.sort();
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for file in files {
+        let path = file.to_string_lossy().replace('\\', "/");
+        let content = fs::read(&file).unwrap();
+        for byte in path.as_bytes().iter().chain(&content) {
+            hash ^= u64::from(*byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
 }
@@ -241,11 +241,34 @@ pub enum BuiltinFunc {
     MapContains,
     LookupMap,
     MapLen,
+    MapDomain,
     EmptySeq,
     SingleSeq,
     LookupSeq,
     ConcatSeq,
     SeqLen,
+    TakeSeq,
+    DropSeq,
+    SliceSeq,
+    UpdateSeq,
+    SeqContains,
+    /// The sequence of the elements of an array or a slice.
+    ToSeq,
+    EmptySet,
+    SingleSet,
+    UnionSet,
+    IntersectionSet,
+    DifferenceSet,
+    SetContains,
+    SetLen,
+    EmptyMultiSet,
+    SingleMultiSet,
+    UnionMultiSet,
+    IntersectionMultiSet,
+    DifferenceMultiSet,
+    MultiSetContains,
+    MultiSetCount,
+    MultiSetLen,
     NewInt,
     Index,
     Len,
@@ -13,6 +13,8 @@ impl WithIdentifier for ty::Type {
             ty::Type::Int(ty) => ty.get_identifier(),
             ty::Type::Sequence(ty) => ty.get_identifier(),
             ty::Type::Map(ty) => ty.get_identifier(),
+            ty::Type::Set(ty) => ty.get_identifier(),
+            ty::Type::MultiSet(ty) => ty.get_identifier(),
             ty::Type::Float(ty) => ty.get_identifier(),
             ty::Type::TypeVar(ty) => ty.get_identifier(),
             ty::Type::Tuple(ty) => ty.get_identifier(),
@@ -58,6 +60,18 @@ impl WithIdentifier for ty::Map {
     }
 }
 
+impl WithIdentifier for ty::Set {
+    fn get_identifier(&self) -> String {
+        format!("Set${}", self.element_type.get_identifier())
+    }
+}
+
+impl WithIdentifier for ty::MultiSet {
+    fn get_identifier(&self) -> String {
+        format!("MultiSet${}", self.element_type.get_identifier())
+    }
+}
+
 impl WithIdentifier for ty::Float {
     fn get_identifier(&self) -> String {
         self.to_string()
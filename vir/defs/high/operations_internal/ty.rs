@@ -176,6 +176,8 @@ impl Type {
     pub fn contains_type_variables(&self) -> bool {
         match self {
             Self::Sequence(Sequence { element_type, .. })
+            | Self::Set(Set { element_type, .. })
+            | Self::MultiSet(MultiSet { element_type, .. })
             | Self::Array(Array { element_type, .. })
             | Self::Slice(Slice { element_type, .. }) => element_type.is_type_var(),
             Self::Reference(Reference { target_type, .. })
@@ -200,6 +202,24 @@ impl Type {
             _ => false,
         }
     }
+    /// Whether the type is a set or a multiset, or has one as a component,
+    /// for example `&Set<u32>` or `Option<Multiset<u32>>`.
+    pub fn contains_sets(&self) -> bool {
+        struct Finder {
+            found: bool,
+        }
+        impl TypeWalker for Finder {
+            fn walk_set(&mut self, _set: &Set) {
+                self.found = true;
+            }
+            fn walk_multi_set(&mut self, _multi_set: &MultiSet) {
+                self.found = true;
+            }
+        }
+        let mut finder = Finder { found: false };
+        finder.walk_type(self);
+        finder.found
+    }
 }
 
 impl AsRef<str> for VariantIndex {
@@ -178,11 +178,18 @@ pub enum ContainerOpKind {
     SeqIndex,
     SeqConcat,
     SeqLen,
+    SeqTake,
+    SeqDrop,
+    SeqUpdate,
+    SeqContains,
     MapEmpty,
     MapUpdate,
     MapContains,
     MapLookup,
     MapLen,
+    /// The set of the keys of a map. Since the type of the result is not the
+    /// type of the map, `container_type` is the type of the resulting set.
+    MapDomain,
     SetEmpty,
     SetConstructor,
     SetUnion,
@@ -18,6 +18,8 @@ pub enum TypeDecl {
     Struct(Struct),
     Sequence(Sequence),
     Map(Map),
+    Set(Set),
+    MultiSet(MultiSet),
     Enum(Enum),
     Array(Array),
     Reference(Reference),
@@ -101,6 +103,20 @@ pub struct Map {
     pub val_type: Type,
 }
 
+#[display(fmt = "Set({})", element_type)]
+pub struct Set {
+    pub lifetimes: Vec<LifetimeConst>,
+    pub const_parameters: Vec<VariableDecl>,
+    pub element_type: Type,
+}
+
+#[display(fmt = "MultiSet({})", element_type)]
+pub struct MultiSet {
+    pub lifetimes: Vec<LifetimeConst>,
+    pub const_parameters: Vec<VariableDecl>,
+    pub element_type: Type,
+}
+
 #[display(fmt = "&{} {}", uniqueness, target_type)]
 pub struct Reference {
     pub lifetimes: Vec<LifetimeConst>,
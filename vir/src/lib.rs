@@ -16,6 +16,10 @@
 #[path = "../gen/mod.rs"]
 mod gen;
 
+/// A hash of the definitions of the VIR data structures. Two builds of Prusti
+/// can only exchange VIR programs if they agree on this hash.
+pub const FORMAT_HASH: &str = env!("VIR_FORMAT_HASH");
+
 pub mod common;
 pub mod converter;
 /// VIR that is as close to MIR as possible.
@@ -181,8 +181,12 @@ impl Typed for ContainerOp {
             ContainerOpKind::SeqEmpty
             | ContainerOpKind::SeqConstructor
             | ContainerOpKind::SeqConcat
+            | ContainerOpKind::SeqTake
+            | ContainerOpKind::SeqDrop
+            | ContainerOpKind::SeqUpdate
             | ContainerOpKind::MapEmpty
             | ContainerOpKind::MapUpdate
+            | ContainerOpKind::MapDomain
             | ContainerOpKind::SetEmpty
             | ContainerOpKind::SetConstructor
             | ContainerOpKind::SetUnion
@@ -193,15 +197,18 @@ impl Typed for ContainerOp {
             | ContainerOpKind::MultiSetUnion
             | ContainerOpKind::MultiSetIntersection
             | ContainerOpKind::MultiSetMinus => &self.container_type,
+            // The `in` operator of Viper returns the number of occurrences of
+            // the element in a multiset.
             ContainerOpKind::SeqLen
             | ContainerOpKind::MapLen
             | ContainerOpKind::SetCardinality
+            | ContainerOpKind::MultiSetContains
             | ContainerOpKind::MultiSetCardinality => &Type::Int,
-            ContainerOpKind::MapContains
+            ContainerOpKind::SeqContains
+            | ContainerOpKind::MapContains
             | ContainerOpKind::SetSubset
             | ContainerOpKind::SetContains
-            | ContainerOpKind::MultiSetSubset
-            | ContainerOpKind::MultiSetContains => &Type::Bool,
+            | ContainerOpKind::MultiSetSubset => &Type::Bool,
             ContainerOpKind::SeqIndex => match &self.container_type {
                 Type::Seq(ty::Seq { element_type, .. }) => element_type,
                 _ => unreachable!("Expected Seq type, got {:?}", self.container_type),